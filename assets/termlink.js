@@ -0,0 +1,78 @@
+// Hover cards and the glossary shortcut, written by `mdbook-termlink install`.
+//
+// Used with `tooltip = "popover"`: each term carries its rendered definition
+// in `data-termlink-definition`, shown here in place of the native tooltip.
+// Each linked chapter names the glossary page and the key opening it
+// (`glossary-shortcut`, "g" by default) in `data-termlink-glossary`.
+(function () {
+    "use strict";
+
+    var card = null;
+    var hideTimer = null;
+
+    function hide() {
+        clearTimeout(hideTimer);
+        if (card) {
+            card.remove();
+            card = null;
+        }
+    }
+
+    function hideSoon() {
+        clearTimeout(hideTimer);
+        hideTimer = setTimeout(hide, 150);
+    }
+
+    function show(term) {
+        hide();
+        card = document.createElement("div");
+        card.className = "termlink-popover";
+        card.setAttribute("role", "tooltip");
+        // The definition is HTML rendered from the book's own glossary
+        card.innerHTML = term.dataset.termlinkDefinition;
+        card.addEventListener("mouseenter", function () {
+            clearTimeout(hideTimer);
+        });
+        card.addEventListener("mouseleave", hideSoon);
+        document.body.appendChild(card);
+
+        var rect = term.getBoundingClientRect();
+        var maxLeft = document.documentElement.clientWidth - card.offsetWidth - 8;
+        card.style.left = Math.max(8, Math.min(rect.left, maxLeft)) + window.scrollX + "px";
+        card.style.top = rect.bottom + window.scrollY + 6 + "px";
+    }
+
+    document.querySelectorAll("[data-termlink-definition]").forEach(function (term) {
+        term.removeAttribute("title");
+        term.addEventListener("mouseenter", function () {
+            show(term);
+        });
+        term.addEventListener("focus", function () {
+            show(term);
+        });
+        term.addEventListener("mouseleave", hideSoon);
+        term.addEventListener("blur", hideSoon);
+    });
+
+    document.addEventListener("keydown", function (e) {
+        if (e.key === "Escape") {
+            hide();
+        }
+    });
+
+    var glossary = document.querySelector("[data-termlink-glossary]");
+    if (glossary) {
+        var key = glossary.dataset.termlinkShortcut || "g";
+        document.addEventListener("keydown", function (e) {
+            if (e.key !== key || e.ctrlKey || e.metaKey || e.altKey || e.defaultPrevented) {
+                return;
+            }
+            // Leave typing alone, as in mdBook's search box
+            var target = e.target;
+            if (target.isContentEditable || /^(INPUT|TEXTAREA|SELECT)$/.test(target.tagName)) {
+                return;
+            }
+            window.location.href = glossary.dataset.termlinkGlossary;
+        });
+    }
+})();
@@ -0,0 +1,101 @@
+//! The acronym list appended to the book (`generate-acronym-list`).
+
+use std::fmt::Write as _;
+
+use crate::glossary::Term;
+use crate::linker::GlossaryHrefs;
+
+/// Path of the acronym list chapter, relative to the `src` directory.
+pub const ACRONYM_LIST_PATH: &str = "acronyms.md";
+
+/// Writes the acronym list: a table of every term with a short form, sorted by it.
+///
+/// Each acronym links to its glossary entry, or to the term's own URL; an
+/// inline term without a URL is listed unlinked:
+///
+/// ```markdown
+/// # Acronyms
+///
+/// | Acronym | Meaning |
+/// | --- | --- |
+/// | [API](reference/glossary.html#api-application-programming-interface) | Application Programming Interface |
+/// ```
+///
+/// Returns `None` if no term has a short form.
+pub fn acronym_list(terms: &[Term], hrefs: &GlossaryHrefs, title: &str) -> Option<String> {
+    let mut acronyms: Vec<(&str, &str, Option<String>)> = terms
+        .iter()
+        .filter_map(|term| {
+            let short = term.short_name()?;
+            let long = term.long_name()?;
+            Some((
+                short,
+                long,
+                term.has_target().then(|| hrefs.term_href(term)),
+            ))
+        })
+        .collect();
+    if acronyms.is_empty() {
+        return None;
+    }
+    acronyms.sort_by(|a, b| {
+        (a.0.to_lowercase(), a.1.to_lowercase()).cmp(&(b.0.to_lowercase(), b.1.to_lowercase()))
+    });
+    // A term shadowed by a scoped glossary's may appear twice
+    acronyms.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+
+    let mut page = format!("# {title}\n\n| Acronym | Meaning |\n| --- | --- |\n");
+    for (short, long, href) in acronyms {
+        let acronym = href.map_or_else(
+            || table_cell(short),
+            |href| format!("[{}](<{href}>)", table_cell(short)),
+        );
+        let _ = writeln!(page, "| {acronym} | {} |", table_cell(long));
+    }
+    Some(page)
+}
+
+/// Escapes the characters that would end a table cell or a link text.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acronym_list() {
+        let terms = [
+            Term::new("REST (Representational State Transfer)"),
+            Term::new("Glossary"),
+            Term::new("CLI (Command-Line Interface)").defined_inline(),
+            Term::new("API (Application Programming Interface)"),
+            Term::new("TLS (Transport Layer Security)")
+                .with_url(Some("https://datatracker.ietf.org/doc/html/rfc8446".into())),
+        ];
+
+        let page = acronym_list(&terms, &GlossaryHrefs::single("glossary.html"), "Acronyms");
+        assert_eq!(
+            page.as_deref(),
+            Some(
+                "# Acronyms\n\n| Acronym | Meaning |\n| --- | --- |\n\
+                 | [API](<glossary.html#api-application-programming-interface>) | Application Programming Interface |\n\
+                 | CLI | Command-Line Interface |\n\
+                 | [REST](<glossary.html#rest-representational-state-transfer>) | Representational State Transfer |\n\
+                 | [TLS](<https://datatracker.ietf.org/doc/html/rfc8446>) | Transport Layer Security |\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_acronym_list_without_acronyms() {
+        let terms = [Term::new("Glossary")];
+        assert_eq!(
+            acronym_list(&terms, &GlossaryHrefs::single("glossary.html"), "Acronyms"),
+            None
+        );
+    }
+}
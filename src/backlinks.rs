@@ -0,0 +1,186 @@
+//! "Referenced in" lists added to glossary entries.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+use crate::linker::calculate_relative_path;
+
+/// A chapter linking to a glossary term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterRef {
+    /// Path of the chapter relative to `src`.
+    pub path: PathBuf,
+    /// Title of the chapter.
+    pub name: String,
+    /// Number of links to the term in the chapter.
+    pub links: usize,
+}
+
+/// Lists the chapters linking to each term below its glossary entry.
+///
+/// `uses` maps term names to the chapters linking to them, in book order.
+/// Every definition list entry of `content`, the glossary page at `page`,
+/// whose term is used gets one more definition:
+///
+/// ```markdown
+/// API
+/// : A set of protocols.
+/// : Referenced in: [Intro](../intro.md), [Setup](../guide/setup.md)
+/// ```
+///
+/// mdBook turns the `.md` links into links to the rendered pages.
+pub fn add_backlinks(
+    content: &str,
+    page: &Path,
+    uses: &HashMap<String, Vec<ChapterRef>>,
+    title: &str,
+) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_DEFINITION_LIST);
+
+    // (end of the entry's last definition, without trailing blank lines; term name)
+    let mut entries: Vec<(usize, String)> = Vec::new();
+    let mut title_text: Option<String> = None;
+    let mut current: Option<(usize, String)> = None;
+
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::DefinitionListTitle) => {
+                entries.extend(current.take());
+                title_text = Some(String::new());
+            }
+            Event::End(TagEnd::DefinitionListTitle) => {
+                current = title_text
+                    .take()
+                    .map(|text| (trimmed_end(content, range), text.trim().to_string()));
+            }
+            Event::End(TagEnd::DefinitionListDefinition) => {
+                if let Some((end, _)) = current.as_mut() {
+                    *end = trimmed_end(content, range);
+                }
+            }
+            Event::End(TagEnd::DefinitionList) => entries.extend(current.take()),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(title_text) = title_text.as_mut() {
+                    title_text.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut copied = 0;
+    for (end, name) in entries {
+        let Some(chapters) = uses.get(&name).filter(|chapters| !chapters.is_empty()) else {
+            continue;
+        };
+        let links: Vec<String> = chapters
+            .iter()
+            .map(|chapter| {
+                format!(
+                    "[{}]({})",
+                    escape_link_text(&chapter.name),
+                    link_destination(&calculate_relative_path(page, &chapter.path))
+                )
+            })
+            .collect();
+
+        output.push_str(&content[copied..end]);
+        let _ = write!(output, "\n: {title}: {}", links.join(", "));
+        copied = end;
+    }
+    output.push_str(&content[copied..]);
+    output
+}
+
+/// Returns the end of `range` in `content`, before any trailing whitespace.
+fn trimmed_end(content: &str, range: Range<usize>) -> usize {
+    range.start + content[range].trim_end().len()
+}
+
+/// Escapes the characters that would end or format markdown link text.
+fn escape_link_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '*' | '_' | '`' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Wraps a link destination in `<>` when it contains spaces or parentheses.
+fn link_destination(path: &str) -> String {
+    if path.contains([' ', '(', ')']) {
+        format!("<{path}>")
+    } else {
+        path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(path: &str, name: &str) -> ChapterRef {
+        ChapterRef {
+            path: PathBuf::from(path),
+            name: name.to_string(),
+            links: 1,
+        }
+    }
+
+    #[test]
+    fn test_add_backlinks() {
+        let content = "# Glossary\n\nAPI\n: A set of protocols.\n: Source: ISO\n\nREST\n: A style.\n\nJSON\n: A data format.";
+        let uses = HashMap::from([
+            (
+                "API".to_string(),
+                vec![
+                    chapter("intro.md", "Intro"),
+                    chapter("guide/setup.md", "Set [up]"),
+                ],
+            ),
+            ("JSON".to_string(), vec![chapter("my data.md", "Data")]),
+        ]);
+
+        let output = add_backlinks(
+            content,
+            Path::new("reference/glossary.md"),
+            &uses,
+            "Referenced in",
+        );
+        assert_eq!(
+            output,
+            "# Glossary\n\nAPI\n: A set of protocols.\n: Source: ISO\n\
+             : Referenced in: [Intro](../intro.md), [Set \\[up\\]](../guide/setup.md)\n\n\
+             REST\n: A style.\n\nJSON\n: A data format.\n: Referenced in: [Data](<../my data.md>)"
+        );
+    }
+
+    #[test]
+    fn test_add_backlinks_without_uses() {
+        let content = "API\n: A set of protocols.";
+        assert_eq!(
+            add_backlinks(
+                content,
+                Path::new("glossary.md"),
+                &HashMap::new(),
+                "Referenced in"
+            ),
+            content
+        );
+
+        let uses = HashMap::from([("API".to_string(), vec![chapter("intro.md", "Intro")])]);
+        assert_eq!(
+            add_backlinks(content, Path::new("glossary.md"), &uses, "Used in"),
+            "API\n: A set of protocols.\n: Used in: [Intro](intro.md)"
+        );
+    }
+}
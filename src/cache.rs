@@ -0,0 +1,196 @@
+//! On-disk cache of linked chapters, so rebuilds skip unchanged chapters.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::Config;
+use crate::glossary::Term;
+use crate::linker::LinkedChapter;
+
+/// The cache file as written to disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    /// Version of mdbook-termlink that wrote the file; other versions start over.
+    version: String,
+    /// Linked chapters by cache key.
+    chapters: BTreeMap<String, LinkedChapter>,
+}
+
+/// Linked chapters of the previous build, and those of this one.
+///
+/// A chapter is keyed by a hash of its content and path, the glossary, the
+/// configuration, and the terms linked on earlier pages, so any change that
+/// could alter its links misses the cache. Only the chapters of this build
+/// are saved, which drops those that were edited or removed.
+#[derive(Debug)]
+pub struct ChapterCache {
+    /// File the cache is read from and saved to.
+    path: PathBuf,
+    /// Hash of everything the whole book shares: glossary and configuration.
+    book_hash: u64,
+    /// Chapters of the previous build.
+    previous: BTreeMap<String, LinkedChapter>,
+    /// Chapters of this build.
+    current: BTreeMap<String, LinkedChapter>,
+}
+
+impl ChapterCache {
+    /// Loads the cache at `path` for a book with `terms` and `config`.
+    ///
+    /// A missing or unreadable cache file, or one written by another
+    /// version, starts an empty cache.
+    #[must_use]
+    pub fn load(path: PathBuf, terms: &[Term], config: &Config) -> Self {
+        let mut hasher = DefaultHasher::new();
+        terms.hash(&mut hasher);
+        config.hash(&mut hasher);
+
+        let previous = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<CacheFile>(&text).ok())
+            .filter(|file| file.version == env!("CARGO_PKG_VERSION"))
+            .map(|file| file.chapters)
+            .unwrap_or_default();
+        log::debug!(
+            "Loaded {} cached chapters from {}",
+            previous.len(),
+            path.display()
+        );
+
+        Self {
+            path,
+            book_hash: hasher.finish(),
+            previous,
+            current: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the linked chapter from the previous build, or links it with `link`.
+    ///
+    /// `linked_before` holds the anchors of the terms linked on earlier pages.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `link`; failed chapters are not cached.
+    pub fn link(
+        &mut self,
+        chapter_path: &Path,
+        content: &str,
+        linked_before: &HashSet<String>,
+        link: impl FnOnce() -> Result<LinkedChapter>,
+    ) -> Result<LinkedChapter> {
+        let key = self.key(chapter_path, content, linked_before);
+        if let Some(linked) = self.get(&key) {
+            log::debug!("Reusing cached links for {}", chapter_path.display());
+            return Ok(linked);
+        }
+        let linked = link()?;
+        self.insert(key, &linked);
+        Ok(linked)
+    }
+
+    /// Returns the cache key of a chapter.
+    fn key(&self, chapter_path: &Path, content: &str, linked_before: &HashSet<String>) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.book_hash.hash(&mut hasher);
+        chapter_path.hash(&mut hasher);
+        content.hash(&mut hasher);
+        // Sorted, as set iteration order varies between runs
+        linked_before
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Returns the chapter linked under `key` in the previous build, keeping it for the next.
+    fn get(&mut self, key: &str) -> Option<LinkedChapter> {
+        let linked = self.previous.remove(key)?;
+        self.current.insert(key.to_string(), linked.clone());
+        Some(linked)
+    }
+
+    /// Stores a chapter linked in this build.
+    fn insert(&mut self, key: String, linked: &LinkedChapter) {
+        self.current.insert(key, linked.clone());
+    }
+
+    /// Writes the chapters of this build to the cache file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file or its directory cannot be written.
+    pub fn save(self) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = CacheFile {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            chapters: self.current,
+        };
+        fs::write(&self.path, serde_json::to_string(&file)?)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn linked(content: &str) -> LinkedChapter {
+        LinkedChapter {
+            content: content.to_string(),
+            linked: vec!["API".to_string()],
+            link_counts: HashMap::from([("API".to_string(), 1)]),
+            linked_anchors: HashSet::from(["api".to_string()]),
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_chapter_cache() {
+        let dir = std::env::temp_dir().join(format!("termlink-cache-{}", std::process::id()));
+        let path = dir.join("cache.json");
+        let terms = [Term::new("API")];
+        let config = Config::default();
+
+        let mut cache = ChapterCache::load(path.clone(), &terms, &config);
+        let key = cache.key(Path::new("intro.md"), "The API.", &HashSet::new());
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key.clone(), &linked("The [API].\n"));
+        cache.save().unwrap();
+
+        let mut cache = ChapterCache::load(path.clone(), &terms, &config);
+        assert_eq!(cache.get(&key), Some(linked("The [API].\n")));
+        // Another path, content, or earlier link misses
+        assert_ne!(
+            cache.key(Path::new("other.md"), "The API.", &HashSet::new()),
+            key
+        );
+        assert_ne!(
+            cache.key(
+                Path::new("intro.md"),
+                "The API.",
+                &HashSet::from(["api".to_string()])
+            ),
+            key
+        );
+
+        // A changed glossary misses too
+        let cache = ChapterCache::load(path, &[Term::new("REST")], &config);
+        assert_ne!(
+            cache.key(Path::new("intro.md"), "The API.", &HashSet::new()),
+            key
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
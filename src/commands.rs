@@ -0,0 +1,85 @@
+//! Standalone subcommands that operate on a book directory.
+//!
+//! These back the `mdbook-termlink <subcommand>` CLI and work on the files
+//! on disk rather than on the JSON book mdBook pipes to the preprocessor.
+
+mod anchors;
+mod check_anchors;
+mod explain;
+mod export;
+mod install;
+mod lsp_data;
+mod rename;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use similar::TextDiff;
+
+pub use anchors::anchor_table;
+pub use check_anchors::{MissingAnchor, check_anchors};
+pub use explain::{Explanation, Occurrence, Outcome, Reason, explain};
+pub use export::{export_csv, export_json};
+pub use install::install;
+pub use lsp_data::lsp_data;
+pub use rename::{RenameOptions, rename_term};
+
+/// A pending change to a file, holding its original and updated content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path of the changed file.
+    path: PathBuf,
+    /// Content before the change.
+    before: String,
+    /// Content after the change.
+    after: String,
+}
+
+impl FileChange {
+    /// Creates a change for `path` from its original and updated content.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>, before: String, after: String) -> Self {
+        Self {
+            path: path.into(),
+            before,
+            after,
+        }
+    }
+
+    /// Returns the path of the changed file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the updated file content.
+    #[must_use]
+    pub fn after(&self) -> &str {
+        &self.after
+    }
+
+    /// Renders the change as a unified diff.
+    #[must_use]
+    pub fn unified_diff(&self) -> String {
+        let path = self.path.display().to_string();
+        TextDiff::from_lines(&self.before, &self.after)
+            .unified_diff()
+            .header(&path, &path)
+            .to_string()
+    }
+
+    /// Writes the updated content to disk, creating missing parent directories.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&self.path, &self.after)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
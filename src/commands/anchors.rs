@@ -0,0 +1,95 @@
+//! The `anchors` subcommand.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::BookSource;
+use crate::glossary::{self, Term};
+use crate::linker::GlossaryHrefs;
+
+/// Builds the term → anchor → target table for the book.
+///
+/// Each row shows the glossary term, the URL fragment generated for it, and
+/// the full target a link to the term resolves to (relative to the book root,
+/// or site-absolute with `use-site-url`).
+///
+/// # Errors
+///
+/// Returns an error if the book or its glossary cannot be loaded.
+pub fn anchor_table(source: &BookSource) -> Result<String> {
+    let book = source.load_book()?;
+    let terms = glossary::resolve_terms(&book, source.root(), source.src_dir(), source.config())?;
+    let config = source.config();
+    let glossary_targets = glossary::resolve_glossary_targets(&terms, config.redirects());
+    let hrefs = GlossaryHrefs::for_chapter(Path::new(""), &glossary_targets, config);
+
+    Ok(format_table(&terms, &hrefs))
+}
+
+/// Formats terms as an aligned three-column table.
+fn format_table(terms: &[Term], hrefs: &GlossaryHrefs) -> String {
+    let rows: Vec<[String; 3]> = terms
+        .iter()
+        .map(|term| {
+            [
+                term.name().to_string(),
+                term.anchor().to_string(),
+                hrefs.term_href(term),
+            ]
+        })
+        .collect();
+
+    let header = [
+        String::from("TERM"),
+        String::from("ANCHOR"),
+        String::from("TARGET"),
+    ];
+    let mut widths = header.each_ref().map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let _ = writeln!(
+            table,
+            "{:<w0$}  {:<w1$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            w0 = widths[0],
+            w1 = widths[1],
+        );
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table() {
+        let terms = vec![
+            Term::new("API (Application Programming Interface)"),
+            Term::new("REST"),
+        ];
+        let table = format_table(&terms, &GlossaryHrefs::single("reference/glossary.html"));
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("TERM "));
+        assert!(lines[1].contains("api-application-programming-interface"));
+        assert!(
+            lines[1].ends_with("reference/glossary.html#api-application-programming-interface")
+        );
+        assert!(lines[2].starts_with("REST "));
+        assert!(lines[2].ends_with("reference/glossary.html#rest"));
+        // Anchor column is aligned across rows
+        assert_eq!(lines[1].find("api-"), lines[2].find("rest  "));
+    }
+}
@@ -0,0 +1,142 @@
+//! The `check-anchors` subcommand.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::BookSource;
+use crate::glossary::{self, GlossaryTarget, Term};
+
+/// `id` attributes of HTML elements.
+static ID_ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\sid\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("id attribute regex is valid")
+});
+
+/// A glossary link whose anchor the rendered glossary page doesn't have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingAnchor {
+    /// Name of the term linked to.
+    pub term: String,
+    /// Anchor the links point to.
+    pub anchor: String,
+    /// Rendered glossary page, relative to the output directory.
+    pub page: PathBuf,
+}
+
+impl fmt::Display for MissingAnchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}': #{} not found in {}",
+            self.term,
+            self.anchor,
+            self.page.display()
+        )
+    }
+}
+
+/// Checks that every anchor termlink links to exists in the rendered glossary.
+///
+/// The glossary pages are read from the HTML output of the last build: the
+/// `html` directory below `build-dir` when mdBook renders more than one
+/// format, `build-dir` itself otherwise. Terms with their own URL and glossary
+/// pages redirected off-site are not checked.
+///
+/// # Errors
+///
+/// Returns an error if the book or its glossary cannot be loaded, or a
+/// rendered glossary page cannot be read (the book has not been built).
+pub fn check_anchors(source: &BookSource) -> Result<Vec<MissingAnchor>> {
+    let book = source.load_book()?;
+    let terms = glossary::resolve_terms(&book, source.root(), source.src_dir(), source.config())?;
+    let targets = glossary::resolve_glossary_targets(&terms, source.config().redirects());
+
+    let html_dir = source.build_dir().join("html");
+    let output_dir = if html_dir.is_dir() {
+        html_dir
+    } else {
+        source.build_dir().to_path_buf()
+    };
+    missing_anchors(&terms, &targets, |page| {
+        let path = output_dir.join(page);
+        fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read the rendered glossary {}; build the book first",
+                path.display()
+            )
+        })
+    })
+}
+
+/// Returns the anchors of `terms` missing from their glossary pages, read with `read_page`.
+fn missing_anchors(
+    terms: &[Term],
+    targets: &BTreeMap<PathBuf, GlossaryTarget>,
+    read_page: impl Fn(&Path) -> Result<String>,
+) -> Result<Vec<MissingAnchor>> {
+    let mut ids: HashMap<&Path, HashSet<String>> = HashMap::new();
+    let mut missing = Vec::new();
+    for term in terms.iter().filter(|term| term.url().is_none()) {
+        // Inline terms have no page and nothing to link to
+        let Some(GlossaryTarget::Local(page)) = term.page().and_then(|page| targets.get(page))
+        else {
+            continue;
+        };
+        if !ids.contains_key(page.as_path()) {
+            ids.insert(page, html_ids(&read_page(page)?));
+        }
+        if !ids[page.as_path()].contains(term.anchor()) {
+            missing.push(MissingAnchor {
+                term: term.name().to_string(),
+                anchor: term.anchor().to_string(),
+                page: page.clone(),
+            });
+        }
+    }
+    Ok(missing)
+}
+
+/// Collects the ids of the elements in `html`.
+fn html_ids(html: &str) -> HashSet<String> {
+    ID_ATTRIBUTE
+        .captures_iter(html)
+        .filter_map(|captures| captures.get(1).or_else(|| captures.get(2)))
+        .map(|id| id.as_str().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_anchors() {
+        let terms = [
+            Term::new("API").with_page("glossary.md"),
+            Term::new("REST (Representational State Transfer)").with_page("glossary.md"),
+            Term::new("RFC")
+                .with_page("glossary.md")
+                .with_url(Some("https://www.rfc-editor.org/".into())),
+            Term::new("SLA").defined_inline(),
+        ];
+        let targets = glossary::resolve_glossary_targets(&terms, &BTreeMap::new());
+        let html = r#"<dl><dt id="api">API</dt><dd>...</dd><dt id='rest'>REST</dt></dl>"#;
+
+        let missing = missing_anchors(&terms, &targets, |page| {
+            assert_eq!(page, Path::new("glossary.html"));
+            Ok(html.to_string())
+        })
+        .unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(
+            missing[0].to_string(),
+            "'REST (Representational State Transfer)': \
+             #rest-representational-state-transfer not found in glossary.html"
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! The `explain` subcommand.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use pulldown_cmark::{Event, Parser, TagEnd};
+
+use crate::config::Config;
+use crate::glossary::{self, Term};
+use crate::linker::{self, GlossaryHrefs, TermLink, TermMatcher};
+use crate::location::{LineIndex, Position};
+use crate::raw_html::HtmlScanner;
+use crate::{BookSource, TermlinkPreprocessor, front_matter};
+
+/// Why an occurrence of a term is not linked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The chapter is a glossary page.
+    GlossaryPage,
+    /// The chapter is left out by `exclude-pages` or `include-pages`.
+    ExcludedPage,
+    /// The front matter of the chapter disables linking.
+    DisabledByFrontMatter,
+    /// The term is in `ignore-terms`, or disabled in `terms`.
+    IgnoredTerm,
+    /// The term is excluded on the chapter, by `page-exclude-terms`, its
+    /// front matter, or a definition list of its own.
+    ExcludedTerm,
+    /// The term belongs to a scoped glossary of other chapters.
+    OutOfScope,
+    /// The occurrence is in the front matter.
+    InFrontMatter,
+    /// The occurrence is inside an element that is never linked, or one in `skip-contexts`.
+    InElement(&'static str),
+    /// The occurrence is in raw HTML.
+    InHtml,
+    /// The occurrence is between `<!-- termlink:off -->` and `<!-- termlink:on -->`.
+    DisabledRegion,
+    /// The occurrence is in a block holding one of the `protected-markers`.
+    MarkerBlock,
+    /// The occurrence is inside an mdBook directive, a placeholder, or math.
+    Protected,
+    /// The occurrence is escaped with a backslash.
+    Escaped,
+    /// The occurrence is part of a longer one, linked to the named term.
+    PartOf(String),
+    /// The term is linked earlier on the page.
+    AlreadyLinked,
+    /// The page has all the links `max-links-per-page` allows.
+    LinkLimit,
+    /// The occurrence is not in text termlink links, such as one written with entities.
+    NotLinkable,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GlossaryPage => f.write_str("the page is a glossary page"),
+            Self::ExcludedPage => f.write_str("the page is excluded by exclude-pages or include-pages"),
+            Self::DisabledByFrontMatter => f.write_str("the front matter disables linking on the page"),
+            Self::IgnoredTerm => f.write_str("the term is in ignore-terms or disabled in terms"),
+            Self::ExcludedTerm => f.write_str(
+                "the term is excluded on the page (page-exclude-terms, front matter, or a local definition)",
+            ),
+            Self::OutOfScope => f.write_str("the term is in a scoped glossary of other chapters"),
+            Self::InFrontMatter => f.write_str("in the front matter"),
+            Self::InElement(element) => write!(f, "inside {element}"),
+            Self::InHtml => f.write_str("in raw HTML (see link-in-html)"),
+            Self::DisabledRegion => f.write_str("in a region disabled by <!-- termlink:off -->"),
+            Self::MarkerBlock => f.write_str("in a block with a protected marker"),
+            Self::Protected => f.write_str("inside an mdBook directive, placeholder, or math"),
+            Self::Escaped => f.write_str("escaped with a backslash"),
+            Self::PartOf(term) => write!(f, "part of a longer occurrence, linked to '{term}'"),
+            Self::AlreadyLinked => f.write_str("already linked earlier on the page (link-first-only)"),
+            Self::LinkLimit => f.write_str("the page has reached max-links-per-page"),
+            Self::NotLinkable => f.write_str("not in text termlink links"),
+        }
+    }
+}
+
+/// Whether an occurrence of a term is linked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The occurrence is linked, to `href` (`None` for a term defined inline without a URL).
+    Linked {
+        /// Target of the link.
+        href: Option<String>,
+    },
+    /// The occurrence is left as written.
+    NotLinked(Reason),
+}
+
+/// An occurrence of a term in a chapter, and whether it is linked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    /// Where the occurrence is in the chapter file.
+    pub position: Position,
+    /// The occurrence, as written.
+    pub matched_text: String,
+    /// Whether it is linked, and why not.
+    pub outcome: Outcome,
+}
+
+/// Every occurrence of a term in a chapter, and whether it is linked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// The chapter, relative to `src`.
+    pub chapter: PathBuf,
+    /// Name of the term.
+    pub term: String,
+    /// The occurrences, in the order of the chapter.
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chapter = self.chapter.display();
+        for occurrence in &self.occurrences {
+            let text = &occurrence.matched_text;
+            match &occurrence.outcome {
+                Outcome::Linked { href: Some(href) } => {
+                    writeln!(
+                        f,
+                        "{chapter}:{}: '{text}' linked to {href}",
+                        occurrence.position
+                    )?;
+                }
+                Outcome::Linked { href: None } => {
+                    writeln!(f, "{chapter}:{}: '{text}' linked", occurrence.position)?;
+                }
+                Outcome::NotLinked(reason) => writeln!(
+                    f,
+                    "{chapter}:{}: '{text}' not linked: {reason}",
+                    occurrence.position
+                )?,
+            }
+        }
+        let linked = self
+            .occurrences
+            .iter()
+            .filter(|o| matches!(o.outcome, Outcome::Linked { .. }))
+            .count();
+        write!(
+            f,
+            "{} occurrences of '{}', {linked} linked",
+            self.occurrences.len(),
+            self.term
+        )
+    }
+}
+
+/// Explains, for every occurrence of `term` in the chapter `file`, whether it is linked and why not.
+///
+/// `term` is looked up by name, short form, or alias, ignoring case, and
+/// `file` is taken relative to the current directory if it exists there,
+/// relative to `src` otherwise. The chapter is linked on its own, so with
+/// `link-first-only = "per-book"` a term linked on an earlier page is still
+/// reported as linked.
+///
+/// # Errors
+///
+/// Returns an error if the book or its glossary cannot be loaded, `term`
+/// is not a glossary term, or `file` cannot be read or is outside `src`.
+pub fn explain(source: &BookSource, term: &str, file: &Path) -> Result<Explanation> {
+    let book = source.load_book()?;
+    let terms = glossary::resolve_terms(&book, source.root(), source.src_dir(), source.config())?;
+    let Some(term) = find_term(&terms, term) else {
+        bail!("'{term}' is not a glossary term");
+    };
+    let config = source.config();
+    let chapter = chapter_path(source.src_dir(), file)?;
+    let content = source.read_chapter(&chapter)?;
+    let (_, body) = front_matter::split(&content)?;
+    let front_matter_len = content.len() - body.len();
+
+    // Ignored terms are still looked for, as if they were not
+    let unignored = Config::default();
+    let find_config = if config.is_ignored(term.name(), term.short_name()) {
+        &unignored
+    } else {
+        config
+    };
+    let finder = TermMatcher::new(std::slice::from_ref(term), find_config)?;
+    let line_index = LineIndex::new(&content);
+    let located = |range: Range<usize>, outcome| Occurrence {
+        position: line_index.position(range.start),
+        matched_text: content[range].to_string(),
+        outcome,
+    };
+
+    // Occurrences in the front matter, then those in the chapter itself
+    let mut occurrences: Vec<Occurrence> = find_occurrences(&finder, &content[..front_matter_len])
+        .into_iter()
+        .map(|range| located(range, Outcome::NotLinked(Reason::InFrontMatter)))
+        .collect();
+    let found = find_occurrences(&finder, body);
+    let shift = |range: &Range<usize>| front_matter_len + range.start..front_matter_len + range.end;
+    if let Some(reason) = page_reason(&terms, term, &chapter, &content, config)? {
+        occurrences.extend(
+            found
+                .iter()
+                .map(|range| located(shift(range), Outcome::NotLinked(reason.clone()))),
+        );
+    } else {
+        let links = chapter_links(&terms, &chapter, &content, config)?
+            .into_iter()
+            .map(|link| TermLink {
+                offset: link.offset - front_matter_len,
+                ..link
+            })
+            .collect::<Vec<_>>();
+        let contexts = text_contexts(body, config);
+        occurrences.extend(found.iter().map(|range| {
+            let outcome = outcome(term, body, range, &links, &contexts, config);
+            located(shift(range), outcome)
+        }));
+    }
+
+    Ok(Explanation {
+        chapter,
+        term: term.name().to_string(),
+        occurrences,
+    })
+}
+
+/// Finds the glossary term named `name`, by name, short form, or alias, ignoring case.
+fn find_term<'a>(terms: &'a [Term], name: &str) -> Option<&'a Term> {
+    let name = name.to_lowercase();
+    terms.iter().find(|term| {
+        std::iter::once(term.name())
+            .chain(term.short_name())
+            .chain(term.aliases().iter().map(String::as_str))
+            .any(|form| form.to_lowercase() == name)
+    })
+}
+
+/// Returns the path of the chapter `file` relative to `src_dir`.
+fn chapter_path(src_dir: &Path, file: &Path) -> Result<PathBuf> {
+    if !file.exists() {
+        return Ok(file.to_path_buf());
+    }
+    let canonical = |path: &Path| {
+        path.canonicalize()
+            .with_context(|| format!("Failed to read {}", path.display()))
+    };
+    let (file, src_dir) = (canonical(file)?, canonical(src_dir)?);
+    file.strip_prefix(&src_dir)
+        .map(Path::to_path_buf)
+        .with_context(|| format!("{} is not below {}", file.display(), src_dir.display()))
+}
+
+/// Returns why no occurrence of `term` is linked in the chapter, if the
+/// whole chapter or the term is left alone there.
+fn page_reason(
+    terms: &[Term],
+    term: &Term,
+    chapter: &Path,
+    content: &str,
+    config: &Config,
+) -> Result<Option<Reason>> {
+    let (page, body) = front_matter::split(content)?;
+    let term_sets = glossary::term_sets(terms, config);
+    let scope = config.chapter_scope(chapter);
+    let scope_terms = term_sets
+        .iter()
+        .find(|(dir, _)| dir.as_deref() == scope)
+        .map_or(&[][..], |(_, terms)| terms.as_slice());
+    let exclude_terms: Vec<String> = config
+        .page_exclude_terms(chapter)
+        .iter()
+        .chain(&page.exclude_terms)
+        .cloned()
+        .chain(glossary::local_definitions(
+            body,
+            scope_terms,
+            config.case_sensitive(),
+        ))
+        .collect();
+
+    let reason = if config.is_glossary_path(chapter) {
+        Reason::GlossaryPage
+    } else if config.should_exclude(chapter) {
+        Reason::ExcludedPage
+    } else if page.disable {
+        Reason::DisabledByFrontMatter
+    } else if config.is_ignored(term.name(), term.short_name()) {
+        Reason::IgnoredTerm
+    } else if config
+        .ignoring(&exclude_terms)
+        .is_ignored(term.name(), term.short_name())
+    {
+        Reason::ExcludedTerm
+    } else if !scope_terms.iter().any(|t| t.name() == term.name()) {
+        Reason::OutOfScope
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(reason))
+}
+
+/// Links the chapter as the preprocessor would, returning the links added.
+fn chapter_links(
+    terms: &[Term],
+    chapter: &Path,
+    content: &str,
+    config: &Config,
+) -> Result<Vec<TermLink>> {
+    let preprocessor = TermlinkPreprocessor::from_config(config.clone());
+    let term_sets = glossary::term_sets(terms, config);
+    let scope = config.chapter_scope(chapter);
+    let Some((_, scope_terms)) = term_sets.iter().find(|(dir, _)| dir.as_deref() == scope) else {
+        return Ok(Vec::new());
+    };
+    let matcher = TermMatcher::new(scope_terms, config)?;
+    let hrefs = GlossaryHrefs::for_chapter(chapter, &preprocessor.glossary_targets(terms), config);
+    let linked = preprocessor.link_page(chapter, content, &matcher, &hrefs, &HashSet::new())?;
+    Ok(linked.links)
+}
+
+/// Returns the occurrences `finder` finds in `text`, leftmost-longest and not overlapping.
+fn find_occurrences(finder: &TermMatcher, text: &str) -> Vec<Range<usize>> {
+    let mut found: Vec<Range<usize>> = finder.find_all(text).into_iter().map(|m| m.range).collect();
+    found.sort_by_key(|range| (range.start, Reverse(range.end)));
+    let mut kept: Vec<Range<usize>> = Vec::new();
+    for range in found {
+        if kept.last().is_none_or(|last| last.end <= range.start) {
+            kept.push(range);
+        }
+    }
+    kept
+}
+
+/// Tells whether the occurrence at `range` of `body` is linked, and why not.
+///
+/// `links` are the links added to the chapter, at their offsets in `body`,
+/// and `contexts` the texts and elements of `body` from [`text_contexts`].
+fn outcome(
+    term: &Term,
+    body: &str,
+    range: &Range<usize>,
+    links: &[TermLink],
+    contexts: &[(Range<usize>, Option<Reason>)],
+    config: &Config,
+) -> Outcome {
+    let same_term = |link: &&TermLink| link.term == term.name();
+    if let Some(link) = links
+        .iter()
+        .filter(same_term)
+        .find(|link| link.offset == range.start)
+    {
+        return Outcome::Linked {
+            href: link.href.clone(),
+        };
+    }
+    let reason = match contexts
+        .iter()
+        .filter(|(context, _)| context.contains(&range.start))
+        .min_by_key(|(context, _)| context.len())
+    {
+        None => Reason::NotLinkable,
+        Some((_, Some(reason))) => reason.clone(),
+        Some((text, None)) => {
+            let in_text = range.start - text.start..range.end - text.start;
+            if linker::protected_spans(&body[text.clone()], config)
+                .iter()
+                .any(|span| span.start < in_text.end && in_text.start < span.end)
+            {
+                Reason::Protected
+            } else if linker::is_escaped(body, range.start) {
+                Reason::Escaped
+            } else if let Some(link) = links.iter().find(|link| {
+                link.offset < range.end && range.start < link.offset + link.matched_text.len()
+            }) {
+                Reason::PartOf(link.term.clone())
+            } else if links
+                .iter()
+                .filter(same_term)
+                .any(|link| link.offset < range.start)
+            {
+                Reason::AlreadyLinked
+            } else if config
+                .max_links_per_page()
+                .is_some_and(|max| links.len() >= max)
+            {
+                Reason::LinkLimit
+            } else {
+                Reason::NotLinkable
+            }
+        }
+    };
+    Outcome::NotLinked(reason)
+}
+
+/// Follows the events of `body` as the linker does, returning the source
+/// range of each text, inline code, and raw HTML, and of each element that
+/// is never linked, with why its occurrences are not linked.
+///
+/// Texts termlink may link have no reason.
+fn text_contexts(body: &str, config: &Config) -> Vec<(Range<usize>, Option<Reason>)> {
+    let (events, ranges): (Vec<Event>, Vec<Range<usize>>) =
+        Parser::new_ext(body, linker::parser_options())
+            .into_offset_iter()
+            .unzip();
+    let marker_blocks = linker::marker_block_ranges(&events, config.protected_markers());
+    let mut contexts = Vec::new();
+    // The skipped elements the current event is inside, innermost last
+    let mut skipped: Vec<TagEnd> = Vec::new();
+    let mut linking_on = true;
+    let mut raw_html = HtmlScanner::default();
+
+    for (index, (event, range)) in events.iter().zip(ranges).enumerate() {
+        let reason = match skipped.last() {
+            Some(end) => Some(Reason::InElement(element_name(*end))),
+            None if !linking_on => Some(Reason::DisabledRegion),
+            None if raw_html.in_skipped_element() => Some(Reason::InHtml),
+            None if marker_blocks.iter().any(|block| block.contains(&index)) => {
+                Some(Reason::MarkerBlock)
+            }
+            None => None,
+        };
+        match event {
+            // Entities and backslash escapes read differently in the source; they are left alone
+            Event::Text(text) => {
+                let reason = reason
+                    .or_else(|| (body[range.clone()] != **text).then_some(Reason::NotLinkable));
+                contexts.push((range, reason));
+            }
+            Event::Code(_) => contexts.push((range, Some(Reason::InElement("inline code")))),
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let linkable = reason.is_none() && config.link_in_html();
+                linking_on = linker::region_switch(html).unwrap_or(linking_on);
+                raw_html.scan(html);
+                contexts.push((range, (!linkable).then_some(Reason::InHtml)));
+            }
+            Event::Start(tag) if linker::is_skipped(tag.to_end(), config) => {
+                skipped.push(tag.to_end());
+                contexts.push((range, Some(Reason::InElement(element_name(tag.to_end())))));
+            }
+            Event::End(end) if linker::is_skipped(*end, config) => {
+                skipped.pop();
+            }
+            _ => {}
+        }
+    }
+    contexts
+}
+
+/// Names the element ending with `end`, for the elements the linker skips.
+const fn element_name(end: TagEnd) -> &'static str {
+    match end {
+        TagEnd::CodeBlock => "a code block",
+        TagEnd::Link => "a link",
+        TagEnd::Image => "an image",
+        TagEnd::Heading(_) => "a heading (skip-contexts)",
+        TagEnd::BlockQuote(_) => "a block quote (skip-contexts)",
+        TagEnd::Table => "a table (skip-contexts)",
+        TagEnd::TableHead => "a table header (skip-contexts)",
+        TagEnd::Item => "a list (skip-contexts)",
+        TagEnd::FootnoteDefinition => "a footnote (skip-contexts)",
+        _ => "an element termlink skips",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome() {
+        let terms = [Term::new("API"), Term::new("REST API")];
+        let config = Config::default();
+        let body =
+            "# API\n\nThe API, the REST API, \\API, and `API`.\n\n```\nAPI\n```\n\nAPI again.\n";
+        let matcher = TermMatcher::new(&terms, &config).unwrap();
+        let linked = linker::add_term_links(
+            body,
+            &matcher,
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::<String>::new(),
+        )
+        .unwrap();
+        let contexts = text_contexts(body, &config);
+        let finder = TermMatcher::new(&terms[..1], &config).unwrap();
+
+        let outcomes: Vec<Outcome> = find_occurrences(&finder, body)
+            .iter()
+            .map(|range| outcome(&terms[0], body, range, &linked.links, &contexts, &config))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                Outcome::NotLinked(Reason::InElement("a heading (skip-contexts)")),
+                Outcome::Linked {
+                    href: Some("glossary.html#api".to_string())
+                },
+                Outcome::NotLinked(Reason::PartOf("REST API".to_string())),
+                Outcome::NotLinked(Reason::Escaped),
+                Outcome::NotLinked(Reason::InElement("inline code")),
+                Outcome::NotLinked(Reason::InElement("a code block")),
+                Outcome::NotLinked(Reason::AlreadyLinked),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_term() {
+        let terms = [Term::new("API (Application Programming Interface)")
+            .with_aliases(vec!["web api".to_string()])];
+        assert!(find_term(&terms, "api").is_some());
+        assert!(find_term(&terms, "Web API").is_some());
+        assert!(find_term(&terms, "REST").is_none());
+    }
+}
@@ -0,0 +1,199 @@
+//! The `export` subcommand.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::BookSource;
+use crate::config::CsvColumns;
+use crate::glossary::{self, Term};
+use crate::paths;
+
+/// Version of the JSON shape, bumped on incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// The parsed glossary, as exported to JSON.
+#[derive(Debug, Serialize)]
+struct Export<'a> {
+    /// Version of this JSON shape.
+    version: u32,
+    /// Every glossary term, in glossary order.
+    terms: Vec<ExportTerm<'a>>,
+}
+
+/// One glossary term as exported.
+#[derive(Debug, Serialize)]
+struct ExportTerm<'a> {
+    /// Full term name as written in the glossary.
+    name: &'a str,
+    /// Short form of an acronym term, e.g. "API".
+    short_name: Option<&'a str>,
+    /// Long form of an acronym term, e.g. "Application Programming Interface".
+    long_name: Option<&'a str>,
+    /// URL fragment of the term on its glossary page.
+    anchor: &'a str,
+    /// Definition as plain text.
+    definition: Option<&'a str>,
+    /// Definition as markdown, as written in the glossary.
+    definition_markdown: Option<&'a str>,
+    /// Aliases from the glossary and `book.toml`.
+    aliases: &'a [String],
+    /// Citation of the definition.
+    citation: Option<&'a str>,
+    /// Glossary section heading the term is listed under.
+    category: Option<&'a str>,
+    /// Glossary chapter defining the term, relative to `src`.
+    page: Option<String>,
+    /// URL the term links to instead of its glossary entry.
+    url: Option<&'a str>,
+    /// Priority of the term's occurrences over overlapping ones of other terms.
+    priority: i32,
+    /// Deprecation note, empty when the term is deprecated without one.
+    deprecated: Option<&'a str>,
+}
+
+/// Exports the parsed glossary as JSON.
+///
+/// The shape is documented in the README and versioned by its `version` field.
+///
+/// # Errors
+///
+/// Returns an error if the book or its glossary cannot be loaded.
+pub fn export_json(source: &BookSource) -> Result<String> {
+    let terms = load_terms(source)?;
+    Ok(serde_json::to_string_pretty(&build_export(&terms))?)
+}
+
+/// Exports the parsed glossary as CSV, one row per term.
+///
+/// The columns are named after `csv-columns`, so a book using
+/// `glossary-source = "file"` can read the file back in.
+///
+/// # Errors
+///
+/// Returns an error if the book or its glossary cannot be loaded.
+pub fn export_csv(source: &BookSource) -> Result<String> {
+    let terms = load_terms(source)?;
+    write_csv(&terms, source.config().csv_columns())
+}
+
+/// Loads the glossary terms of the book.
+fn load_terms(source: &BookSource) -> Result<Vec<Term>> {
+    let book = source.load_book()?;
+    glossary::resolve_terms(&book, source.root(), source.src_dir(), source.config())
+}
+
+/// Collects the serializable data for `terms`.
+fn build_export(terms: &[Term]) -> Export<'_> {
+    Export {
+        version: FORMAT_VERSION,
+        terms: terms
+            .iter()
+            .map(|term| ExportTerm {
+                name: term.name(),
+                short_name: term.short_name(),
+                long_name: term.long_name(),
+                anchor: term.anchor(),
+                definition: term.definition(),
+                definition_markdown: term.definition_markdown(),
+                aliases: term.aliases(),
+                citation: term.citation(),
+                category: term.category(),
+                page: term.page().map(paths::to_url_path),
+                url: term.url(),
+                priority: term.priority(),
+                deprecated: term.deprecation(),
+            })
+            .collect(),
+    }
+}
+
+/// Writes `terms` as CSV with a header row.
+///
+/// Definitions are written as markdown where the glossary has it.
+fn write_csv(terms: &[Term], columns: &CsvColumns) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        columns.name.as_str(),
+        "short_name",
+        &columns.anchor,
+        &columns.definition,
+        &columns.aliases,
+        &columns.citation,
+        &columns.category,
+        "page",
+        &columns.url,
+        &columns.priority,
+    ])?;
+    for term in terms {
+        writer.write_record([
+            term.name(),
+            term.short_name().unwrap_or_default(),
+            term.anchor(),
+            term.definition_markdown()
+                .or_else(|| term.definition())
+                .unwrap_or_default(),
+            &term.aliases().join(&columns.alias_separator),
+            term.citation().unwrap_or_default(),
+            term.category().unwrap_or_default(),
+            &term.page().map(paths::to_url_path).unwrap_or_default(),
+            term.url().unwrap_or_default(),
+            &term.priority().to_string(),
+        ])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms() -> Vec<Term> {
+        vec![
+            Term::with_definition(
+                "API (Application Programming Interface)",
+                Some("A set of protocols.".to_string()),
+            )
+            .with_definition_markdown(Some("A set of *protocols*.".to_string()))
+            .with_aliases(vec!["apis".to_string(), "endpoints".to_string()])
+            .with_page("reference/glossary.md"),
+            Term::new("REST, \"RESTful\"")
+                .deprecated("")
+                .with_priority(2),
+        ]
+    }
+
+    #[test]
+    fn test_build_export() {
+        let terms = terms();
+        let json = serde_json::to_value(build_export(&terms)).unwrap();
+
+        assert_eq!(json["version"], 1);
+        assert_eq!(json["terms"][0]["short_name"], "API");
+        assert_eq!(
+            json["terms"][0]["long_name"],
+            "Application Programming Interface"
+        );
+        assert_eq!(
+            json["terms"][0]["definition_markdown"],
+            "A set of *protocols*."
+        );
+        assert_eq!(json["terms"][0]["page"], "reference/glossary.md");
+        assert_eq!(json["terms"][0]["deprecated"], serde_json::Value::Null);
+        assert_eq!(json["terms"][1]["deprecated"], "");
+        assert_eq!(json["terms"][0]["priority"], 0);
+        assert_eq!(json["terms"][1]["priority"], 2);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let csv = write_csv(&terms(), &CsvColumns::default()).unwrap();
+
+        assert_eq!(
+            csv,
+            "name,short_name,anchor,definition,aliases,citation,category,page,url,priority\n\
+             API (Application Programming Interface),API,api-application-programming-interface,\
+             A set of *protocols*.,apis;endpoints,,,reference/glossary.md,,0\n\
+             \"REST, \"\"RESTful\"\"\",,rest-restful,,,,,,,2\n"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! The `install` subcommand.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use toml_edit::{Array, DocumentMut, Table, value};
+
+use super::FileChange;
+use crate::config::GlossarySource;
+use crate::{BookSource, Config, paths};
+
+/// Script showing the hover cards of `tooltip = "popover"`.
+const TERMLINK_JS: &str = include_str!("../../assets/termlink.js");
+
+/// Styles of the hover cards.
+const TERMLINK_CSS: &str = include_str!("../../assets/termlink.css");
+
+/// Markdown glossary written when the book has none yet.
+const STARTER_GLOSSARY: &str = "\
+# Glossary
+
+API (Application Programming Interface)
+: A set of protocols and tools for building software applications.
+";
+
+/// Term file written when a book using `glossary-source = "file"` has none yet.
+const STARTER_TERM_FILE: &str = "\
+[[term]]
+name = \"API (Application Programming Interface)\"
+definition = \"A set of protocols and tools for building software applications.\"
+";
+
+/// Computes the file changes that set a book up for termlink.
+///
+/// - `book.toml` gets a `[preprocessor.termlink]` section unless it has one
+///   (or the configuration lives in a standalone file).
+/// - A starter glossary is created at `glossary-path` (or `glossary-file`)
+///   if there is none, and linked from `SUMMARY.md`.
+/// - `termlink.css` and `termlink.js` are written to the book root and listed
+///   in `output.html.additional-css` and `additional-js` of `book.toml`, so
+///   mdBook includes them on every page.
+///
+/// Files already up to date are left out, so rerunning it only refreshes the assets.
+///
+/// # Errors
+///
+/// Returns an error if the book has no `book.toml`, or a file cannot be read or parsed.
+pub fn install(source: &BookSource) -> Result<Vec<FileChange>> {
+    let book_toml_path = source.root().join("book.toml");
+    if !book_toml_path.exists() {
+        bail!(
+            "No book.toml in {}; install needs one to list the assets in",
+            source.root().display()
+        );
+    }
+
+    let mut changes = Vec::new();
+    for (name, content) in [("termlink.css", TERMLINK_CSS), ("termlink.js", TERMLINK_JS)] {
+        let path = source.root().join(name);
+        let existing = read_if_exists(&path)?;
+        if existing != content {
+            changes.push(FileChange::new(path, existing, content.to_string()));
+        }
+    }
+    changes.extend(starter_glossary(source)?);
+
+    let book_toml = read_if_exists(&book_toml_path)?;
+    let add_section = !source.config_table_keys().is_empty();
+    if let Some(updated) = update_book_toml(&book_toml, add_section)? {
+        changes.push(FileChange::new(book_toml_path, book_toml, updated));
+    }
+
+    Ok(changes)
+}
+
+/// Creates a starter glossary if the configured one does not exist.
+///
+/// A new glossary chapter is also appended to `SUMMARY.md`, so mdBook renders it.
+fn starter_glossary(source: &BookSource) -> Result<Vec<FileChange>> {
+    let config = source.config();
+    if config.glossary_source() == GlossarySource::File {
+        let path = source.root().join(config.glossary_file());
+        if path.exists() {
+            return Ok(Vec::new());
+        }
+        return Ok(vec![FileChange::new(
+            path,
+            String::new(),
+            STARTER_TERM_FILE.to_string(),
+        )]);
+    }
+
+    // A split glossary (a directory) is left for the author to lay out
+    let path = source.src_dir().join(config.glossary_path());
+    if path.exists() || path.extension().is_none_or(|ext| ext != "md") {
+        return Ok(Vec::new());
+    }
+    let mut changes = vec![FileChange::new(
+        path,
+        String::new(),
+        STARTER_GLOSSARY.to_string(),
+    )];
+
+    let summary_path = source.src_dir().join("SUMMARY.md");
+    let summary = read_if_exists(&summary_path)?;
+    let link = paths::to_url_path(config.glossary_path());
+    if !summary.is_empty() && !summary.contains(&format!("({link})")) {
+        let updated = format!("{}\n\n[Glossary]({link})\n", summary.trim_end());
+        changes.push(FileChange::new(summary_path, summary, updated));
+    }
+    Ok(changes)
+}
+
+/// Reads a file, or returns an empty string if it does not exist.
+fn read_if_exists(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Adds the `[preprocessor.termlink]` section (when `add_section` is set)
+/// and lists the assets in `[output.html]` of `book.toml`.
+///
+/// Returns `None` if nothing changes.
+fn update_book_toml(book_toml: &str, add_section: bool) -> Result<Option<String>> {
+    let mut doc: DocumentMut = book_toml.parse().context("Failed to parse book.toml")?;
+
+    let mut changed = add_section && add_preprocessor(&mut doc)?;
+    changed |= add_assets(&mut doc)?;
+    Ok(changed.then(|| doc.to_string()))
+}
+
+/// Adds a `[preprocessor.termlink]` section naming the default glossary path.
+///
+/// Returns whether the section was missing.
+fn add_preprocessor(doc: &mut DocumentMut) -> Result<bool> {
+    let preprocessor = doc
+        .entry("preprocessor")
+        .or_insert_with(|| implicit_table().into())
+        .as_table_like_mut()
+        .context("book.toml `preprocessor` is not a table")?;
+    if preprocessor.contains_key("termlink") {
+        return Ok(false);
+    }
+
+    let mut termlink = Table::new();
+    termlink.insert(
+        "glossary-path",
+        value(paths::to_url_path(Config::default().glossary_path())),
+    );
+    preprocessor.insert("termlink", termlink.into());
+    Ok(true)
+}
+
+/// Lists the assets in `[output.html]` of `book.toml`.
+///
+/// Returns whether either was missing.
+fn add_assets(doc: &mut DocumentMut) -> Result<bool> {
+    let output = doc
+        .entry("output")
+        .or_insert_with(|| implicit_table().into())
+        .as_table_mut()
+        .context("book.toml `output` is not a table")?;
+    let html = output
+        .entry("html")
+        .or_insert_with(|| Table::new().into())
+        .as_table_like_mut()
+        .context("book.toml `output.html` is not a table")?;
+
+    let mut changed = false;
+    for (key, file) in [
+        ("additional-css", "termlink.css"),
+        ("additional-js", "termlink.js"),
+    ] {
+        let list = html
+            .entry(key)
+            .or_insert_with(|| value(Array::new()))
+            .as_array_mut()
+            .with_context(|| format!("book.toml `output.html.{key}` is not an array"))?;
+        if !list.iter().any(|entry| entry.as_str() == Some(file)) {
+            list.push(file);
+            changed = true;
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Returns a table that is only written as a prefix of its sub-tables (`[output.html]`).
+fn implicit_table() -> Table {
+    let mut table = Table::new();
+    table.set_implicit(true);
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_book_toml_creates_sections() {
+        let updated = update_book_toml("[book]\ntitle = \"Book\"\n", true)
+            .unwrap()
+            .unwrap();
+
+        let doc: toml::Table = updated.parse().unwrap();
+        let html = &doc["output"]["html"];
+        assert_eq!(html["additional-css"].as_array().unwrap().len(), 1);
+        assert_eq!(html["additional-js"][0].as_str(), Some("termlink.js"));
+        assert!(updated.contains("[output.html]"));
+        assert!(!updated.contains("[output]\n"));
+        assert_eq!(
+            doc["preprocessor"]["termlink"]["glossary-path"].as_str(),
+            Some("reference/glossary.md")
+        );
+        assert!(updated.contains("[preprocessor.termlink]"));
+        assert!(!updated.contains("[preprocessor]\n"));
+    }
+
+    #[test]
+    fn test_update_book_toml_keeps_existing_entries() {
+        let book_toml = "[preprocessor.termlink]\nglossary-path = \"terms.md\"\n\n\
+                         [output.html]\nadditional-css = [\"custom.css\"]\n";
+        let updated = update_book_toml(book_toml, true).unwrap().unwrap();
+
+        let doc: toml::Table = updated.parse().unwrap();
+        let css = doc["output"]["html"]["additional-css"].as_array().unwrap();
+        assert_eq!(css[0].as_str(), Some("custom.css"));
+        assert_eq!(css[1].as_str(), Some("termlink.css"));
+
+        assert_eq!(
+            doc["preprocessor"]["termlink"]["glossary-path"].as_str(),
+            Some("terms.md")
+        );
+
+        // Running it again changes nothing
+        assert_eq!(update_book_toml(&updated, true).unwrap(), None);
+    }
+}
@@ -0,0 +1,122 @@
+//! The `lsp-data` subcommand.
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::BookSource;
+use crate::glossary::{self, Term};
+use crate::linker::GlossaryHrefs;
+use crate::paths;
+
+/// Version of the JSON shape, bumped on incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// Glossary data for editor extensions.
+#[derive(Debug, Serialize)]
+struct LspData<'a> {
+    /// Version of this JSON shape.
+    version: u32,
+    /// Every glossary term, in glossary order.
+    terms: Vec<LspTerm<'a>>,
+}
+
+/// One glossary term as seen by editor extensions.
+#[derive(Debug, Serialize)]
+struct LspTerm<'a> {
+    /// Full term name as written in the glossary.
+    name: &'a str,
+    /// Short form of an acronym term, e.g. "API".
+    short_name: Option<&'a str>,
+    /// Aliases configured in `book.toml`.
+    aliases: &'a [String],
+    /// Definition text.
+    definition: Option<&'a str>,
+    /// Citation of the definition.
+    citation: Option<&'a str>,
+    /// Glossary section heading the term is listed under.
+    category: Option<&'a str>,
+    /// URL fragment of the term on its glossary page.
+    anchor: &'a str,
+    /// Glossary chapter defining the term, relative to `src`.
+    page: Option<String>,
+    /// Link target relative to the book root (site-absolute with `use-site-url`).
+    href: String,
+}
+
+/// Emits the glossary as JSON for editor completion and hover previews.
+///
+/// The shape is documented in the README and versioned by its `version` field.
+///
+/// # Errors
+///
+/// Returns an error if the book or its glossary cannot be loaded.
+pub fn lsp_data(source: &BookSource) -> Result<String> {
+    let book = source.load_book()?;
+    let terms = glossary::resolve_terms(&book, source.root(), source.src_dir(), source.config())?;
+    let config = source.config();
+    let glossary_targets = glossary::resolve_glossary_targets(&terms, config.redirects());
+    let hrefs = GlossaryHrefs::for_chapter(Path::new(""), &glossary_targets, config);
+
+    Ok(serde_json::to_string_pretty(&build_data(&terms, &hrefs))?)
+}
+
+/// Collects the serializable data for `terms`.
+fn build_data<'a>(terms: &'a [Term], hrefs: &GlossaryHrefs) -> LspData<'a> {
+    LspData {
+        version: FORMAT_VERSION,
+        terms: terms
+            .iter()
+            .map(|term| LspTerm {
+                name: term.name(),
+                short_name: term.short_name(),
+                aliases: term.aliases(),
+                definition: term.definition(),
+                citation: term.citation(),
+                category: term.category(),
+                anchor: term.anchor(),
+                page: term.page().map(paths::to_url_path),
+                href: hrefs.term_href(term),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_data() {
+        let terms = vec![
+            Term::with_definition(
+                "API (Application Programming Interface)",
+                Some("A set of protocols.".to_string()),
+            )
+            .with_aliases(vec!["apis".to_string()])
+            .with_category(Some("Web".to_string()))
+            .with_page("reference/glossary.md"),
+        ];
+        let data = build_data(&terms, &GlossaryHrefs::single("reference/glossary.html"));
+        let json = serde_json::to_value(&data).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "terms": [{
+                    "name": "API (Application Programming Interface)",
+                    "short_name": "API",
+                    "aliases": ["apis"],
+                    "definition": "A set of protocols.",
+                    "citation": null,
+                    "category": "Web",
+                    "anchor": "api-application-programming-interface",
+                    "page": "reference/glossary.md",
+                    "href": "reference/glossary.html#api-application-programming-interface",
+                }],
+            })
+        );
+    }
+}
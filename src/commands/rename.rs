@@ -0,0 +1,445 @@
+//! The `rename-term` subcommand.
+
+use std::ops::Range;
+
+use anyhow::{Context, Result, bail};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use toml_edit::{Array, DocumentMut, Item};
+
+use super::FileChange;
+use crate::BookSource;
+use crate::config::GlossarySource;
+use crate::linker::is_word_edge;
+use crate::progress::Progress;
+
+/// Options controlling what `rename-term` touches.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenameOptions {
+    /// Also rewrite occurrences of the old name in chapter prose.
+    pub rewrite_chapters: bool,
+}
+
+/// Computes the file changes needed to rename a glossary term.
+///
+/// The glossary entry is always renamed, and the term's key in
+/// `[preprocessor.termlink.terms]` or `[preprocessor.termlink.aliases]` is
+/// moved if present. With
+/// [`RenameOptions::rewrite_chapters`], whole-word occurrences of the old
+/// name in chapter prose are rewritten too; code is never touched.
+///
+/// Nothing is written; callers either print or apply the returned changes.
+///
+/// # Errors
+///
+/// Returns an error if the old term is not in the glossary, the new name is
+/// already a term, the terms come from a `glossary-file`, or any file cannot
+/// be read or parsed.
+pub fn rename_term(
+    source: &BookSource,
+    old: &str,
+    new: &str,
+    options: RenameOptions,
+) -> Result<Vec<FileChange>> {
+    let config = source.config();
+    if config.glossary_source() == GlossarySource::File {
+        bail!(
+            "Terms from glossary-file {} cannot be renamed; rename '{old}' there by hand",
+            config.glossary_file().display()
+        );
+    }
+    let mut changes = Vec::new();
+
+    // 1. Glossary entry (on whichever page defines it)
+    for glossary_file in source.glossary_files()? {
+        let glossary = std::fs::read_to_string(&glossary_file)
+            .with_context(|| format!("Failed to read {}", glossary_file.display()))?;
+
+        if find_term_title(&glossary, new).is_some() {
+            bail!("Term '{new}' already exists in the glossary");
+        }
+        if let Some(renamed_glossary) = rename_in_glossary(&glossary, old, new) {
+            changes.push(FileChange::new(glossary_file, glossary, renamed_glossary));
+        }
+    }
+    if changes.is_empty() {
+        bail!(
+            "Term '{old}' not found in glossary {}",
+            source.config().glossary_path().display()
+        );
+    }
+
+    // 2. Aliases and URLs in book.toml (or the standalone config file)
+    let config_file = source.config_file();
+    let config_toml = std::fs::read_to_string(config_file)
+        .with_context(|| format!("Failed to read {}", config_file.display()))?;
+    if let Some(renamed_toml) =
+        rename_alias_key(&config_toml, source.config_table_keys(), old, new)?
+    {
+        changes.push(FileChange::new(config_file, config_toml, renamed_toml));
+    }
+
+    // 3. Occurrences in chapters
+    if options.rewrite_chapters {
+        let paths = source.chapter_paths()?;
+        let mut progress = Progress::new(paths.len());
+        let mut rewritten_chapters = 0;
+
+        for path in paths {
+            if !source.config().is_glossary_path(&path) {
+                let content = source.read_chapter(&path)?;
+                let rewritten = rename_in_chapter(&content, old, new);
+                if rewritten != content {
+                    rewritten_chapters += 1;
+                    changes.push(FileChange::new(
+                        source.src_dir().join(&path),
+                        content,
+                        rewritten,
+                    ));
+                }
+            }
+            progress.chapter_done(&path, 0);
+        }
+
+        progress.finish(&format!("Found '{old}' in {rewritten_chapters} chapter(s)"));
+    }
+
+    Ok(changes)
+}
+
+/// Returns the source range of the definition list title whose text is `name`.
+fn find_term_title(content: &str, name: &str) -> Option<Range<usize>> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_DEFINITION_LIST);
+
+    let mut title: Option<(Range<usize>, String)> = None;
+
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::DefinitionListTitle) => {
+                title = Some((range, String::new()));
+            }
+            Event::End(TagEnd::DefinitionListTitle) => {
+                if let Some((range, text)) = title.take()
+                    && text.trim() == name
+                {
+                    return Some(range);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, ref mut title_text)) = title {
+                    title_text.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Renames the glossary entry titled `old`, returning `None` if it does not exist.
+fn rename_in_glossary(content: &str, old: &str, new: &str) -> Option<String> {
+    let range = find_term_title(content, old)?;
+    let title_source = &content[range.clone()];
+    let offset = title_source.find(old)?;
+
+    let start = range.start + offset;
+    let mut renamed = String::with_capacity(content.len() + new.len());
+    renamed.push_str(&content[..start]);
+    renamed.push_str(new);
+    renamed.push_str(&content[start + old.len()..]);
+    Some(renamed)
+}
+
+/// Moves the `old` key of the termlink `terms`, `aliases`, `urls`, `patterns`, and `priorities` tables to `new`.
+///
+/// Entries naming `old` in the `deprecated-terms`, `ignore-terms`, and
+/// `page-exclude-terms` lists are renamed too.
+///
+/// `table_keys` leads to the termlink table (`["preprocessor", "termlink"]`
+/// in `book.toml`). Returns `None` if no table has the term.
+/// Formatting and comments of the rest of the file are preserved.
+fn rename_alias_key(
+    config_toml: &str,
+    table_keys: &[&str],
+    old: &str,
+    new: &str,
+) -> Result<Option<String>> {
+    let mut doc: DocumentMut = config_toml
+        .parse()
+        .context("Failed to parse configuration file")?;
+
+    let mut item = doc.as_item_mut();
+    for key in table_keys {
+        let Some(next) = item.get_mut(key) else {
+            return Ok(None);
+        };
+        item = next;
+    }
+
+    let mut renamed = false;
+    for table_name in ["terms", "aliases", "urls", "patterns", "priorities"] {
+        let Some(table) = item.get_mut(table_name).and_then(|t| t.as_table_like_mut()) else {
+            continue;
+        };
+        if !table.contains_key(old) {
+            continue;
+        }
+        if table.contains_key(new) {
+            bail!("book.toml already defines {table_name} for '{new}'");
+        }
+        if let Some(value) = table.remove(old) {
+            table.insert(new, value);
+            renamed = true;
+        }
+    }
+
+    for list_name in ["deprecated-terms", "ignore-terms"] {
+        if let Some(list) = item.get_mut(list_name).and_then(Item::as_array_mut) {
+            renamed |= rename_in_list(list, old, new);
+        }
+    }
+    if let Some(pages) = item
+        .get_mut("page-exclude-terms")
+        .and_then(|t| t.as_table_like_mut())
+    {
+        for (_, terms) in pages.iter_mut() {
+            if let Some(list) = terms.as_array_mut() {
+                renamed |= rename_in_list(list, old, new);
+            }
+        }
+    }
+
+    Ok(renamed.then(|| doc.to_string()))
+}
+
+/// Replaces the `old` entries of a list of term names with `new`, keeping their formatting.
+///
+/// Returns whether any entry was renamed.
+fn rename_in_list(list: &mut Array, old: &str, new: &str) -> bool {
+    let mut renamed = false;
+    for value in list.iter_mut() {
+        if value.as_str() == Some(old) {
+            let decor = value.decor().clone();
+            *value = new.into();
+            *value.decor_mut() = decor;
+            renamed = true;
+        }
+    }
+    renamed
+}
+
+/// Rewrites whole-word occurrences of `old` in chapter prose.
+///
+/// Words are delimited as the linker finds terms, so names starting or
+/// ending with a symbol ("C++", "API (Application Programming Interface)")
+/// are found too. Only text outside code blocks and inline code is touched;
+/// the rest of the chapter is left byte-identical.
+fn rename_in_chapter(content: &str, old: &str, new: &str) -> String {
+    if old.is_empty() {
+        return content.to_string();
+    }
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_DEFINITION_LIST);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut code_depth = 0usize;
+    let mut edits: Vec<Range<usize>> = Vec::new();
+
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => code_depth += 1,
+            Event::End(TagEnd::CodeBlock) => code_depth = code_depth.saturating_sub(1),
+            Event::Text(_) if code_depth == 0 => {
+                edits.extend(
+                    whole_words(&content[range.clone()], old)
+                        .map(|m| range.start + m.start..range.start + m.end),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let mut rewritten = String::with_capacity(content.len());
+    let mut last_end = 0;
+    for edit in edits {
+        // Offset ranges of nested events can repeat; skip anything already rewritten
+        if edit.start < last_end {
+            continue;
+        }
+        rewritten.push_str(&content[last_end..edit.start]);
+        rewritten.push_str(new);
+        last_end = edit.end;
+    }
+    rewritten.push_str(&content[last_end..]);
+    rewritten
+}
+
+/// Returns the ranges of the whole-word occurrences of `word` in `text`.
+fn whole_words<'a>(text: &'a str, word: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+    text.match_indices(word).filter_map(move |(start, _)| {
+        let end = start + word.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        (is_word_edge(before, word.chars().next()) && is_word_edge(after, word.chars().next_back()))
+            .then_some(start..end)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOK_TOML_KEYS: &[&str] = &["preprocessor", "termlink"];
+
+    const GLOSSARY: &str = "# Glossary
+
+API (Application Programming Interface)
+: A set of protocols.
+
+REST
+: Representational State Transfer.
+";
+
+    #[test]
+    fn test_rename_in_glossary() {
+        let renamed = rename_in_glossary(GLOSSARY, "REST", "RESTful API").unwrap();
+        assert!(renamed.contains("\nRESTful API\n: Representational State Transfer."));
+        assert!(renamed.contains("API (Application Programming Interface)\n"));
+    }
+
+    #[test]
+    fn test_rename_in_glossary_full_name_with_short_form() {
+        let renamed = rename_in_glossary(
+            GLOSSARY,
+            "API (Application Programming Interface)",
+            "API (Application Program Interface)",
+        )
+        .unwrap();
+        assert!(renamed.contains("API (Application Program Interface)\n: A set"));
+    }
+
+    #[test]
+    fn test_rename_in_glossary_missing_term() {
+        assert_eq!(rename_in_glossary(GLOSSARY, "XPT", "XPORT"), None);
+    }
+
+    #[test]
+    fn test_rename_alias_key() {
+        let book_toml = r#"[book]
+title = "Test"
+
+[preprocessor.termlink.aliases]
+# REST variants
+REST = ["RESTful"]
+API = ["apis"]
+"#;
+        let renamed = rename_alias_key(book_toml, BOOK_TOML_KEYS, "REST", "REST API")
+            .unwrap()
+            .unwrap();
+        assert!(renamed.contains(r#""REST API" = ["RESTful"]"#));
+        assert!(!renamed.contains("\nREST = "));
+        assert!(renamed.contains("title = \"Test\""));
+    }
+
+    #[test]
+    fn test_rename_alias_key_moves_term_table() {
+        let book_toml = "[preprocessor.termlink.terms.REST]\ncss-class = \"rest\"\n";
+        let renamed = rename_alias_key(book_toml, BOOK_TOML_KEYS, "REST", "REST API")
+            .unwrap()
+            .unwrap();
+        assert!(renamed.contains("[preprocessor.termlink.terms.\"REST API\"]"));
+        assert!(!renamed.contains("terms.REST]"));
+    }
+
+    #[test]
+    fn test_rename_alias_key_moves_url() {
+        let book_toml = "[preprocessor.termlink.urls]\nRFC = \"https://example.com\"\n";
+        let renamed = rename_alias_key(book_toml, BOOK_TOML_KEYS, "RFC", "RFC 7231")
+            .unwrap()
+            .unwrap();
+        assert!(renamed.contains(r#""RFC 7231" = "https://example.com""#));
+    }
+
+    #[test]
+    fn test_rename_alias_key_moves_priority() {
+        let book_toml = "[preprocessor.termlink.priorities]\nREST = 10\nAPI = 1\n";
+        let renamed = rename_alias_key(book_toml, BOOK_TOML_KEYS, "REST", "REST API")
+            .unwrap()
+            .unwrap();
+        assert!(renamed.contains(r#""REST API" = 10"#));
+        assert!(!renamed.contains("\nREST = "));
+        assert!(renamed.contains("API = 1"));
+    }
+
+    #[test]
+    fn test_rename_alias_key_renames_name_lists() {
+        let book_toml = r#"[preprocessor.termlink]
+deprecated-terms = ["REST", "SOAP"]
+ignore-terms = [
+    "REST", # too common here
+]
+
+[preprocessor.termlink.page-exclude-terms]
+"intro.md" = ["API", "REST"]
+"#;
+        let renamed = rename_alias_key(book_toml, BOOK_TOML_KEYS, "REST", "REST API")
+            .unwrap()
+            .unwrap();
+        assert!(renamed.contains(r#"deprecated-terms = ["REST API", "SOAP"]"#));
+        assert!(renamed.contains("    \"REST API\", # too common here\n"));
+        assert!(renamed.contains(r#""intro.md" = ["API", "REST API"]"#));
+    }
+
+    #[test]
+    fn test_rename_alias_key_without_aliases() {
+        let book_toml = "[preprocessor.termlink]\nglossary-path = \"glossary.md\"\n";
+        assert_eq!(
+            rename_alias_key(book_toml, BOOK_TOML_KEYS, "REST", "REST API").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rename_alias_key_conflict() {
+        let book_toml = "[preprocessor.termlink.aliases]\nREST = [\"a\"]\nHTTP = [\"b\"]\n";
+        assert!(rename_alias_key(book_toml, BOOK_TOML_KEYS, "REST", "HTTP").is_err());
+    }
+
+    #[test]
+    fn test_rename_alias_key_standalone_config() {
+        let config_toml = "glossary-path = \"glossary.md\"\n\n[aliases]\nREST = [\"RESTful\"]\n";
+        let renamed = rename_alias_key(config_toml, &[], "REST", "REST API")
+            .unwrap()
+            .unwrap();
+        assert!(renamed.contains(r#""REST API" = ["RESTful"]"#));
+    }
+
+    #[test]
+    fn test_rename_in_chapter_skips_code() {
+        let content = "Use REST here.\n\n```\nREST in code\n```\n\nInline `REST` and RESTful.\n";
+        let rewritten = rename_in_chapter(content, "REST", "HTTP");
+        assert_eq!(
+            rewritten,
+            "Use HTTP here.\n\n```\nREST in code\n```\n\nInline `REST` and RESTful.\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_in_chapter_symbol_edges() {
+        let content =
+            "Write C++, not C++20.\n\nThe API (Application Programming Interface) here.\n";
+        assert_eq!(
+            rename_in_chapter(content, "C++", "Cpp"),
+            "Write Cpp, not C++20.\n\nThe API (Application Programming Interface) here.\n"
+        );
+        assert_eq!(
+            rename_in_chapter(content, "API (Application Programming Interface)", "API"),
+            "Write C++, not C++20.\n\nThe API here.\n"
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! Configuration parsing for the termlink preprocessor.
 
-use std::collections::HashMap;
+mod builder;
+
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
@@ -8,47 +10,642 @@ use glob::Pattern;
 use mdbook_preprocessor::PreprocessorContext;
 use serde::Deserialize;
 
+use crate::paths;
+
+pub use builder::ConfigBuilder;
+
 /// Configuration for the termlink preprocessor.
 ///
 /// All fields are private to allow future changes without breaking the API.
-/// Use the getter methods to access configuration values.
-#[derive(Debug, Clone)]
+/// Use the getter methods to access configuration values, and
+/// [`Config::builder`] to configure termlink in code.
+#[derive(Debug, Clone, Hash)]
+#[allow(clippy::struct_excessive_bools)] // independent on/off switches from book.toml
 pub struct Config {
-    /// Path to the glossary file relative to src directory.
+    /// Path to the glossary file (or directory of glossary pages) relative to src directory.
     glossary_path: PathBuf,
+    /// Glossary pages applying only to the chapters below a directory (directory, page), relative to src.
+    scoped_glossaries: Vec<(PathBuf, PathBuf)>,
+    /// Where glossary terms are read from.
+    glossary_source: GlossarySource,
+    /// Term file relative to the book root, read with `glossary-source = "file"`.
+    glossary_file: PathBuf,
+    /// Header names of the term fields in a CSV term file.
+    csv_columns: CsvColumns,
     /// Whether to only link the first occurrence of each term per page.
     link_first_only: bool,
+    /// Part of the book within which `link-first-only` links each term once.
+    link_scope: LinkScope,
     /// CSS class to apply to glossary term links.
     css_class: String,
+    /// How matched terms are marked up.
+    output: Output,
+    /// How term definitions are shown on hover.
+    tooltip: Tooltip,
+    /// Part of the definition shown in the `title` tooltip.
+    tooltip_source: TooltipSource,
+    /// Longest `title` tooltip definition, in characters (unlimited when unset).
+    tooltip_max_length: Option<usize>,
     /// Whether term matching should be case-sensitive.
     case_sensitive: bool,
+    /// Whether plural forms of terms match too.
+    match_inflections: bool,
+    /// Whether hyphens and whitespace between words match each other.
+    flexible_separators: bool,
+    /// Whether diacritics are ignored when matching terms ("resume" matches "résumé").
+    ignore_diacritics: bool,
+    /// Shortest term form (in characters) that is matched in chapters.
+    min_term_length: usize,
+    /// Names of terms that are never linked.
+    ignore_terms: Vec<String>,
+    /// Most links added to one page (unlimited when unset).
+    max_links_per_page: Option<usize>,
     /// Glob patterns for pages to exclude from term linking.
     exclude_pages: Vec<Pattern>,
-    /// Additional aliases for terms (term name -> list of aliases).
-    aliases: HashMap<String, Vec<String>>,
+    /// Glob patterns for the only pages to link (all pages when empty).
+    include_pages: Vec<Pattern>,
+    /// Terms not linked on specific pages (page path -> term names).
+    page_exclude_terms: BTreeMap<String, Vec<String>>,
+    /// Elements whose text is not linked.
+    skip_contexts: Vec<SkipContext>,
+    /// Whether terms in the text of raw HTML blocks are linked.
+    link_in_html: bool,
+    /// Settings of single terms (term name -> settings), from `terms` and `aliases`.
+    terms: BTreeMap<String, TermOverride>,
+    /// Terms defined in `book.toml` rather than a glossary (term name -> definition).
+    inline_terms: BTreeMap<String, InlineTerm>,
+    /// External link targets for terms (term name -> URL).
+    urls: BTreeMap<String, String>,
+    /// Regexes matching further forms of terms (term name -> regex).
+    patterns: BTreeMap<String, String>,
+    /// Priorities of terms whose overlapping occurrences win (term name -> priority).
+    priorities: BTreeMap<String, i32>,
+    /// Extra link classes for term categories (category -> class).
+    category_classes: BTreeMap<String, String>,
+    /// Names of terms being phased out.
+    deprecated_terms: Vec<String>,
+    /// Site URL prefix for absolute glossary links (set when `use-site-url` is enabled).
+    site_url: Option<String>,
+    /// mdBook's `output.html.redirect` map (old path -> new URL).
+    redirects: BTreeMap<String, String>,
+    /// Whether links point at the glossary's markdown sources rather than its HTML pages.
+    link_sources: bool,
+    /// Whether only the definitions of a glossary page are linked, each without its own term.
+    definitions_only: bool,
+    /// Marker comments whose enclosing block is never linked.
+    protected_markers: Vec<String>,
+    /// Opening and closing delimiters of math, whose content is never linked.
+    math_delimiters: Vec<(String, String)>,
+    /// Languages of the book and its glossaries.
+    languages: GlossaryLanguages,
+    /// Whether to add a screen-reader-only expansion after the first linked acronym.
+    sr_expansion: bool,
+    /// Whether the first linked acronym on a page is followed by its long form in parentheses.
+    expand_acronyms: bool,
+    /// How to export the list of terms linked on each page.
+    page_terms: PageTerms,
+    /// Whether later occurrences link to the first mention on the page.
+    link_to_first_mention: bool,
+    /// Where to add the "Terms used on this page" list.
+    terms_summary: TermsSummary,
+    /// Heading text of the "Terms used on this page" list.
+    terms_summary_title: String,
+    /// Number of terms in the "Key terms" callout (0 disables it).
+    key_terms: usize,
+    /// Heading text of the "Key terms" callout.
+    key_terms_title: String,
+    /// Whether glossary entries list the chapters linking to them.
+    backlinks: bool,
+    /// Label of the list of chapters linking to a glossary entry.
+    backlinks_title: String,
+    /// Whether terms in glossary definitions are linked to their own entries.
+    link_glossary: bool,
+    /// Whether a chapter listing the acronyms is appended to the book.
+    generate_acronym_list: bool,
+    /// Title of the acronym list chapter.
+    acronym_list_title: String,
+    /// Whether glossary pages get an A–Z index.
+    glossary_index: GlossaryIndex,
+    /// How the anchors of glossary entries are derived from their names.
+    anchor_style: AnchorStyle,
+    /// Key that opens the glossary page (`None` when disabled).
+    glossary_shortcut: Option<char>,
+    /// Whether `output.html.additional-js` loads `termlink.js`, which handles the shortcut.
+    termlink_js: bool,
+    /// How much the preprocessor logs.
+    log: LogLevel,
+    /// File relative to the book root to write term usage statistics to.
+    report: Option<PathBuf>,
+    /// File relative to the book root to log every link added to, as JSON lines.
+    replacement_log: Option<PathBuf>,
+    /// Whether to warn about glossary terms no chapter links to.
+    warn_unused_terms: bool,
+    /// Whether chapter errors and unused terms fail the build instead of being logged.
+    strict: bool,
+    /// Whether to print the changes as a diff and leave the book unchanged.
+    dry_run: bool,
+    /// File relative to the book root caching linked chapters between builds.
+    cache: Option<PathBuf>,
+}
+
+/// How much the preprocessor logs when `RUST_LOG` is not set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Errors only.
+    Quiet,
+    /// Warnings and a short summary per build.
+    #[default]
+    Normal,
+    /// Also explain per-chapter decisions, such as skipped pages.
+    Verbose,
+}
+
+impl LogLevel {
+    /// Returns the maximum log level this setting allows.
+    #[must_use]
+    pub const fn filter(self) -> log::LevelFilter {
+        match self {
+            Self::Quiet => log::LevelFilter::Error,
+            Self::Normal => log::LevelFilter::Info,
+            Self::Verbose => log::LevelFilter::Debug,
+        }
+    }
+}
+
+/// Part of the book within which `link-first-only` links each term once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkScope {
+    /// The whole page (`link-first-only = true`).
+    #[default]
+    PerPage,
+    /// Each section, starting at a level 1 or 2 heading.
+    PerSection,
+    /// The whole book: each term links only at its first occurrence in book order.
+    PerBook,
+}
+
+/// `link-first-only` as written: a switch, or the scope of the first link.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+enum RawLinkFirstOnly {
+    Enabled(bool),
+    Scope(LinkScope),
+}
+
+/// Where glossary terms are read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlossarySource {
+    /// Definition lists in the glossary chapter(s).
+    #[default]
+    Chapter,
+    /// A structured term file (`glossary-file`); links still point to the glossary chapter.
+    File,
+}
+
+/// Header names of the term fields in a CSV term file.
+///
+/// Spreadsheet exports rarely use the field names termlink expects, so each
+/// field can be mapped to the header of the column holding it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CsvColumns {
+    /// Column holding the term name.
+    pub(crate) name: String,
+    /// Column holding the definition.
+    pub(crate) definition: String,
+    /// Column holding the aliases, separated by `alias-separator`.
+    pub(crate) aliases: String,
+    /// Separator between aliases within the aliases column.
+    pub(crate) alias_separator: String,
+    /// Column holding a custom anchor.
+    pub(crate) anchor: String,
+    /// Column holding the citation.
+    pub(crate) citation: String,
+    /// Column holding the category.
+    pub(crate) category: String,
+    /// Column holding a URL to link to instead of the glossary.
+    pub(crate) url: String,
+    /// Column holding the priority over overlapping terms.
+    pub(crate) priority: String,
+    /// Column holding the language of the entry.
+    pub(crate) lang: String,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        Self {
+            name: String::from("name"),
+            definition: String::from("definition"),
+            aliases: String::from("aliases"),
+            alias_separator: String::from(";"),
+            anchor: String::from("anchor"),
+            citation: String::from("citation"),
+            category: String::from("category"),
+            url: String::from("url"),
+            priority: String::from("priority"),
+            lang: String::from("lang"),
+        }
+    }
+}
+
+/// Settings of one term, from `[preprocessor.termlink.terms."API"]`.
+///
+/// Each setting left out falls back to the book-wide one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TermOverride {
+    /// Further names matched as the term.
+    pub aliases: Vec<String>,
+    /// Class added to the term's links, after `css-class`.
+    pub css_class: Option<String>,
+    /// URL to link to instead of the glossary entry.
+    pub url: Option<String>,
+    /// Whether the term is matched case-sensitively.
+    pub case_sensitive: Option<bool>,
+    /// Whether only the first occurrence of the term on a page is linked.
+    pub link_first_only: Option<bool>,
+    /// Whether the term is never linked, as with `ignore-terms`.
+    pub disabled: bool,
+}
+
+/// A term defined in `[preprocessor.termlink.inline-terms]`, for books without a glossary.
+///
+/// Its occurrences get a tooltip but no link, unless it has a `url`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InlineTerm {
+    /// Definition in markdown, shown as plain text in tooltips.
+    pub definition: Option<String>,
+    /// URL the term links to.
+    pub url: Option<String>,
+}
+
+/// An inline term as written: just its definition, or a table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum RawInlineTerm {
+    Definition(String),
+    Table(InlineTerm),
+}
+
+/// A glossary page from `[preprocessor.termlink.scoped-glossaries]`, given as a table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScopedGlossary {
+    /// The glossary page, relative to `src`.
+    pub path: String,
+    /// Language of the glossary, declared on links if it differs from the book's.
+    pub lang: Option<String>,
+}
+
+/// A scoped glossary as written: just its page, or a table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum RawScopedGlossary {
+    Page(String),
+    Table(ScopedGlossary),
+}
+
+/// The languages of the book and its glossaries.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GlossaryLanguages {
+    /// `book.language`.
+    book: String,
+    /// `glossary-lang`, for glossaries without a language of their own.
+    glossary: Option<String>,
+    /// The `glossary-paths` language the glossary was picked for.
+    main: Option<String>,
+    /// The `lang` of each scoped glossary page declaring one.
+    scoped: BTreeMap<PathBuf, String>,
+}
+
+impl Default for GlossaryLanguages {
+    fn default() -> Self {
+        Self {
+            book: String::from("en"),
+            glossary: None,
+            main: None,
+            scoped: BTreeMap::new(),
+        }
+    }
+}
+
+/// How matched terms are marked up in chapters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Output {
+    /// A link to the term's glossary entry.
+    #[default]
+    Link,
+    /// A `<span>` carrying the class and tooltip, without navigation.
+    Span,
+    /// A `<strong>` element carrying the class and tooltip, without navigation.
+    Bold,
+    /// A link whose text is an `<abbr>` spelling out the term's short form.
+    Abbr,
+    /// A markdown link, leaving no HTML in the chapter for non-HTML renderers.
+    Markdown,
+}
+
+/// How term definitions are shown when hovering a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tooltip {
+    /// The browser's native tooltip, from the `title` attribute.
+    #[default]
+    Title,
+    /// A hover card showing the formatted definition (needs `termlink.js`, see `install`).
+    Popover,
+}
+
+/// An element whose text is not linked, listed in `skip-contexts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipContext {
+    /// Headings.
+    Heading,
+    /// Block quotes.
+    Blockquote,
+    /// Whole tables.
+    Table,
+    /// The header row of tables.
+    TableHeader,
+    /// List items.
+    List,
+    /// Footnote definitions.
+    Footnote,
+}
+
+/// Which part of a definition the `title` tooltip shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TooltipSource {
+    /// The first sentence.
+    FirstSentence,
+    /// The first paragraph.
+    FirstParagraph,
+    /// The whole definition.
+    #[default]
+    Full,
+}
+
+/// What the A–Z index does to glossary pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlossaryIndex {
+    /// Glossary pages are left as written.
+    #[default]
+    Off,
+    /// A line of letter links is added below the page title.
+    Navigation,
+    /// The page is rewritten with its terms sorted under letter headings.
+    Generate,
+}
+
+/// How the anchors of glossary entries are derived from their names, matching
+/// the tool that renders the glossary page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorStyle {
+    /// mdBook's ids: runs of other characters become one hyphen.
+    #[default]
+    Mdbook,
+    /// GitHub's ids: spaces become hyphens and punctuation is dropped.
+    Github,
+    /// No generated ids: every entry declares its anchor as `{#id}`.
+    Custom,
+}
+
+/// Where the "Terms used on this page" list is added to each chapter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TermsSummary {
+    /// No list is added.
+    #[default]
+    Off,
+    /// At the start of the chapter.
+    Prepend,
+    /// At the end of the chapter.
+    Append,
+}
+
+/// How the terms linked on a page are exported for downstream tooling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageTerms {
+    /// Nothing is exported.
+    #[default]
+    Off,
+    /// An HTML comment listing the anchors of the linked terms.
+    Comment,
+    /// A `<script type="application/json">` element with name, anchor, and link of each term.
+    Json,
 }
 
+/// Marker comments consumed by other preprocessors (mdbook-toc, mdBook anchors).
+const DEFAULT_PROTECTED_MARKERS: &[&str] = &["<!-- toc -->", "<!-- ANCHOR:", "<!-- ANCHOR_END:"];
+
+/// Default term file read with `glossary-source = "file"`.
+const DEFAULT_GLOSSARY_FILE: &str = "glossary.toml";
+
+/// Default heading of the "Terms used on this page" list.
+const DEFAULT_TERMS_SUMMARY_TITLE: &str = "Terms used on this page";
+
+/// Default heading of the "Key terms" callout.
+const DEFAULT_KEY_TERMS_TITLE: &str = "Key terms";
+
+/// Default label of the chapters linking to a glossary entry.
+const DEFAULT_BACKLINKS_TITLE: &str = "Referenced in";
+
+/// Default title of the acronym list chapter.
+const DEFAULT_ACRONYM_LIST_TITLE: &str = "Acronyms";
+
+/// Default key that opens the glossary page.
+const DEFAULT_GLOSSARY_SHORTCUT: char = 'g';
+
+/// Environment variable mdBook reads to override `book.language`.
+const LANGUAGE_ENV: &str = "MDBOOK_BOOK__LANGUAGE";
+
 /// Raw configuration as deserialized from book.toml.
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 struct RawConfig {
     glossary_path: Option<String>,
-    link_first_only: Option<bool>,
+    glossary_paths: Option<BTreeMap<String, String>>,
+    scoped_glossaries: Option<BTreeMap<String, RawScopedGlossary>>,
+    glossary_source: Option<GlossarySource>,
+    glossary_file: Option<String>,
+    csv_columns: Option<CsvColumns>,
+    link_first_only: Option<RawLinkFirstOnly>,
     css_class: Option<String>,
+    output: Option<Output>,
+    tooltip: Option<Tooltip>,
+    tooltip_source: Option<TooltipSource>,
+    tooltip_max_length: Option<usize>,
     case_sensitive: Option<bool>,
+    match_inflections: Option<bool>,
+    flexible_separators: Option<bool>,
+    ignore_diacritics: Option<bool>,
+    min_term_length: Option<usize>,
+    ignore_terms: Option<Vec<String>>,
+    max_links_per_page: Option<usize>,
     exclude_pages: Option<Vec<String>>,
-    aliases: Option<HashMap<String, Vec<String>>>,
+    include_pages: Option<Vec<String>>,
+    page_exclude_terms: Option<BTreeMap<String, Vec<String>>>,
+    skip_contexts: Option<Vec<SkipContext>>,
+    link_in_html: Option<bool>,
+    aliases: Option<BTreeMap<String, Vec<String>>>,
+    terms: Option<BTreeMap<String, TermOverride>>,
+    inline_terms: Option<BTreeMap<String, RawInlineTerm>>,
+    urls: Option<BTreeMap<String, String>>,
+    patterns: Option<BTreeMap<String, String>>,
+    priorities: Option<BTreeMap<String, i32>>,
+    category_classes: Option<BTreeMap<String, String>>,
+    deprecated_terms: Option<Vec<String>>,
+    use_site_url: Option<bool>,
+    print_page: Option<bool>,
+    protected_markers: Option<Vec<String>>,
+    math_delimiters: Option<Vec<(String, String)>>,
+    glossary_lang: Option<String>,
+    sr_expansion: Option<bool>,
+    expand_acronyms: Option<bool>,
+    page_terms: Option<PageTerms>,
+    link_to_first_mention: Option<bool>,
+    terms_summary: Option<TermsSummary>,
+    terms_summary_title: Option<String>,
+    key_terms: Option<usize>,
+    key_terms_title: Option<String>,
+    backlinks: Option<bool>,
+    backlinks_title: Option<String>,
+    link_glossary: Option<bool>,
+    generate_acronym_list: Option<bool>,
+    acronym_list_title: Option<String>,
+    glossary_index: Option<GlossaryIndex>,
+    anchor_style: Option<AnchorStyle>,
+    glossary_shortcut: Option<String>,
+    log: Option<LogLevel>,
+    report: Option<String>,
+    replacement_log: Option<String>,
+    warn_unused_terms: Option<bool>,
+    strict: Option<bool>,
+    dry_run: Option<bool>,
+    cache: Option<String>,
+}
+
+/// Deserializes a termlink table for `renderer`.
+///
+/// The `renderer.<name>` table of the renderer, if any, overrides the other
+/// settings. Renderers other than HTML default to `output = "markdown"`, as
+/// the other output forms are HTML.
+fn raw_config(mut table: toml::Table, renderer: &str) -> Result<RawConfig> {
+    let profiles = table.remove("renderer");
+    if renderer != "html" {
+        table.insert("output".to_string(), "markdown".into());
+    }
+    if let Some(profile) = profiles.as_ref().and_then(|p| p.get(renderer)) {
+        table.extend(toml_table(profile)?);
+    }
+    Ok(toml::Value::Table(table).try_into()?)
+}
+
+/// Returns the table `value` holds.
+fn toml_table(value: &toml::Value) -> Result<toml::Table> {
+    value
+        .as_table()
+        .cloned()
+        .with_context(|| format!("Expected a table, found {}", value.type_str()))
+}
+
+/// Book-wide settings read from outside `[preprocessor.termlink]`.
+#[derive(Debug, Clone, Default)]
+struct BookSettings {
+    /// `book.language`.
+    language: Option<String>,
+    /// `output.html.site-url`.
+    site_url: Option<String>,
+    /// `output.html.redirect`.
+    redirects: BTreeMap<String, String>,
+    /// `output.html.additional-js`.
+    additional_js: Vec<String>,
+}
+
+impl BookSettings {
+    /// Returns whether the book's scripts include `termlink.js`.
+    fn loads_termlink_js(&self) -> bool {
+        self.additional_js
+            .iter()
+            .any(|script| script.ends_with("termlink.js"))
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             glossary_path: PathBuf::from("reference/glossary.md"),
+            scoped_glossaries: Vec::new(),
+            glossary_source: GlossarySource::Chapter,
+            glossary_file: PathBuf::from(DEFAULT_GLOSSARY_FILE),
+            csv_columns: CsvColumns::default(),
             link_first_only: true,
+            link_scope: LinkScope::PerPage,
             css_class: String::from("glossary-term"),
+            output: Output::Link,
+            tooltip: Tooltip::Title,
+            tooltip_source: TooltipSource::Full,
+            tooltip_max_length: None,
             case_sensitive: false,
+            match_inflections: false,
+            flexible_separators: false,
+            ignore_diacritics: false,
+            min_term_length: 0,
+            ignore_terms: Vec::new(),
+            max_links_per_page: None,
             exclude_pages: Vec::new(),
-            aliases: HashMap::new(),
+            include_pages: Vec::new(),
+            page_exclude_terms: BTreeMap::new(),
+            skip_contexts: vec![SkipContext::Heading],
+            link_in_html: false,
+            terms: BTreeMap::new(),
+            inline_terms: BTreeMap::new(),
+            urls: BTreeMap::new(),
+            patterns: BTreeMap::new(),
+            priorities: BTreeMap::new(),
+            category_classes: BTreeMap::new(),
+            deprecated_terms: Vec::new(),
+            site_url: None,
+            redirects: BTreeMap::new(),
+            link_sources: false,
+            definitions_only: false,
+            protected_markers: default_protected_markers(),
+            math_delimiters: Vec::new(),
+            languages: GlossaryLanguages::default(),
+            sr_expansion: false,
+            expand_acronyms: false,
+            page_terms: PageTerms::Off,
+            link_to_first_mention: false,
+            terms_summary: TermsSummary::Off,
+            terms_summary_title: String::from(DEFAULT_TERMS_SUMMARY_TITLE),
+            key_terms: 0,
+            key_terms_title: String::from(DEFAULT_KEY_TERMS_TITLE),
+            backlinks: false,
+            backlinks_title: String::from(DEFAULT_BACKLINKS_TITLE),
+            link_glossary: false,
+            generate_acronym_list: false,
+            acronym_list_title: String::from(DEFAULT_ACRONYM_LIST_TITLE),
+            glossary_index: GlossaryIndex::Off,
+            anchor_style: AnchorStyle::Mdbook,
+            glossary_shortcut: Some(DEFAULT_GLOSSARY_SHORTCUT),
+            termlink_js: false,
+            log: LogLevel::Normal,
+            report: None,
+            replacement_log: None,
+            warn_unused_terms: false,
+            strict: false,
+            dry_run: false,
+            cache: None,
         }
     }
 }
@@ -60,185 +657,1794 @@ impl Config {
     ///
     /// Returns an error if the configuration in `book.toml` is malformed.
     pub fn from_context(ctx: &PreprocessorContext) -> Result<Self> {
-        // Get all preprocessor configs as a BTreeMap
-        let preprocessors: std::collections::BTreeMap<String, RawConfig> = ctx
+        // Get the termlink config for the renderer being built, or use defaults
+        let table: Option<toml::Table> = ctx
             .config
-            .preprocessors()
+            .get("preprocessor.termlink")
             .context("Failed to parse preprocessor configuration")?;
+        let raw = raw_config(table.unwrap_or_default(), &ctx.renderer)
+            .context("Failed to parse [preprocessor.termlink] configuration")?;
 
-        // Get the termlink config, or use defaults
-        let raw = preprocessors.get("termlink").cloned().unwrap_or_default();
+        let redirects: Option<BTreeMap<String, String>> = ctx
+            .config
+            .get("output.html.redirect")
+            .context("Failed to parse output.html.redirect")?;
+        let book = BookSettings {
+            language: ctx.config.book.language.clone(),
+            site_url: ctx
+                .config
+                .get("output.html.site-url")
+                .context("Failed to parse output.html.site-url")?,
+            redirects: redirects.unwrap_or_default(),
+            additional_js: ctx
+                .config
+                .get("output.html.additional-js")
+                .context("Failed to parse output.html.additional-js")?
+                .unwrap_or_default(),
+        };
 
-        // Parse exclude-pages glob patterns with warnings for invalid patterns
-        let exclude_pages: Vec<Pattern> = raw
-            .exclude_pages
-            .unwrap_or_default()
-            .iter()
-            .filter_map(|p| match Pattern::new(p) {
-                Ok(pattern) => Some(pattern),
-                Err(e) => {
-                    log::warn!("Invalid exclude-pages glob pattern '{p}': {e}");
-                    None
-                }
-            })
-            .collect();
+        Ok(Self::from_raw(raw, book).for_renderer(&ctx.renderer))
+    }
+
+    /// Creates configuration from the `[preprocessor.termlink]` table of a parsed `book.toml`.
+    ///
+    /// Used by the standalone subcommands, which read `book.toml` from disk
+    /// instead of receiving a [`PreprocessorContext`] from mdBook.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the termlink table is malformed.
+    pub fn from_book_toml(book_toml: &toml::Table) -> Result<Self> {
+        let raw = book_toml
+            .get("preprocessor")
+            .and_then(|p| p.get("termlink"))
+            .map(|table| raw_config(toml_table(table)?, "html"))
+            .transpose()
+            .context("Failed to parse [preprocessor.termlink] configuration")?
+            .unwrap_or_default();
+
+        let html = book_toml.get("output").and_then(|o| o.get("html"));
+        let book = BookSettings {
+            // mdBook lets the environment override `book.language`, as translation builds do
+            language: std::env::var(LANGUAGE_ENV).ok().or_else(|| {
+                book_toml
+                    .get("book")
+                    .and_then(|b| b.get("language"))
+                    .and_then(toml::Value::as_str)
+                    .map(String::from)
+            }),
+            site_url: html
+                .and_then(|h| h.get("site-url"))
+                .and_then(toml::Value::as_str)
+                .map(String::from),
+            redirects: html
+                .and_then(|h| h.get("redirect"))
+                .map(|r| r.clone().try_into())
+                .transpose()
+                .context("Failed to parse output.html.redirect")?
+                .unwrap_or_default(),
+            additional_js: html
+                .and_then(|h| h.get("additional-js"))
+                .map(|js| js.clone().try_into())
+                .transpose()
+                .context("Failed to parse output.html.additional-js")?
+                .unwrap_or_default(),
+        };
+
+        Ok(Self::from_raw(raw, book))
+    }
+
+    /// Adapts the configuration to the renderer the book is built for.
+    ///
+    /// Renderers other than HTML get markdown links to the glossary's
+    /// markdown sources (`glossary.md#api`), which they resolve themselves,
+    /// and the key terms and terms summary as markdown too. What needs the
+    /// HTML renderer is dropped: `site-url`, redirects, the glossary shortcut,
+    /// the screen-reader expansion, and the JSON page terms, which are
+    /// exported in a comment instead.
+    #[must_use]
+    pub fn for_renderer(&self, renderer: &str) -> Self {
+        let mut config = self.clone();
+        if renderer != "html" {
+            config.link_sources = config.output == Output::Markdown;
+            config.site_url = None;
+            config.redirects.clear();
+            config.glossary_shortcut = None;
+            config.sr_expansion = false;
+            if config.page_terms == PageTerms::Json {
+                config.page_terms = PageTerms::Comment;
+            }
+        }
+        config
+    }
+
+    /// Adapts the configuration to linking the definitions on a glossary page.
+    ///
+    /// Each entry links a term once, and never to itself. The page-level
+    /// additions of chapters (summaries, key terms, the glossary shortcut)
+    /// and the per-page link limit do not apply.
+    #[must_use]
+    pub fn for_glossary_page(&self) -> Self {
+        let mut config = self.clone();
+        config.definitions_only = true;
+        config.link_first_only = true;
+        config.link_scope = LinkScope::PerPage;
+        config.link_to_first_mention = false;
+        config.max_links_per_page = None;
+        config.page_terms = PageTerms::Off;
+        config.terms_summary = TermsSummary::Off;
+        config.key_terms = 0;
+        config.glossary_shortcut = None;
+        config
+    }
+
+    /// Creates configuration from a standalone termlink table.
+    ///
+    /// The table holds the same keys as `[preprocessor.termlink]`, at the top
+    /// level. This is the format of the file passed to `--config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table is malformed.
+    pub fn from_termlink_toml(table: &toml::Table) -> Result<Self> {
+        let raw =
+            raw_config(table.clone(), "html").context("Failed to parse termlink configuration")?;
+        Ok(Self::from_raw(raw, BookSettings::default()))
+    }
+
+    /// Converts the raw deserialized configuration, applying defaults.
+    ///
+    /// `book` carries the settings termlink reads from the rest of `book.toml`.
+    fn from_raw(raw: RawConfig, book: BookSettings) -> Self {
+        let termlink_js = book.loads_termlink_js();
+        let (glossary_path, languages) = glossary_path(
+            raw.glossary_path,
+            raw.glossary_paths,
+            raw.glossary_lang,
+            book.language,
+        );
+        let (scoped_glossaries, languages) = scoped_glossaries(raw.scoped_glossaries, languages);
+
+        // Only meaningful when later occurrences would otherwise stay unlinked
+        let print_page = raw.print_page.unwrap_or(false);
+        let (link_first_only, link_scope) = link_first_only(raw.link_first_only, print_page);
+        let output = raw.output.unwrap_or_default();
+        // Each page has one first mention to link back to
+        let link_to_first_mention = raw.link_to_first_mention.unwrap_or(false)
+            && link_first_only
+            && link_scope == LinkScope::PerPage
+            && matches!(output, Output::Link | Output::Abbr);
 
-        Ok(Self {
-            glossary_path: raw
-                .glossary_path
-                .map_or_else(|| PathBuf::from("reference/glossary.md"), PathBuf::from),
-            link_first_only: raw.link_first_only.unwrap_or(true),
+        Self {
+            glossary_path,
+            scoped_glossaries,
+            glossary_source: raw.glossary_source.unwrap_or_default(),
+            glossary_file: raw.glossary_file.map_or_else(
+                || PathBuf::from(DEFAULT_GLOSSARY_FILE),
+                |path| paths::from_config(&path),
+            ),
+            csv_columns: raw.csv_columns.unwrap_or_default(),
+            link_first_only,
+            link_scope,
             css_class: raw
                 .css_class
                 .unwrap_or_else(|| String::from("glossary-term")),
+            output,
+            tooltip: raw.tooltip.unwrap_or_default(),
+            tooltip_source: raw.tooltip_source.unwrap_or_default(),
+            // A zero-length tooltip would only show the ellipsis
+            tooltip_max_length: raw.tooltip_max_length.filter(|&max| max > 0),
             case_sensitive: raw.case_sensitive.unwrap_or(false),
-            exclude_pages,
-            aliases: raw.aliases.unwrap_or_default(),
-        })
+            match_inflections: raw.match_inflections.unwrap_or(false),
+            flexible_separators: raw.flexible_separators.unwrap_or(false),
+            ignore_diacritics: raw.ignore_diacritics.unwrap_or(false),
+            min_term_length: raw.min_term_length.unwrap_or(0),
+            ignore_terms: raw.ignore_terms.unwrap_or_default(),
+            max_links_per_page: raw.max_links_per_page,
+            exclude_pages: glob_patterns(raw.exclude_pages, "exclude-pages"),
+            include_pages: glob_patterns(raw.include_pages, "include-pages"),
+            page_exclude_terms: page_exclude_terms(raw.page_exclude_terms),
+            skip_contexts: raw
+                .skip_contexts
+                .unwrap_or_else(|| vec![SkipContext::Heading]),
+            link_in_html: raw.link_in_html.unwrap_or(false),
+            terms: term_overrides(raw.terms, raw.aliases),
+            inline_terms: inline_terms(raw.inline_terms),
+            urls: raw.urls.unwrap_or_default(),
+            patterns: raw.patterns.unwrap_or_default(),
+            priorities: raw.priorities.unwrap_or_default(),
+            category_classes: raw.category_classes.unwrap_or_default(),
+            deprecated_terms: raw.deprecated_terms.unwrap_or_default(),
+            site_url: site_url(raw.use_site_url, print_page, book.site_url),
+            redirects: book.redirects,
+            link_sources: false,
+            definitions_only: false,
+            protected_markers: default_protected_markers()
+                .into_iter()
+                .chain(raw.protected_markers.unwrap_or_default())
+                .collect(),
+            math_delimiters: math_delimiters(raw.math_delimiters),
+            languages,
+            sr_expansion: raw.sr_expansion.unwrap_or(false),
+            expand_acronyms: raw.expand_acronyms.unwrap_or(false),
+            page_terms: raw.page_terms.unwrap_or_default(),
+            link_to_first_mention,
+            terms_summary: raw.terms_summary.unwrap_or_default(),
+            terms_summary_title: raw
+                .terms_summary_title
+                .unwrap_or_else(|| String::from(DEFAULT_TERMS_SUMMARY_TITLE)),
+            key_terms: raw.key_terms.unwrap_or(0),
+            key_terms_title: raw
+                .key_terms_title
+                .unwrap_or_else(|| String::from(DEFAULT_KEY_TERMS_TITLE)),
+            backlinks: raw.backlinks.unwrap_or(false),
+            backlinks_title: raw
+                .backlinks_title
+                .unwrap_or_else(|| String::from(DEFAULT_BACKLINKS_TITLE)),
+            link_glossary: raw.link_glossary.unwrap_or(false),
+            generate_acronym_list: raw.generate_acronym_list.unwrap_or(false),
+            acronym_list_title: raw
+                .acronym_list_title
+                .unwrap_or_else(|| String::from(DEFAULT_ACRONYM_LIST_TITLE)),
+            glossary_index: raw.glossary_index.unwrap_or_default(),
+            anchor_style: raw.anchor_style.unwrap_or_default(),
+            glossary_shortcut: glossary_shortcut(raw.glossary_shortcut.as_deref()),
+            termlink_js,
+            log: raw.log.unwrap_or_default(),
+            report: raw.report.map(|path| paths::from_config(&path)),
+            replacement_log: raw.replacement_log.map(|path| paths::from_config(&path)),
+            warn_unused_terms: raw.warn_unused_terms.unwrap_or(false),
+            strict: raw.strict.unwrap_or(false),
+            dry_run: raw.dry_run.unwrap_or(false),
+            cache: raw.cache.map(|path| paths::from_config(&path)),
+        }
     }
 
-    /// Returns the path to the glossary file.
+    /// Returns the path to the glossary file, or the directory holding a split glossary.
     #[must_use]
     pub fn glossary_path(&self) -> &Path {
         &self.glossary_path
     }
 
+    /// Returns where glossary terms are read from.
+    #[must_use]
+    pub const fn glossary_source(&self) -> GlossarySource {
+        self.glossary_source
+    }
+
+    /// Returns the term file read with `glossary-source = "file"`, relative to the book root.
+    #[must_use]
+    pub fn glossary_file(&self) -> &Path {
+        &self.glossary_file
+    }
+
+    /// Returns the header names of the term fields in a CSV term file.
+    #[must_use]
+    pub const fn csv_columns(&self) -> &CsvColumns {
+        &self.csv_columns
+    }
+
     /// Returns true if only the first occurrence of each term should be linked.
     #[must_use]
     pub const fn link_first_only(&self) -> bool {
         self.link_first_only
     }
 
+    /// Returns the part of the book within which `link-first-only` links each term once.
+    #[must_use]
+    pub const fn link_scope(&self) -> LinkScope {
+        self.link_scope
+    }
+
     /// Returns the CSS class to apply to glossary term links.
     #[must_use]
     pub fn css_class(&self) -> &str {
         &self.css_class
     }
 
+    /// Returns how matched terms are marked up.
+    #[must_use]
+    pub const fn output(&self) -> Output {
+        self.output
+    }
+
+    /// Returns how term definitions are shown on hover.
+    #[must_use]
+    pub const fn tooltip(&self) -> Tooltip {
+        self.tooltip
+    }
+
+    /// Returns which part of a definition the `title` tooltip shows.
+    #[must_use]
+    pub const fn tooltip_source(&self) -> TooltipSource {
+        self.tooltip_source
+    }
+
+    /// Returns the longest `title` tooltip definition in characters, if limited.
+    #[must_use]
+    pub const fn tooltip_max_length(&self) -> Option<usize> {
+        self.tooltip_max_length
+    }
+
     /// Returns true if term matching should be case-sensitive.
     #[must_use]
     pub const fn case_sensitive(&self) -> bool {
         self.case_sensitive
     }
 
-    /// Checks if the given path is the glossary file.
+    /// Returns true if plural forms of terms ("APIs", "caches", "indices") match too.
     #[must_use]
-    pub fn is_glossary_path(&self, path: &Path) -> bool {
-        path == self.glossary_path || path.ends_with(&self.glossary_path)
+    pub const fn match_inflections(&self) -> bool {
+        self.match_inflections
     }
 
-    /// Checks if the given path should be excluded from term linking.
+    /// Returns true if "load balancer" also matches "load-balancer" and "load  balancer".
     #[must_use]
-    pub fn should_exclude(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
-        self.exclude_pages.iter().any(|p| p.matches(&path_str))
+    pub const fn flexible_separators(&self) -> bool {
+        self.flexible_separators
     }
 
-    /// Returns aliases for a term name (if configured).
+    /// Returns true if "resume" also matches "résumé".
     #[must_use]
-    pub fn aliases(&self, term_name: &str) -> Option<&Vec<String>> {
-        self.aliases.get(term_name)
+    pub const fn ignore_diacritics(&self) -> bool {
+        self.ignore_diacritics
     }
 
-    /// Returns iterator over all aliases (for conflict detection).
-    pub fn all_aliases(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
-        self.aliases.iter()
+    /// Returns the shortest term form (in characters) that is matched in chapters (0 for any).
+    #[must_use]
+    pub const fn min_term_length(&self) -> usize {
+        self.min_term_length
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Returns the most links added to one page, if limited.
+    #[must_use]
+    pub const fn max_links_per_page(&self) -> Option<usize> {
+        self.max_links_per_page
+    }
 
-    #[test]
-    fn test_default_config() {
-        let config = Config::default();
-        assert_eq!(config.glossary_path(), Path::new("reference/glossary.md"));
-        assert!(config.link_first_only());
-        assert_eq!(config.css_class(), "glossary-term");
-        assert!(!config.case_sensitive());
+    /// Returns the site URL prefix for absolute glossary links, if `use-site-url` or `print-page` is enabled.
+    #[must_use]
+    pub fn site_url(&self) -> Option<&str> {
+        self.site_url.as_deref()
     }
 
-    #[test]
-    fn test_is_glossary_path_exact_match() {
-        let config = Config::default();
-        assert!(config.is_glossary_path(Path::new("reference/glossary.md")));
+    /// Returns mdBook's `output.html.redirect` map (old path -> new URL).
+    #[must_use]
+    pub const fn redirects(&self) -> &BTreeMap<String, String> {
+        &self.redirects
     }
 
-    #[test]
-    fn test_is_glossary_path_suffix_match() {
-        let config = Config::default();
-        assert!(config.is_glossary_path(Path::new("src/reference/glossary.md")));
+    /// Returns true if links point at the glossary's markdown sources (`glossary.md`), for renderers other than HTML.
+    #[must_use]
+    pub const fn link_sources(&self) -> bool {
+        self.link_sources
     }
 
-    #[test]
-    fn test_is_glossary_path_no_match() {
-        let config = Config::default();
-        assert!(!config.is_glossary_path(Path::new("chapter1.md")));
-        assert!(!config.is_glossary_path(Path::new("glossary.md")));
+    /// Returns true if only glossary definitions are linked (set by [`Config::for_glossary_page`]).
+    #[must_use]
+    pub const fn definitions_only(&self) -> bool {
+        self.definitions_only
     }
 
-    #[test]
-    fn test_should_exclude_exact_match() {
-        let config = Config {
-            exclude_pages: vec![Pattern::new("changelog.md").unwrap()],
-            ..Default::default()
-        };
-        assert!(config.should_exclude(Path::new("changelog.md")));
-        assert!(!config.should_exclude(Path::new("chapter1.md")));
+    /// Returns the marker comments whose enclosing block is never linked.
+    ///
+    /// Includes the built-in markers (`<!-- toc -->`, anchor comments) plus
+    /// any configured via `protected-markers`.
+    #[must_use]
+    pub fn protected_markers(&self) -> &[String] {
+        &self.protected_markers
     }
 
-    #[test]
-    fn test_should_exclude_wildcard() {
-        let config = Config {
-            exclude_pages: vec![Pattern::new("appendix/*").unwrap()],
-            ..Default::default()
-        };
-        assert!(config.should_exclude(Path::new("appendix/a.md")));
-        assert!(config.should_exclude(Path::new("appendix/b.md")));
-        assert!(!config.should_exclude(Path::new("chapter1.md")));
+    /// Returns the opening and closing delimiters of math, such as `("$", "$")`.
+    ///
+    /// Terms between a pair of delimiters are never linked.
+    #[must_use]
+    pub fn math_delimiters(&self) -> &[(String, String)] {
+        &self.math_delimiters
     }
 
-    #[test]
-    fn test_should_exclude_recursive_glob() {
-        let config = Config {
-            exclude_pages: vec![Pattern::new("**/draft-*.md").unwrap()],
-            ..Default::default()
-        };
-        assert!(config.should_exclude(Path::new("draft-intro.md")));
-        assert!(config.should_exclude(Path::new("chapters/draft-chapter1.md")));
-        assert!(!config.should_exclude(Path::new("chapter1.md")));
+    /// Returns the language of the glossary page `page`, or of `inline-terms` for `None`.
+    ///
+    /// That is the `lang` of a scoped glossary, the language `glossary-paths`
+    /// picked the glossary for, or else `glossary-lang`.
+    #[must_use]
+    pub fn glossary_lang(&self, page: Option<&Path>) -> Option<&str> {
+        let languages = &self.languages;
+        let own = page.and_then(|page| {
+            self.scoped_glossary(page)
+                .map_or(languages.main.as_ref(), |(_, glossary)| {
+                    languages.scoped.get(glossary)
+                })
+        });
+        own.or(languages.glossary.as_ref()).map(String::as_str)
     }
 
-    #[test]
-    fn test_aliases_getter() {
-        let mut aliases = HashMap::new();
-        aliases.insert(
-            "API".to_string(),
-            vec!["apis".to_string(), "api endpoint".to_string()],
-        );
-        let config = Config {
-            aliases,
-            ..Default::default()
-        };
+    /// Returns `lang` if it differs from the book language, to be declared on links.
+    ///
+    /// Declared via `lang`/`hreflang` so screen readers switch pronunciation.
+    #[must_use]
+    pub fn link_lang<'a>(&self, lang: Option<&'a str>) -> Option<&'a str> {
+        lang.filter(|lang| !lang.eq_ignore_ascii_case(&self.languages.book))
+    }
 
-        assert_eq!(
-            config.aliases("API"),
-            Some(&vec!["apis".to_string(), "api endpoint".to_string()])
-        );
-        assert_eq!(config.aliases("REST"), None);
+    /// Returns true if linked acronyms get a screen-reader-only expansion.
+    #[must_use]
+    pub const fn sr_expansion(&self) -> bool {
+        self.sr_expansion
     }
 
-    #[test]
-    fn test_all_aliases_iterator() {
-        let mut aliases = HashMap::new();
-        aliases.insert("API".to_string(), vec!["apis".to_string()]);
-        aliases.insert("REST".to_string(), vec!["RESTful".to_string()]);
-        let config = Config {
-            aliases,
-            ..Default::default()
-        };
+    /// Returns true if the first linked acronym on a page is expanded in the text.
+    #[must_use]
+    pub const fn expand_acronyms(&self) -> bool {
+        self.expand_acronyms
+    }
 
-        assert_eq!(config.all_aliases().count(), 2);
+    /// Returns how the terms linked on each page are exported.
+    #[must_use]
+    pub const fn page_terms(&self) -> PageTerms {
+        self.page_terms
+    }
+
+    /// Returns true if later occurrences of a term link to its first mention on the page.
+    ///
+    /// Always false unless `link-first-only` is enabled for the whole page and terms are
+    /// output as links.
+    #[must_use]
+    pub const fn link_to_first_mention(&self) -> bool {
+        self.link_to_first_mention
+    }
+
+    /// Returns where the "Terms used on this page" list is added.
+    #[must_use]
+    pub const fn terms_summary(&self) -> TermsSummary {
+        self.terms_summary
+    }
+
+    /// Returns the heading text of the "Terms used on this page" list.
+    #[must_use]
+    pub fn terms_summary_title(&self) -> &str {
+        &self.terms_summary_title
+    }
+
+    /// Returns the number of terms in the "Key terms" callout (0 when disabled).
+    #[must_use]
+    pub const fn key_terms(&self) -> usize {
+        self.key_terms
+    }
+
+    /// Returns the heading text of the "Key terms" callout.
+    #[must_use]
+    pub fn key_terms_title(&self) -> &str {
+        &self.key_terms_title
+    }
+
+    /// Returns true if glossary entries list the chapters linking to them.
+    #[must_use]
+    pub const fn backlinks(&self) -> bool {
+        self.backlinks
+    }
+
+    /// Returns the label of the list of chapters linking to a glossary entry.
+    #[must_use]
+    pub fn backlinks_title(&self) -> &str {
+        &self.backlinks_title
+    }
+
+    /// Returns true if terms in glossary definitions are linked to their entries.
+    #[must_use]
+    pub const fn link_glossary(&self) -> bool {
+        self.link_glossary
+    }
+
+    /// Returns true if a chapter listing the acronyms is appended to the book.
+    #[must_use]
+    pub const fn generate_acronym_list(&self) -> bool {
+        self.generate_acronym_list
+    }
+
+    /// Returns the title of the acronym list chapter.
+    #[must_use]
+    pub fn acronym_list_title(&self) -> &str {
+        &self.acronym_list_title
+    }
+
+    /// Returns what the A–Z index does to glossary pages.
+    #[must_use]
+    pub const fn glossary_index(&self) -> GlossaryIndex {
+        self.glossary_index
+    }
+
+    /// Returns how the anchors of glossary entries are derived from their names.
+    #[must_use]
+    pub const fn anchor_style(&self) -> AnchorStyle {
+        self.anchor_style
+    }
+
+    /// Returns the key that opens the glossary page, unless disabled.
+    #[must_use]
+    pub const fn glossary_shortcut(&self) -> Option<char> {
+        self.glossary_shortcut
+    }
+
+    /// Returns whether the book loads `termlink.js` through `output.html.additional-js`.
+    #[must_use]
+    pub const fn termlink_js(&self) -> bool {
+        self.termlink_js
+    }
+
+    /// Returns how much the preprocessor logs.
+    #[must_use]
+    pub const fn log(&self) -> LogLevel {
+        self.log
+    }
+
+    /// Returns the file to write term usage statistics to, relative to the book root.
+    #[must_use]
+    pub fn report(&self) -> Option<&Path> {
+        self.report.as_deref()
+    }
+
+    /// Returns the file to log every link added to, relative to the book root.
+    #[must_use]
+    pub fn replacement_log(&self) -> Option<&Path> {
+        self.replacement_log.as_deref()
+    }
+
+    /// Returns true if glossary terms no chapter links to are warned about.
+    #[must_use]
+    pub const fn warn_unused_terms(&self) -> bool {
+        self.warn_unused_terms
+    }
+
+    /// Returns true if chapter errors and unused terms fail the build instead of being logged.
+    #[must_use]
+    pub const fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Returns true if the changes are printed as a diff and the book is left unchanged.
+    #[must_use]
+    pub const fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Returns the file caching linked chapters between builds, relative to the book root.
+    #[must_use]
+    pub fn cache(&self) -> Option<&Path> {
+        self.cache.as_deref()
+    }
+
+    /// Checks if the given path is the glossary file, a page below the glossary directory, or a scoped glossary.
+    #[must_use]
+    pub fn is_glossary_path(&self, path: &Path) -> bool {
+        // mdBook renames a `README.md` chapter to `index.md`; either may be configured
+        let path = paths::index_page(&paths::normalize(path));
+        let glossary_path = paths::index_page(&self.glossary_path);
+        // Ancestors cover a glossary split across the chapters of a directory
+        path.ancestors()
+            .any(|p| p == glossary_path || p.ends_with(&glossary_path))
+            || self.glossary_scope(&path).is_some()
+    }
+
+    /// Returns the scoped glossaries, each with the directory of the chapters it applies to.
+    #[must_use]
+    pub fn scoped_glossaries(&self) -> &[(PathBuf, PathBuf)] {
+        &self.scoped_glossaries
+    }
+
+    /// Returns the directory of the innermost scoped glossary applying to `chapter` (if any).
+    #[must_use]
+    pub fn chapter_scope(&self, chapter: &Path) -> Option<&Path> {
+        let chapter = paths::normalize(chapter);
+        self.scoped_glossaries
+            .iter()
+            .map(|(dir, _)| dir.as_path())
+            .filter(|dir| chapter.starts_with(dir))
+            .max_by_key(|dir| dir.components().count())
+    }
+
+    /// Returns the directory a scoped glossary page applies to, if `page` is one.
+    #[must_use]
+    pub fn glossary_scope(&self, page: &Path) -> Option<&Path> {
+        self.scoped_glossary(page).map(|(dir, _)| dir.as_path())
+    }
+
+    /// Returns the scoped glossary (directory, page) that `page` is the page of.
+    fn scoped_glossary(&self, page: &Path) -> Option<&(PathBuf, PathBuf)> {
+        let page = paths::index_page(&paths::normalize(page));
+        self.scoped_glossaries
+            .iter()
+            .find(|(_, glossary)| paths::index_page(glossary) == page)
+    }
+
+    /// Checks if the given path should be excluded from term linking.
+    ///
+    /// A page is excluded if it matches `exclude-pages`, or if
+    /// `include-pages` is set and it matches none of its patterns.
+    #[must_use]
+    pub fn should_exclude(&self, path: &Path) -> bool {
+        // Patterns are written with `/`, whatever the platform's separator
+        let path_str = paths::to_url_path(path);
+        let included = self.include_pages.is_empty()
+            || self.include_pages.iter().any(|p| p.matches(&path_str));
+        !included || self.exclude_pages.iter().any(|p| p.matches(&path_str))
+    }
+
+    /// Returns true if text inside `context` elements is not linked.
+    #[must_use]
+    pub fn skips(&self, context: SkipContext) -> bool {
+        self.skip_contexts.contains(&context)
+    }
+
+    /// Returns true if terms in the text of raw HTML blocks, such as `<div>API</div>`, are linked.
+    #[must_use]
+    pub const fn link_in_html(&self) -> bool {
+        self.link_in_html
+    }
+
+    /// Returns the terms `page-exclude-terms` keeps from being linked on the page at `path`.
+    #[must_use]
+    pub fn page_exclude_terms(&self, path: &Path) -> &[String] {
+        self.page_exclude_terms
+            .get(&paths::to_url_path(path))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the terms defined in `inline-terms`, by name.
+    #[must_use]
+    pub const fn inline_terms(&self) -> &BTreeMap<String, InlineTerm> {
+        &self.inline_terms
+    }
+
+    /// Returns the settings of a term from `terms` (if any).
+    #[must_use]
+    pub fn term_override(&self, term_name: &str) -> Option<&TermOverride> {
+        self.terms.get(term_name)
+    }
+
+    /// Returns aliases for a term name (if configured).
+    #[must_use]
+    pub fn aliases(&self, term_name: &str) -> Option<&Vec<String>> {
+        self.term_override(term_name)
+            .map(|term| &term.aliases)
+            .filter(|aliases| !aliases.is_empty())
+    }
+
+    /// Returns true if the term is matched case-sensitively, by its own setting or `case-sensitive`.
+    #[must_use]
+    pub fn is_case_sensitive(&self, term_name: &str) -> bool {
+        self.term_override(term_name)
+            .and_then(|term| term.case_sensitive)
+            .unwrap_or(self.case_sensitive)
+    }
+
+    /// Returns true if only the first occurrence of the term is linked, by its own setting or `link-first-only`.
+    #[must_use]
+    pub fn links_first_only(&self, term_name: &str) -> bool {
+        self.term_override(term_name)
+            .and_then(|term| term.link_first_only)
+            .unwrap_or(self.link_first_only)
+    }
+
+    /// Returns the extra link class configured for a term category (if any).
+    #[must_use]
+    pub fn category_class(&self, category: &str) -> Option<&str> {
+        self.category_classes.get(category).map(String::as_str)
+    }
+
+    /// Returns true if the term is listed in `ignore-terms`, by its name or short form, or disabled in `terms`.
+    #[must_use]
+    pub fn is_ignored(&self, term_name: &str, short_name: Option<&str>) -> bool {
+        self.ignore_terms
+            .iter()
+            .any(|name| name == term_name || Some(name.as_str()) == short_name)
+            || self
+                .term_override(term_name)
+                .is_some_and(|term| term.disabled)
+    }
+
+    /// Returns a copy of the configuration that also ignores the terms named in `names`.
+    ///
+    /// Used for the terms excluded on one page, by `page-exclude-terms` or front matter.
+    #[must_use]
+    pub fn ignoring(&self, names: &[String]) -> Self {
+        let mut config = self.clone();
+        config.ignore_terms.extend_from_slice(names);
+        config
+    }
+
+    /// Returns true if the term is listed in `deprecated-terms`.
+    #[must_use]
+    pub fn is_deprecated(&self, term_name: &str) -> bool {
+        self.deprecated_terms.iter().any(|name| name == term_name)
+    }
+
+    /// Returns the external URL a term links to instead of the glossary (if configured).
+    ///
+    /// A `url` in `terms` wins over one in `urls`.
+    #[must_use]
+    pub fn url(&self, term_name: &str) -> Option<&str> {
+        self.term_override(term_name)
+            .and_then(|term| term.url.as_deref())
+            .or_else(|| self.urls.get(term_name).map(String::as_str))
+    }
+
+    /// Returns the regex matching further forms of a term (if configured).
+    #[must_use]
+    pub fn pattern(&self, term_name: &str) -> Option<&str> {
+        self.patterns.get(term_name).map(String::as_str)
+    }
+
+    /// Returns the configured priority of a term (if any).
+    #[must_use]
+    pub fn priority(&self, term_name: &str) -> Option<i32> {
+        self.priorities.get(term_name).copied()
+    }
+
+    /// Returns iterator over all aliases (for conflict detection), in term name order.
+    pub fn all_aliases(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.terms
+            .iter()
+            .map(|(name, term)| (name, &term.aliases))
+            .filter(|(_, aliases)| !aliases.is_empty())
+    }
+}
+
+/// Returns the built-in protected markers as owned strings.
+fn default_protected_markers() -> Vec<String> {
+    DEFAULT_PROTECTED_MARKERS
+        .iter()
+        .map(|m| (*m).to_string())
+        .collect()
+}
+
+/// Parses the glob patterns of option `name`, warning about and skipping invalid ones.
+fn glob_patterns(patterns: Option<Vec<String>>, name: &str) -> Vec<Pattern> {
+    patterns
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|p| match Pattern::new(p) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                log::warn!("Invalid {name} glob pattern '{p}': {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Picks the glossary of the book's language from `glossary-paths`, falling back to `glossary-path`.
+///
+/// Also returns the languages of the book and the glossary: the one it was
+/// picked for, or else `glossary_lang`.
+fn glossary_path(
+    path: Option<String>,
+    by_language: Option<BTreeMap<String, String>>,
+    glossary_lang: Option<String>,
+    book_language: Option<String>,
+) -> (PathBuf, GlossaryLanguages) {
+    // mdBook defaults to "en"
+    let book = book_language.unwrap_or_else(|| String::from("en"));
+    let picked = by_language.and_then(|mut by_language| by_language.remove(&book));
+    let languages = GlossaryLanguages {
+        main: picked.is_some().then(|| book.clone()),
+        book,
+        glossary: glossary_lang,
+        scoped: BTreeMap::new(),
+    };
+    let path = picked.or(path).map_or_else(
+        || PathBuf::from("reference/glossary.md"),
+        |path| paths::from_config(&path),
+    );
+    (path, languages)
+}
+
+/// Reads `link-first-only` as whether to link first occurrences only, and in what scope.
+const fn link_first_only(
+    link_first_only: Option<RawLinkFirstOnly>,
+    print_page: bool,
+) -> (bool, LinkScope) {
+    match link_first_only {
+        // One link per term on the print page, not one per chapter
+        None if print_page => (true, LinkScope::PerBook),
+        None => (true, LinkScope::PerPage),
+        Some(RawLinkFirstOnly::Enabled(enabled)) => (enabled, LinkScope::PerPage),
+        Some(RawLinkFirstOnly::Scope(scope)) => (true, scope),
+    }
+}
+
+/// Returns the base of absolute links with `use-site-url`: the book's `output.html.site-url`.
+///
+/// Without a `site-url` to build on, links stay relative, with a warning.
+/// `print-page` links from the site root instead, as mdBook serves the book
+/// unless `site-url` says otherwise.
+fn site_url(
+    use_site_url: Option<bool>,
+    print_page: bool,
+    site_url: Option<String>,
+) -> Option<String> {
+    if print_page {
+        return Some(site_url.unwrap_or_else(|| String::from("/")));
+    }
+    if !use_site_url.unwrap_or(false) {
+        return None;
+    }
+    if site_url.is_none() {
+        log::warn!(
+            "use-site-url is enabled but output.html.site-url is not set; using relative links"
+        );
+    }
+    site_url
+}
+
+/// Keys the `page-exclude-terms` lists by page path with `/` separators.
+fn page_exclude_terms(
+    pages: Option<BTreeMap<String, Vec<String>>>,
+) -> BTreeMap<String, Vec<String>> {
+    pages
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(page, names)| (paths::to_url_path(Path::new(&page)), names))
+        .collect()
+}
+
+/// Reads the `inline-terms` table, where a bare string is the definition.
+fn inline_terms(terms: Option<BTreeMap<String, RawInlineTerm>>) -> BTreeMap<String, InlineTerm> {
+    terms
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, term)| {
+            let term = match term {
+                RawInlineTerm::Definition(definition) => InlineTerm {
+                    definition: Some(definition),
+                    url: None,
+                },
+                RawInlineTerm::Table(term) => term,
+            };
+            (name, term)
+        })
+        .collect()
+}
+
+/// Merges the older flat `aliases` map into the `terms` table.
+///
+/// Aliases from both are kept, those from `terms` first.
+fn term_overrides(
+    terms: Option<BTreeMap<String, TermOverride>>,
+    aliases: Option<BTreeMap<String, Vec<String>>>,
+) -> BTreeMap<String, TermOverride> {
+    let mut terms = terms.unwrap_or_default();
+    for (name, aliases) in aliases.unwrap_or_default() {
+        terms.entry(name).or_default().aliases.extend(aliases);
+    }
+    terms
+}
+
+/// Parses `scoped-glossaries` into (directory, glossary page) pairs, adding
+/// the languages of the pages that declare one to `languages`.
+fn scoped_glossaries(
+    scoped: Option<BTreeMap<String, RawScopedGlossary>>,
+    mut languages: GlossaryLanguages,
+) -> (Vec<(PathBuf, PathBuf)>, GlossaryLanguages) {
+    let glossaries = scoped
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(dir, glossary)| {
+            let glossary = match glossary {
+                RawScopedGlossary::Page(path) => ScopedGlossary { path, lang: None },
+                RawScopedGlossary::Table(glossary) => glossary,
+            };
+            let page = paths::from_config(&glossary.path);
+            if let Some(lang) = glossary.lang {
+                languages.scoped.insert(page.clone(), lang);
+            }
+            (paths::from_config(&dir), page)
+        })
+        .collect();
+    (glossaries, languages)
+}
+
+/// Collects the `math-delimiters` pairs, warning about and skipping those with an empty delimiter.
+fn math_delimiters(delimiters: Option<Vec<(String, String)>>) -> Vec<(String, String)> {
+    delimiters
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(open, close)| {
+            let valid = !open.is_empty() && !close.is_empty();
+            if !valid {
+                log::warn!(
+                    "Invalid math-delimiters pair ('{open}', '{close}'): a delimiter is empty"
+                );
+            }
+            valid
+        })
+        .collect()
+}
+
+/// Parses `glossary-shortcut`, `g` when unset: an empty string disables the
+/// shortcut, and anything but a single character is warned about and replaced
+/// by the default.
+///
+/// The shortcut is compared against `KeyboardEvent.key`, so longer values could never match.
+fn glossary_shortcut(key: Option<&str>) -> Option<char> {
+    let Some(key) = key else {
+        return Some(DEFAULT_GLOSSARY_SHORTCUT);
+    };
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => None,
+        (Some(c), None) if !c.is_whitespace() => Some(c),
+        _ => {
+            log::warn!(
+                "glossary-shortcut must be a single character, got '{key}'; \
+                 using \"{DEFAULT_GLOSSARY_SHORTCUT}\""
+            );
+            Some(DEFAULT_GLOSSARY_SHORTCUT)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+        assert_eq!(config.glossary_path(), Path::new("reference/glossary.md"));
+        assert!(config.link_first_only());
+        assert_eq!(config.css_class(), "glossary-term");
+        assert!(!config.case_sensitive());
+    }
+
+    #[test]
+    fn test_is_glossary_path_exact_match() {
+        let config = Config::default();
+        assert!(config.is_glossary_path(Path::new("reference/glossary.md")));
+    }
+
+    #[test]
+    fn test_is_glossary_path_suffix_match() {
+        let config = Config::default();
+        assert!(config.is_glossary_path(Path::new("src/reference/glossary.md")));
+    }
+
+    #[test]
+    fn test_is_glossary_path_directory() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nglossary-path = \"glossary\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.is_glossary_path(Path::new("glossary/a-f.md")));
+        assert!(config.is_glossary_path(Path::new("glossary/g-m.md")));
+        assert!(!config.is_glossary_path(Path::new("glossary.md")));
+        assert!(!config.is_glossary_path(Path::new("guide/glossary-usage.md")));
+    }
+
+    #[test]
+    fn test_is_glossary_path_readme() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nglossary-path = \"reference/README.md\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.is_glossary_path(Path::new("reference/index.md")));
+        assert!(config.is_glossary_path(Path::new("reference/README.md")));
+        assert!(!config.is_glossary_path(Path::new("index.md")));
+    }
+
+    #[test]
+    fn test_scoped_glossaries() {
+        let book_toml: toml::Table = r#"
+[preprocessor.termlink.scoped-glossaries]
+api = "api/glossary.md"
+"api/v2" = "api/v2/README.md"
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert!(config.is_glossary_path(Path::new("api/glossary.md")));
+        assert!(config.is_glossary_path(Path::new("api/v2/index.md")));
+        assert_eq!(
+            config.glossary_scope(Path::new("./api/glossary.md")),
+            Some(Path::new("api"))
+        );
+        assert_eq!(config.glossary_scope(Path::new("api/intro.md")), None);
+
+        assert_eq!(
+            config.chapter_scope(Path::new("api/v2/auth.md")),
+            Some(Path::new("api/v2"))
+        );
+        assert_eq!(
+            config.chapter_scope(Path::new("api/intro.md")),
+            Some(Path::new("api"))
+        );
+        assert_eq!(config.chapter_scope(Path::new("apis.md")), None);
+    }
+
+    #[test]
+    fn test_is_glossary_path_unnormalized() {
+        let config = Config::default();
+        assert!(config.is_glossary_path(Path::new("./reference/glossary.md")));
+        assert!(config.is_glossary_path(Path::new("guide/../reference/glossary.md")));
+    }
+
+    #[test]
+    fn test_is_glossary_path_no_match() {
+        let config = Config::default();
+        assert!(!config.is_glossary_path(Path::new("chapter1.md")));
+        assert!(!config.is_glossary_path(Path::new("glossary.md")));
+    }
+
+    #[test]
+    fn test_should_exclude_exact_match() {
+        let config = Config {
+            exclude_pages: vec![Pattern::new("changelog.md").unwrap()],
+            ..Default::default()
+        };
+        assert!(config.should_exclude(Path::new("changelog.md")));
+        assert!(!config.should_exclude(Path::new("chapter1.md")));
+    }
+
+    #[test]
+    fn test_should_exclude_wildcard() {
+        let config = Config {
+            exclude_pages: vec![Pattern::new("appendix/*").unwrap()],
+            ..Default::default()
+        };
+        assert!(config.should_exclude(Path::new("appendix/a.md")));
+        assert!(config.should_exclude(Path::new("appendix/b.md")));
+        assert!(!config.should_exclude(Path::new("chapter1.md")));
+    }
+
+    #[test]
+    fn test_should_exclude_recursive_glob() {
+        let config = Config {
+            exclude_pages: vec![Pattern::new("**/draft-*.md").unwrap()],
+            ..Default::default()
+        };
+        assert!(config.should_exclude(Path::new("draft-intro.md")));
+        assert!(config.should_exclude(Path::new("chapters/draft-chapter1.md")));
+        assert!(!config.should_exclude(Path::new("chapter1.md")));
+    }
+
+    #[test]
+    fn test_should_exclude_outside_include_pages() {
+        let config = Config {
+            include_pages: vec![Pattern::new("tutorial/**").unwrap()],
+            exclude_pages: vec![Pattern::new("tutorial/draft.md").unwrap()],
+            ..Default::default()
+        };
+        assert!(!config.should_exclude(Path::new("tutorial/setup.md")));
+        assert!(!config.should_exclude(Path::new("tutorial/advanced/api.md")));
+        assert!(config.should_exclude(Path::new("tutorial/draft.md")));
+        assert!(config.should_exclude(Path::new("reference/api.md")));
+    }
+
+    #[test]
+    fn test_aliases_getter() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.aliases]\nAPI = [\"apis\", \"api endpoint\"]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert_eq!(
+            config.aliases("API"),
+            Some(&vec!["apis".to_string(), "api endpoint".to_string()])
+        );
+        assert_eq!(config.aliases("REST"), None);
+    }
+
+    #[test]
+    fn test_all_aliases_iterator() {
+        let book_toml: toml::Table = "[preprocessor.termlink.aliases]\nAPI = [\"apis\"]\n\n\
+                                      [preprocessor.termlink.terms.REST]\naliases = [\"RESTful\"]\n\n\
+                                      [preprocessor.termlink.terms.JSON]\ndisabled = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert_eq!(config.all_aliases().count(), 2);
+    }
+
+    #[test]
+    fn test_inline_terms() {
+        let book_toml: toml::Table = r#"
+[preprocessor.termlink.inline-terms]
+API = "A set of protocols."
+RFC = { url = "https://www.rfc-editor.org/" }
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let terms = config.inline_terms();
+        assert_eq!(
+            terms["API"].definition.as_deref(),
+            Some("A set of protocols.")
+        );
+        assert_eq!(terms["API"].url, None);
+        assert_eq!(terms["RFC"].definition, None);
+        assert_eq!(
+            terms["RFC"].url.as_deref(),
+            Some("https://www.rfc-editor.org/")
+        );
+
+        let unknown: toml::Table = "[preprocessor.termlink.inline-terms]\nAPI = { href = \"x\" }\n"
+            .parse()
+            .unwrap();
+        assert!(Config::from_book_toml(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_term_overrides() {
+        let book_toml: toml::Table = r#"
+[preprocessor.termlink]
+case-sensitive = true
+
+[preprocessor.termlink.aliases]
+API = ["apis"]
+
+[preprocessor.termlink.urls]
+RFC = "https://example.com/old"
+
+[preprocessor.termlink.terms.API]
+aliases = ["api endpoint"]
+css-class = "api-term"
+case-sensitive = false
+link-first-only = false
+
+[preprocessor.termlink.terms.RFC]
+url = "https://example.com/rfc"
+disabled = true
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let api = config.term_override("API").unwrap();
+        assert_eq!(api.aliases, ["api endpoint", "apis"]);
+        assert_eq!(api.css_class.as_deref(), Some("api-term"));
+        assert!(!config.is_case_sensitive("API"));
+        assert!(config.is_case_sensitive("REST"));
+        assert!(!config.links_first_only("API"));
+        assert!(config.links_first_only("REST"));
+        assert_eq!(config.url("RFC"), Some("https://example.com/rfc"));
+        assert!(config.is_ignored("RFC", None));
+        assert!(!config.is_ignored("API", None));
+
+        let unknown: toml::Table = "[preprocessor.termlink.terms.API]\nalias = [\"apis\"]\n"
+            .parse()
+            .unwrap();
+        assert!(Config::from_book_toml(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_all_aliases_ordered() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.aliases]\nXPT = [\"xport\"]\nAPI = [\"apis\"]\nREST = [\"RESTful\"]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let names: Vec<&str> = config
+            .all_aliases()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["API", "REST", "XPT"]);
+    }
+
+    #[test]
+    fn test_protected_markers_extend_defaults() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nprotected-markers = [\"<!-- keep -->\"]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(
+            config
+                .protected_markers()
+                .contains(&"<!-- toc -->".to_string())
+        );
+        assert!(
+            config
+                .protected_markers()
+                .contains(&"<!-- keep -->".to_string())
+        );
+    }
+
+    #[test]
+    fn test_for_renderer() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\noutput = \"abbr\"\nuse-site-url = true\n\n[output.html]\nsite-url = \"/docs/\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let html = config.for_renderer("html");
+        assert_eq!(html.output(), Output::Abbr);
+        assert!(!html.link_sources());
+        assert_eq!(html.site_url(), Some("/docs/"));
+
+        let epub = config.for_renderer("epub");
+        assert!(!epub.link_sources());
+        assert_eq!(epub.site_url(), None);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\noutput = \"markdown\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.for_renderer("epub").link_sources());
+        assert!(!config.for_renderer("html").link_sources());
+    }
+
+    #[test]
+    fn test_for_renderer_drops_html_extras() {
+        let book_toml: toml::Table = "[preprocessor.termlink]
+page-terms = \"json\"\nsr-expansion = true\nkey-terms = 3\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let html = config.for_renderer("html");
+        assert_eq!(html.page_terms(), PageTerms::Json);
+        assert!(html.sr_expansion());
+        assert_eq!(html.glossary_shortcut(), Some('g'));
+
+        for renderer in ["epub", "markdown", "pandoc"] {
+            let other = config.for_renderer(renderer);
+            assert_eq!(other.page_terms(), PageTerms::Comment);
+            assert!(!other.sr_expansion());
+            assert_eq!(other.glossary_shortcut(), None);
+            // Written as markdown by the linker instead
+            assert_eq!(other.key_terms(), 3);
+        }
+    }
+
+    #[test]
+    fn test_renderer_profiles() {
+        let table: toml::Table = r#"
+            output = "abbr"
+            css-class = "term"
+
+            [renderer.html]
+            tooltip = "popover"
+
+            [renderer.pandoc]
+            css-class = "pandoc-term"
+        "#
+        .parse()
+        .unwrap();
+        let config = |renderer: &str| {
+            Config::from_raw(
+                raw_config(table.clone(), renderer).unwrap(),
+                BookSettings::default(),
+            )
+        };
+
+        assert_eq!(config("html").output(), Output::Abbr);
+        assert_eq!(config("html").tooltip(), Tooltip::Popover);
+        assert_eq!(config("html").css_class(), "term");
+        // Other renderers default to markdown links
+        assert_eq!(config("epub").output(), Output::Markdown);
+        assert_eq!(config("epub").tooltip(), Tooltip::Title);
+        assert_eq!(config("pandoc").css_class(), "pandoc-term");
+
+        let invalid: toml::Table = "[renderer]\nhtml = 1\n".parse().unwrap();
+        assert!(raw_config(invalid, "html").is_err());
+    }
+
+    #[test]
+    fn test_math_delimiters() {
+        assert!(Config::default().math_delimiters().is_empty());
+
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nmath-delimiters = [[\"$\", \"$\"], [\"\\\\(\", \"\\\\)\"], [\"\", \"$\"]]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(
+            config.math_delimiters(),
+            [
+                ("$".to_string(), "$".to_string()),
+                ("\\(".to_string(), "\\)".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glossary_paths() {
+        let termlink = "[preprocessor.termlink]\nglossary-path = \"reference/glossary.md\"\n\
+                        glossary-paths = { de = \"referenz/glossar.md\" }\n";
+        for (language, expected) in [
+            ("de", "referenz/glossar.md"),
+            ("fr", "reference/glossary.md"),
+        ] {
+            let book_toml: toml::Table = format!("[book]\nlanguage = \"{language}\"\n{termlink}")
+                .parse()
+                .unwrap();
+            let config = Config::from_book_toml(&book_toml).unwrap();
+            assert_eq!(config.glossary_path(), Path::new(expected), "{language}");
+        }
+    }
+
+    #[test]
+    fn test_glossary_lang_differs_from_book() {
+        let book_toml: toml::Table =
+            "[book]\nlanguage = \"nl\"\n[preprocessor.termlink]\nglossary-lang = \"en\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let lang = config.glossary_lang(Some(Path::new("reference/glossary.md")));
+        assert_eq!(config.link_lang(lang), Some("en"));
+    }
+
+    #[test]
+    fn test_glossary_lang_same_as_book() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nglossary-lang = \"en\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.glossary_lang(None), Some("en"));
+        assert_eq!(config.link_lang(config.glossary_lang(None)), None);
+    }
+
+    #[test]
+    fn test_glossary_lang_per_source() {
+        let book_toml: toml::Table = r#"
+[book]
+language = "de"
+
+[preprocessor.termlink]
+glossary-lang = "en"
+glossary-paths = { de = "referenz/glossar.md" }
+
+[preprocessor.termlink.scoped-glossaries]
+api = { path = "api/glossary.md", lang = "fr" }
+cli = "cli/glossary.md"
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        // The glossary picked for the book's language is in that language
+        assert_eq!(
+            config.glossary_lang(Some(Path::new("referenz/glossar.md"))),
+            Some("de")
+        );
+        assert_eq!(
+            config.glossary_lang(Some(Path::new("./api/glossary.md"))),
+            Some("fr")
+        );
+        assert_eq!(
+            config.glossary_lang(Some(Path::new("cli/glossary.md"))),
+            Some("en")
+        );
+        assert_eq!(config.glossary_lang(None), Some("en"));
+        assert_eq!(config.link_lang(Some("DE")), None);
+        assert!(config.is_glossary_path(Path::new("api/glossary.md")));
+    }
+
+    #[test]
+    fn test_use_site_url() {
+        let book_toml: toml::Table = r#"
+[preprocessor.termlink]
+use-site-url = true
+
+[output.html]
+site-url = "/docs/"
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.site_url(), Some("/docs/"));
+    }
+
+    #[test]
+    fn test_use_site_url_without_site_url() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nuse-site-url = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.site_url(), None);
+    }
+
+    #[test]
+    fn test_print_page() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nprint-page = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.site_url(), Some("/"));
+        assert_eq!(config.link_scope(), LinkScope::PerBook);
+
+        let book_toml: toml::Table = r#"
+[preprocessor.termlink]
+print-page = true
+link-first-only = false
+
+[output.html]
+site-url = "/docs/"
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.site_url(), Some("/docs/"));
+        assert!(!config.links_first_only("API"));
+    }
+
+    #[test]
+    fn test_site_url_ignored_when_disabled() {
+        let book_toml: toml::Table = "[output.html]\nsite-url = \"/docs/\"\n".parse().unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.site_url(), None);
+    }
+
+    #[test]
+    fn test_link_to_first_mention_requires_link_first_only() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nlink-to-first-mention = true\nlink-first-only = false\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(!config.link_to_first_mention());
+    }
+
+    #[test]
+    fn test_link_first_only_per_section() {
+        let parse = |value: &str| {
+            let book_toml: toml::Table =
+                format!("[preprocessor.termlink]\nlink-first-only = {value}\n")
+                    .parse()
+                    .unwrap();
+            Config::from_book_toml(&book_toml)
+        };
+
+        let config = parse("\"per-section\"").unwrap();
+        assert!(config.link_first_only());
+        assert_eq!(config.link_scope(), LinkScope::PerSection);
+
+        let config = parse("false").unwrap();
+        assert!(!config.link_first_only());
+        assert_eq!(config.link_scope(), LinkScope::PerPage);
+
+        assert_eq!(
+            parse("\"per-book\"").unwrap().link_scope(),
+            LinkScope::PerBook
+        );
+        assert!(parse("\"per-paragraph\"").is_err());
+    }
+
+    #[test]
+    fn test_backlinks() {
+        let config = Config::default();
+        assert!(!config.backlinks());
+        assert_eq!(config.backlinks_title(), "Referenced in");
+
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nbacklinks = true\nbacklinks-title = \"Used in\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.backlinks());
+        assert_eq!(config.backlinks_title(), "Used in");
+    }
+
+    #[test]
+    fn test_link_glossary() {
+        assert!(!Config::default().link_glossary());
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlink-glossary = true\nlink-first-only = false\nterms-summary = \"append\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.link_glossary());
+        assert!(!config.definitions_only());
+
+        let glossary = config.for_glossary_page();
+        assert!(glossary.definitions_only());
+        assert!(glossary.link_first_only());
+        assert_eq!(glossary.terms_summary(), TermsSummary::Off);
+    }
+
+    #[test]
+    fn test_acronym_list() {
+        let config = Config::default();
+        assert!(!config.generate_acronym_list());
+        assert_eq!(config.acronym_list_title(), "Acronyms");
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\ngenerate-acronym-list = true\nacronym-list-title = \"Abbreviations\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.generate_acronym_list());
+        assert_eq!(config.acronym_list_title(), "Abbreviations");
+    }
+
+    #[test]
+    fn test_report() {
+        assert_eq!(Config::default().report(), None);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nreport = \"termlink-report.json\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.report(), Some(Path::new("termlink-report.json")));
+    }
+
+    #[test]
+    fn test_replacement_log() {
+        assert_eq!(Config::default().replacement_log(), None);
+
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nreplacement-log = \"termlink-links.jsonl\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(
+            config.replacement_log(),
+            Some(Path::new("termlink-links.jsonl"))
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        assert_eq!(Config::default().cache(), None);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\ncache = \".termlink-cache.json\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.cache(), Some(Path::new(".termlink-cache.json")));
+    }
+
+    #[test]
+    fn test_unused_terms() {
+        let config = Config::default();
+        assert!(!config.warn_unused_terms());
+        assert!(!config.strict());
+
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nwarn-unused-terms = true\nstrict = true\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.warn_unused_terms());
+        assert!(config.strict());
+    }
+
+    #[test]
+    fn test_dry_run() {
+        assert!(!Config::default().dry_run());
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\ndry-run = true\n".parse().unwrap();
+        assert!(Config::from_book_toml(&book_toml).unwrap().dry_run());
+    }
+
+    #[test]
+    fn test_glossary_index() {
+        assert_eq!(Config::default().glossary_index(), GlossaryIndex::Off);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nglossary-index = \"generate\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.glossary_index(), GlossaryIndex::Generate);
+    }
+
+    #[test]
+    fn test_anchor_style() {
+        assert_eq!(Config::default().anchor_style(), AnchorStyle::Mdbook);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nanchor-style = \"github\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.anchor_style(), AnchorStyle::Github);
+
+        let unknown: toml::Table = "[preprocessor.termlink]\nanchor-style = \"gitlab\"\n"
+            .parse()
+            .unwrap();
+        assert!(Config::from_book_toml(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_glossary_shortcut_must_be_single_character() {
+        let parse = |value: &str| {
+            let book_toml: toml::Table =
+                format!("[preprocessor.termlink]\nglossary-shortcut = \"{value}\"\n")
+                    .parse()
+                    .unwrap();
+            Config::from_book_toml(&book_toml)
+                .unwrap()
+                .glossary_shortcut()
+        };
+        assert_eq!(parse("k"), Some('k'));
+        assert_eq!(parse("kk"), Some('g'));
+        assert_eq!(parse(""), None);
+        assert_eq!(Config::default().glossary_shortcut(), Some('g'));
+    }
+
+    #[test]
+    fn test_log_level() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlog = \"quiet\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.log(), LogLevel::Quiet);
+        assert_eq!(config.log().filter(), log::LevelFilter::Error);
+        assert_eq!(Config::default().log(), LogLevel::Normal);
+    }
+
+    #[test]
+    fn test_glossary_source() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nglossary-source = \"file\"\nglossary-file = \"terms/glossary.yaml\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.glossary_source(), GlossarySource::File);
+        assert_eq!(config.glossary_file(), Path::new("terms/glossary.yaml"));
+
+        let config = Config::default();
+        assert_eq!(config.glossary_source(), GlossarySource::Chapter);
+        assert_eq!(config.glossary_file(), Path::new("glossary.toml"));
+    }
+
+    #[test]
+    fn test_csv_columns() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.csv-columns]\nname = \"Term\"\nalias-separator = \"|\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.csv_columns().name, "Term");
+        assert_eq!(config.csv_columns().alias_separator, "|");
+        assert_eq!(config.csv_columns().definition, "definition");
+    }
+
+    #[test]
+    fn test_urls() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.urls]\n\"RFC 7231\" = \"https://www.rfc-editor.org/rfc/rfc7231\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(
+            config.url("RFC 7231"),
+            Some("https://www.rfc-editor.org/rfc/rfc7231")
+        );
+        assert_eq!(config.url("API"), None);
+    }
+
+    #[test]
+    fn test_priorities() {
+        let book_toml: toml::Table = "[preprocessor.termlink.priorities]\nREST = 10\nAPI = -1\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.priority("REST"), Some(10));
+        assert_eq!(config.priority("API"), Some(-1));
+        assert_eq!(config.priority("REST API"), None);
+    }
+
+    #[test]
+    fn test_skip_contexts() {
+        let config = Config::default();
+        assert!(config.skips(SkipContext::Heading));
+        assert!(!config.skips(SkipContext::Blockquote));
+
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nskip-contexts = [\"blockquote\", \"table-header\"]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(!config.skips(SkipContext::Heading));
+        assert!(config.skips(SkipContext::Blockquote));
+        assert!(config.skips(SkipContext::TableHeader));
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nskip-contexts = [\"sidebar\"]\n"
+            .parse()
+            .unwrap();
+        assert!(Config::from_book_toml(&book_toml).is_err());
+    }
+
+    #[test]
+    fn test_page_exclude_terms() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.page-exclude-terms]\n\"guide/intro.md\" = [\"Rust\", \"API\"]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(
+            config.page_exclude_terms(Path::new("guide/intro.md")),
+            ["Rust", "API"]
+        );
+        assert!(config.page_exclude_terms(Path::new("intro.md")).is_empty());
+    }
+
+    #[test]
+    fn test_deprecated_terms() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\ndeprecated-terms = [\"XML-RPC\"]\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(config.is_deprecated("XML-RPC"));
+        assert!(!config.is_deprecated("REST"));
+    }
+
+    #[test]
+    fn test_noise_filtering() {
+        let config = Config::default();
+        assert_eq!(config.min_term_length(), 0);
+        assert!(!config.is_ignored("OK", None));
+
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nmin-term-length = 3\nignore-terms = [\"OK\", \"ID\"]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.min_term_length(), 3);
+        assert!(config.is_ignored("OK", None));
+        assert!(config.is_ignored("ID (Identifier)", Some("ID")));
+        assert!(!config.is_ignored("API", None));
+    }
+
+    #[test]
+    fn test_max_links_per_page() {
+        assert_eq!(Config::default().max_links_per_page(), None);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nmax-links-per-page = 20\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.max_links_per_page(), Some(20));
+    }
+
+    #[test]
+    fn test_tooltip() {
+        assert_eq!(Config::default().tooltip(), Tooltip::Title);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\ntooltip = \"popover\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.tooltip(), Tooltip::Popover);
+    }
+
+    #[test]
+    fn test_tooltip_source() {
+        assert_eq!(Config::default().tooltip_source(), TooltipSource::Full);
+        assert_eq!(Config::default().tooltip_max_length(), None);
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\ntooltip-source = \"first-sentence\"\ntooltip-max-length = 80\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.tooltip_source(), TooltipSource::FirstSentence);
+        assert_eq!(config.tooltip_max_length(), Some(80));
+    }
+
+    #[test]
+    fn test_output() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\noutput = \"span\"\nlink-to-first-mention = true\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.output(), Output::Span);
+        assert!(!config.link_to_first_mention());
+        assert_eq!(Config::default().output(), Output::Link);
+    }
+
+    #[test]
+    fn test_page_terms() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\npage-terms = \"json\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(config.page_terms(), PageTerms::Json);
+        assert_eq!(Config::default().page_terms(), PageTerms::Off);
     }
 }
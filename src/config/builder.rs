@@ -0,0 +1,398 @@
+//! Building a [`Config`] in code, for use as a library and in tests.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+use glob::Pattern;
+use regex::Regex;
+
+use super::{
+    AnchorStyle, BookSettings, Config, CsvColumns, GlossaryIndex, GlossarySource, InlineTerm,
+    LinkScope, LogLevel, Output, PageTerms, RawConfig, RawInlineTerm, RawLinkFirstOnly,
+    RawScopedGlossary, ScopedGlossary, SkipContext, TermOverride, TermsSummary, Tooltip,
+    TooltipSource,
+};
+
+/// Builds a [`Config`] option by option, as `book.toml` would set them.
+///
+/// Each setter takes the value of the `book.toml` option of the same name
+/// (in snake case); options left unset keep their defaults. [`Self::build`]
+/// checks the values that `book.toml` parsing only warns about.
+///
+/// ```
+/// use mdbook_termlink::Config;
+/// use mdbook_termlink::config::LinkScope;
+///
+/// let config = Config::builder()
+///     .glossary_path("glossary.md")
+///     .link_scope(LinkScope::PerSection)
+///     .css_class("term")
+///     .build()?;
+/// assert_eq!(config.css_class(), "term");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    /// The options set so far.
+    raw: RawConfig,
+    /// The book-wide settings set so far.
+    book: BookSettings,
+}
+
+/// Defines setters storing their value in the raw option of the same name.
+macro_rules! setters {
+    ($($(#[$doc:meta])* $name:ident: $ty:ty;)*) => {
+        $(
+            $(#[$doc])*
+            #[must_use]
+            pub fn $name(mut self, value: impl Into<$ty>) -> Self {
+                self.raw.$name = Some(value.into());
+                self
+            }
+        )*
+    };
+}
+
+impl ConfigBuilder {
+    /// Creates a builder with every option at its default.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    setters! {
+        /// Sets `glossary-path`, relative to `src`.
+        glossary_path: String;
+        /// Sets `glossary-paths`, the glossary per book language.
+        glossary_paths: BTreeMap<String, String>;
+        /// Sets `glossary-source`.
+        glossary_source: GlossarySource;
+        /// Sets `glossary-file`, relative to the book root.
+        glossary_file: String;
+        /// Sets `csv-columns`.
+        csv_columns: CsvColumns;
+        /// Sets `css-class`.
+        css_class: String;
+        /// Sets `output`.
+        output: Output;
+        /// Sets `tooltip`.
+        tooltip: Tooltip;
+        /// Sets `tooltip-source`.
+        tooltip_source: TooltipSource;
+        /// Sets `tooltip-max-length`.
+        tooltip_max_length: usize;
+        /// Sets `case-sensitive`.
+        case_sensitive: bool;
+        /// Sets `match-inflections`.
+        match_inflections: bool;
+        /// Sets `flexible-separators`.
+        flexible_separators: bool;
+        /// Sets `ignore-diacritics`.
+        ignore_diacritics: bool;
+        /// Sets `min-term-length`.
+        min_term_length: usize;
+        /// Sets `ignore-terms`.
+        ignore_terms: Vec<String>;
+        /// Sets `max-links-per-page`.
+        max_links_per_page: usize;
+        /// Sets `exclude-pages`, glob patterns of pages to skip.
+        exclude_pages: Vec<String>;
+        /// Sets `include-pages`, glob patterns of the only pages to link.
+        include_pages: Vec<String>;
+        /// Sets `page-exclude-terms`, the terms not linked per page.
+        page_exclude_terms: BTreeMap<String, Vec<String>>;
+        /// Sets `skip-contexts`.
+        skip_contexts: Vec<SkipContext>;
+        /// Sets `link-in-html`.
+        link_in_html: bool;
+        /// Sets `aliases`, the alternative names per term.
+        aliases: BTreeMap<String, Vec<String>>;
+        /// Sets `terms`, the settings per term.
+        terms: BTreeMap<String, TermOverride>;
+        /// Sets `urls`, the external link targets per term.
+        urls: BTreeMap<String, String>;
+        /// Sets `patterns`, the regex per term.
+        patterns: BTreeMap<String, String>;
+        /// Sets `priorities`.
+        priorities: BTreeMap<String, i32>;
+        /// Sets `category-classes`.
+        category_classes: BTreeMap<String, String>;
+        /// Sets `deprecated-terms`.
+        deprecated_terms: Vec<String>;
+        /// Sets `use-site-url`.
+        use_site_url: bool;
+        /// Sets `print-page`, links that work on mdBook's print page.
+        print_page: bool;
+        /// Sets `protected-markers`.
+        protected_markers: Vec<String>;
+        /// Sets `math-delimiters`, pairs of opening and closing delimiters.
+        math_delimiters: Vec<(String, String)>;
+        /// Sets `glossary-lang`.
+        glossary_lang: String;
+        /// Sets `sr-expansion`.
+        sr_expansion: bool;
+        /// Sets `expand-acronyms`.
+        expand_acronyms: bool;
+        /// Sets `page-terms`.
+        page_terms: PageTerms;
+        /// Sets `link-to-first-mention`.
+        link_to_first_mention: bool;
+        /// Sets `terms-summary`.
+        terms_summary: TermsSummary;
+        /// Sets `terms-summary-title`.
+        terms_summary_title: String;
+        /// Sets `key-terms`.
+        key_terms: usize;
+        /// Sets `key-terms-title`.
+        key_terms_title: String;
+        /// Sets `backlinks`.
+        backlinks: bool;
+        /// Sets `backlinks-title`.
+        backlinks_title: String;
+        /// Sets `link-glossary`.
+        link_glossary: bool;
+        /// Sets `generate-acronym-list`.
+        generate_acronym_list: bool;
+        /// Sets `acronym-list-title`.
+        acronym_list_title: String;
+        /// Sets `glossary-index`.
+        glossary_index: GlossaryIndex;
+        /// Sets `anchor-style`.
+        anchor_style: AnchorStyle;
+        /// Sets `log`.
+        log: LogLevel;
+        /// Sets `report`, relative to the book root.
+        report: String;
+        /// Sets `replacement-log`, relative to the book root.
+        replacement_log: String;
+        /// Sets `warn-unused-terms`.
+        warn_unused_terms: bool;
+        /// Sets `strict`.
+        strict: bool;
+        /// Sets `dry-run`.
+        dry_run: bool;
+        /// Sets `cache`, relative to the book root.
+        cache: String;
+    }
+
+    /// Sets `link-first-only = true` or `false`.
+    #[must_use]
+    pub const fn link_first_only(mut self, enabled: bool) -> Self {
+        self.raw.link_first_only = Some(RawLinkFirstOnly::Enabled(enabled));
+        self
+    }
+
+    /// Sets `link-first-only` to a scope: once per page, section, or book.
+    #[must_use]
+    pub const fn link_scope(mut self, scope: LinkScope) -> Self {
+        self.raw.link_first_only = Some(RawLinkFirstOnly::Scope(scope));
+        self
+    }
+
+    /// Sets `scoped-glossaries`, the glossary per chapter directory.
+    #[must_use]
+    pub fn scoped_glossaries(
+        mut self,
+        glossaries: impl Into<BTreeMap<String, ScopedGlossary>>,
+    ) -> Self {
+        let glossaries = glossaries.into().into_iter();
+        self.raw.scoped_glossaries = Some(
+            glossaries
+                .map(|(dir, glossary)| (dir, RawScopedGlossary::Table(glossary)))
+                .collect(),
+        );
+        self
+    }
+
+    /// Sets `inline-terms`, the terms defined without a glossary.
+    #[must_use]
+    pub fn inline_terms(mut self, terms: impl Into<BTreeMap<String, InlineTerm>>) -> Self {
+        let terms = terms.into().into_iter();
+        self.raw.inline_terms = Some(
+            terms
+                .map(|(name, term)| (name, RawInlineTerm::Table(term)))
+                .collect(),
+        );
+        self
+    }
+
+    /// Sets `glossary-shortcut`, the key that opens the glossary.
+    #[must_use]
+    pub fn glossary_shortcut(mut self, key: char) -> Self {
+        self.raw.glossary_shortcut = Some(key.to_string());
+        self
+    }
+
+    /// Turns the glossary shortcut off (`glossary-shortcut = ""`).
+    #[must_use]
+    pub fn no_glossary_shortcut(mut self) -> Self {
+        self.raw.glossary_shortcut = Some(String::new());
+        self
+    }
+
+    /// Sets the book language (`book.language`), which picks from `glossary-paths`.
+    #[must_use]
+    pub fn book_language(mut self, language: impl Into<String>) -> Self {
+        self.book.language = Some(language.into());
+        self
+    }
+
+    /// Sets the site URL (`output.html.site-url`) used with `use-site-url`.
+    #[must_use]
+    pub fn site_url(mut self, site_url: impl Into<String>) -> Self {
+        self.book.site_url = Some(site_url.into());
+        self
+    }
+
+    /// Sets the page redirects (`output.html.redirect`), followed to the glossary.
+    #[must_use]
+    pub fn redirects(mut self, redirects: impl Into<BTreeMap<String, String>>) -> Self {
+        self.book.redirects = redirects.into();
+        self
+    }
+
+    /// Sets the book's scripts (`output.html.additional-js`); listing
+    /// `termlink.js` enables the hover cards and the glossary shortcut.
+    #[must_use]
+    pub fn additional_js(mut self, scripts: impl Into<Vec<String>>) -> Self {
+        self.book.additional_js = scripts.into();
+        self
+    }
+
+    /// Checks the options and builds the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a glob in `exclude-pages` or `include-pages` or a
+    /// regex in `patterns` is invalid, a `math-delimiters` pair has an empty
+    /// delimiter, `tooltip-max-length` is zero, or `glossary-shortcut` is whitespace.
+    pub fn build(self) -> Result<Config> {
+        self.validate()?;
+        Ok(Config::from_raw(self.raw, self.book))
+    }
+
+    /// Checks the options `book.toml` parsing warns about and ignores.
+    fn validate(&self) -> Result<()> {
+        let raw = &self.raw;
+        let globs = [
+            ("exclude-pages", &raw.exclude_pages),
+            ("include-pages", &raw.include_pages),
+        ];
+        for (name, patterns) in globs {
+            for pattern in patterns.iter().flatten() {
+                if let Err(e) = Pattern::new(pattern) {
+                    bail!("Invalid {name} glob pattern '{pattern}': {e}");
+                }
+            }
+        }
+        for (term, pattern) in raw.patterns.iter().flatten() {
+            if let Err(e) = Regex::new(pattern) {
+                bail!("Invalid patterns regex for '{term}': {e}");
+            }
+        }
+        for (open, close) in raw.math_delimiters.iter().flatten() {
+            if open.is_empty() || close.is_empty() {
+                bail!("Invalid math-delimiters pair ('{open}', '{close}'): a delimiter is empty");
+            }
+        }
+        if raw.tooltip_max_length == Some(0) {
+            bail!("tooltip-max-length must be greater than 0");
+        }
+        if raw
+            .glossary_shortcut
+            .as_deref()
+            .is_some_and(|key| !key.is_empty() && key.trim().is_empty())
+        {
+            bail!("glossary-shortcut must not be whitespace");
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Returns a builder for configuring termlink in code.
+    #[must_use]
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let config = Config::builder()
+            .glossary_path("glossary.md")
+            .link_scope(LinkScope::PerSection)
+            .css_class("term")
+            .aliases([("API".to_string(), vec!["apis".to_string()])])
+            .inline_terms([("SLA".to_string(), InlineTerm::default())])
+            .scoped_glossaries([(
+                "api".to_string(),
+                ScopedGlossary {
+                    path: "api/glossary.md".to_string(),
+                    lang: Some("fr".to_string()),
+                },
+            )])
+            .glossary_shortcut('g')
+            .use_site_url(true)
+            .site_url("/docs/")
+            .build()
+            .unwrap();
+
+        assert_eq!(config.glossary_path(), Path::new("glossary.md"));
+        assert!(config.link_first_only());
+        assert_eq!(config.link_scope(), LinkScope::PerSection);
+        assert_eq!(config.css_class(), "term");
+        assert_eq!(config.aliases("API"), Some(&vec!["apis".to_string()]));
+        assert!(config.inline_terms().contains_key("SLA"));
+        assert_eq!(
+            config.glossary_lang(Some(Path::new("api/glossary.md"))),
+            Some("fr")
+        );
+        assert_eq!(config.glossary_shortcut(), Some('g'));
+        assert!(!config.termlink_js());
+        let with_js = Config::builder()
+            .additional_js(vec!["theme/termlink.js".to_string()])
+            .no_glossary_shortcut()
+            .build()
+            .unwrap();
+        assert!(with_js.termlink_js());
+        assert_eq!(with_js.glossary_shortcut(), None);
+        assert_eq!(config.site_url(), Some("/docs/"));
+
+        // Unset options keep their defaults
+        let default = Config::builder().build().unwrap();
+        assert_eq!(default.css_class(), Config::default().css_class());
+        assert!(
+            !Config::builder()
+                .link_first_only(false)
+                .build()
+                .unwrap()
+                .link_first_only()
+        );
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        let err = |builder: ConfigBuilder| builder.build().unwrap_err().to_string();
+
+        assert!(
+            err(Config::builder().exclude_pages(vec!["[".to_string()])).contains("exclude-pages")
+        );
+        assert!(
+            err(Config::builder().patterns([("HTTP".to_string(), "(".to_string())]))
+                .contains("'HTTP'")
+        );
+        assert!(
+            err(Config::builder().math_delimiters(vec![("$".to_string(), String::new())]))
+                .contains("math-delimiters")
+        );
+        assert!(err(Config::builder().tooltip_max_length(0usize)).contains("tooltip-max-length"));
+        assert!(err(Config::builder().glossary_shortcut(' ')).contains("glossary-shortcut"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Folding of text for matching: Unicode case folding and the stripping of diacritics.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Latin and Greek letters with diacritics, by the letter they decompose to.
+///
+/// Derived from the NFKD decomposition of the Latin and Greek blocks, with
+/// the combining marks removed. Other scripts are left alone: there, marks
+/// such as the Japanese dakuten or the Cyrillic breve of "й" make a different letter.
+const DECOMPOSED_LETTERS: &[(char, &str)] = &[
+    ('A', "ÀÁÂÃÄÅĀĂĄǍǞǠǺȀȂȦḀẠẢẤẦẨẪẬẮẰẲẴẶ"),
+    ('B', "ḂḄḆ"),
+    ('C', "ÇĆĈĊČḈ"),
+    ('D', "ĎḊḌḎḐḒ"),
+    ('E', "ÈÉÊËĒĔĖĘĚȄȆȨḔḖḘḚḜẸẺẼẾỀỂỄỆ"),
+    ('F', "Ḟ"),
+    ('G', "ĜĞĠĢǦǴḠ"),
+    ('H', "ĤȞḢḤḦḨḪ"),
+    ('I', "ÌÍÎÏĨĪĬĮİǏȈȊḬḮỈỊ"),
+    ('J', "Ĵ"),
+    ('K', "ĶǨḰḲḴ"),
+    ('L', "ĹĻĽḶḸḺḼ"),
+    ('M', "ḾṀṂ"),
+    ('N', "ÑŃŅŇǸṄṆṈṊ"),
+    ('O', "ÒÓÔÕÖŌŎŐƠǑǪǬȌȎȪȬȮȰṌṎṐṒỌỎỐỒỔỖỘỚỜỞỠỢ"),
+    ('P', "ṔṖ"),
+    ('R', "ŔŖŘȐȒṘṚṜṞ"),
+    ('S', "ŚŜŞŠȘṠṢṤṦṨ"),
+    ('T', "ŢŤȚṪṬṮṰ"),
+    ('U', "ÙÚÛÜŨŪŬŮŰŲƯǓǕǗǙǛȔȖṲṴṶṸṺỤỦỨỪỬỮỰ"),
+    ('V', "ṼṾ"),
+    ('W', "ŴẀẂẄẆẈ"),
+    ('X', "ẊẌ"),
+    ('Y', "ÝŶŸȲẎỲỴỶỸ"),
+    ('Z', "ŹŻŽẐẒẔ"),
+    ('a', "àáâãäåāăąǎǟǡǻȁȃȧḁạảấầẩẫậắằẳẵặ"),
+    ('b', "ḃḅḇ"),
+    ('c', "çćĉċčḉ"),
+    ('d', "ďḋḍḏḑḓ"),
+    ('e', "èéêëēĕėęěȅȇȩḕḗḙḛḝẹẻẽếềểễệ"),
+    ('f', "ḟ"),
+    ('g', "ĝğġģǧǵḡ"),
+    ('h', "ĥȟḣḥḧḩḫẖ"),
+    ('i', "ìíîïĩīĭįǐȉȋḭḯỉị"),
+    ('j', "ĵǰ"),
+    ('k', "ķǩḱḳḵ"),
+    ('l', "ĺļľḷḹḻḽ"),
+    ('m', "ḿṁṃ"),
+    ('n', "ñńņňǹṅṇṉṋ"),
+    ('o', "òóôõöōŏőơǒǫǭȍȏȫȭȯȱṍṏṑṓọỏốồổỗộớờởỡợ"),
+    ('p', "ṕṗ"),
+    ('r', "ŕŗřȑȓṙṛṝṟ"),
+    ('s', "śŝşšșṡṣṥṧṩẛ"),
+    ('t', "ţťțṫṭṯṱẗ"),
+    ('u', "ùúûüũūŭůűųưǔǖǘǚǜȕȗṳṵṷṹṻụủứừửữự"),
+    ('v', "ṽṿ"),
+    ('w', "ŵẁẃẅẇẉẘ"),
+    ('x', "ẋẍ"),
+    ('y', "ýÿŷȳẏẙỳỵỷỹ"),
+    ('z', "źżžẑẓẕ"),
+    ('Æ', "ǢǼ"),
+    ('Ø', "Ǿ"),
+    ('Ʒ', "Ǯ"),
+    ('Α', "ΆἈἉἊἋἌἍἎἏᾈᾉᾊᾋᾌᾍᾎᾏᾸᾹᾺ\u{1FBB}ᾼ"),
+    ('Ε', "ΈἘἙἚἛἜἝῈ\u{1FC9}"),
+    ('Η', "ΉἨἩἪἫἬἭἮἯᾘᾙᾚᾛᾜᾝᾞᾟῊ\u{1FCB}ῌ"),
+    ('Ι', "ΊΪἸἹἺἻἼἽἾἿῘῙῚ\u{1FDB}"),
+    ('Ο', "ΌὈὉὊὋὌὍῸ\u{1FF9}"),
+    ('Ρ', "Ῥ"),
+    ('Υ', "ΎΫϓϔὙὛὝὟῨῩῪ\u{1FEB}"),
+    ('Ω', "ΏὨὩὪὫὬὭὮὯᾨᾩᾪᾫᾬᾭᾮᾯῺ\u{1FFB}ῼ"),
+    ('æ', "ǣǽ"),
+    ('ø', "ǿ"),
+    ('ʒ', "ǯ"),
+    ('α', "άἀἁἂἃἄἅἆἇὰ\u{1F71}ᾀᾁᾂᾃᾄᾅᾆᾇᾰᾱᾲᾳᾴᾶᾷ"),
+    ('ε', "έἐἑἒἓἔἕὲ\u{1F73}"),
+    ('η', "ήἠἡἢἣἤἥἦἧὴ\u{1F75}ᾐᾑᾒᾓᾔᾕᾖᾗῂῃῄῆῇ"),
+    ('ι', "ΐίϊἰἱἲἳἴἵἶἷὶ\u{1F77}ῐῑῒ\u{1FD3}ῖῗ"),
+    ('ο', "όὀὁὂὃὄὅὸ\u{1F79}"),
+    ('ρ', "ῤῥ"),
+    ('υ', "ΰϋύὐὑὒὓὔὕὖὗὺ\u{1F7B}ῠῡῢ\u{1FE3}ῦῧ"),
+    ('ω', "ώὠὡὢὣὤὥὦὧὼ\u{1F7D}ᾠᾡᾢᾣᾤᾥᾦᾧῲῳῴῶῷ"),
+];
+
+/// The base letter of each letter in [`DECOMPOSED_LETTERS`].
+static BASE_LETTERS: LazyLock<HashMap<char, char>> = LazyLock::new(|| {
+    DECOMPOSED_LETTERS
+        .iter()
+        .flat_map(|&(base, letters)| letters.chars().map(move |letter| (letter, base)))
+        .collect()
+});
+
+/// Pushes `c` onto `out` as it is matched.
+///
+/// With `fold_case`, `c` is case-folded: lowercased, with the lowercase
+/// forms that differ only in spelling folded together, so "STRASSE"
+/// matches "straße" and a final "ς" matches "σ". With `ignore_diacritics`,
+/// a Latin or Greek letter loses its diacritics ("é" becomes "e"), and a
+/// combining mark written on its own is dropped.
+pub fn push_folded(out: &mut String, c: char, fold_case: bool, ignore_diacritics: bool) {
+    let c = if ignore_diacritics {
+        if is_combining_mark(c) {
+            return;
+        }
+        BASE_LETTERS.get(&c).copied().unwrap_or(c)
+    } else {
+        c
+    };
+    if !fold_case {
+        out.push(c);
+        return;
+    }
+    for lower in c.to_lowercase() {
+        match lower {
+            'ß' => out.push_str("ss"),
+            'ς' => out.push('σ'),
+            'ſ' => out.push('s'),
+            'µ' => out.push('μ'),
+            'ﬀ' => out.push_str("ff"),
+            'ﬁ' => out.push_str("fi"),
+            'ﬂ' => out.push_str("fl"),
+            'ﬃ' => out.push_str("ffi"),
+            'ﬄ' => out.push_str("ffl"),
+            'ﬅ' | 'ﬆ' => out.push_str("st"),
+            // "İ" lowercases to "i" with a combining dot above
+            '\u{0307}' if ignore_diacritics => {}
+            _ => out.push(lower),
+        }
+    }
+}
+
+/// Checks whether `c` is a combining diacritical mark.
+const fn is_combining_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'   // Combining Diacritical Marks
+        | '\u{1AB0}'..='\u{1AFF}' // Combining Diacritical Marks Extended
+        | '\u{1DC0}'..='\u{1DFF}' // Combining Diacritical Marks Supplement
+        | '\u{20D0}'..='\u{20FF}' // Combining Diacritical Marks for Symbols
+        | '\u{FE20}'..='\u{FE2F}' // Combining Half Marks
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(text: &str, fold_case: bool, ignore_diacritics: bool) -> String {
+        let mut out = String::new();
+        for c in text.chars() {
+            push_folded(&mut out, c, fold_case, ignore_diacritics);
+        }
+        out
+    }
+
+    #[test]
+    fn test_push_folded_case() {
+        assert_eq!(fold("Résumé", true, false), "résumé");
+        assert_eq!(fold("STRASSE Straße", true, false), "strasse strasse");
+        assert_eq!(fold("ΟΔΟΣ οδος", true, false), "οδοσ οδοσ");
+        assert_eq!(fold("Résumé", false, false), "Résumé");
+    }
+
+    #[test]
+    fn test_push_folded_diacritics() {
+        assert_eq!(fold("Résumé", false, true), "Resume");
+        assert_eq!(fold("RÉSUMÉ Ångström", true, true), "resume angstrom");
+        // Decomposed: "e" followed by a combining acute accent
+        assert_eq!(fold("re\u{0301}sume\u{0301}", true, true), "resume");
+        assert_eq!(fold("Ἀθῆναι İstanbul", true, true), "αθηναι istanbul");
+        assert_eq!(fold("が й", true, true), "が й");
+    }
+}
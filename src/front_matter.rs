@@ -0,0 +1,164 @@
+//! Per-chapter settings from front matter.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// The `termlink` settings in the front matter of a chapter.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PageConfig {
+    /// Leaves the chapter without term links.
+    pub disable: bool,
+    /// Terms not linked in the chapter, by name or short form.
+    pub exclude_terms: Vec<String>,
+}
+
+/// Splits the termlink front matter off the top of a chapter.
+///
+/// The front matter is YAML between `---` lines or TOML between `+++`
+/// lines, with the settings under a `termlink` key:
+///
+/// ```markdown
+/// ---
+/// termlink: { disable: false, exclude-terms: ["API"] }
+/// ---
+/// ```
+///
+/// Returns the settings and the chapter without its front matter. A chapter
+/// without front matter, or whose front matter has no `termlink` key, is
+/// returned whole with the default settings, so a leading thematic break
+/// or another tool's front matter is left alone.
+///
+/// # Errors
+///
+/// Returns an error if the `termlink` settings are invalid.
+pub fn split(content: &str) -> Result<(PageConfig, &str)> {
+    let Some((settings, rest)) = termlink_block(content) else {
+        return Ok((PageConfig::default(), content));
+    };
+
+    let page = match settings {
+        Settings::Toml(value) => value.try_into().map_err(anyhow::Error::from),
+        Settings::Yaml(value) => serde_yaml_ng::from_value(value).map_err(anyhow::Error::from),
+    }
+    .context("Invalid termlink front matter")?;
+    Ok((page, rest))
+}
+
+/// Removes the termlink front matter from the top of a chapter, whether or
+/// not its settings are valid.
+pub fn strip(content: &str) -> &str {
+    termlink_block(content).map_or(content, |(_, rest)| rest)
+}
+
+/// The unparsed `termlink` settings of a front matter block.
+enum Settings {
+    Toml(toml::Value),
+    Yaml(serde_yaml_ng::Value),
+}
+
+/// Returns the `termlink` settings of the front matter block at the top of
+/// `content` and the content after it, if the block has a `termlink` key.
+fn termlink_block(content: &str) -> Option<(Settings, &str)> {
+    let (text, rest, toml) = block(content)?;
+    let settings = if toml {
+        toml::from_str::<toml::Table>(text)
+            .ok()?
+            .remove("termlink")
+            .map(Settings::Toml)
+    } else {
+        serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(text)
+            .ok()?
+            .remove("termlink")
+            .map(Settings::Yaml)
+    }?;
+    Some((settings, rest))
+}
+
+/// Returns the text of the front matter block at the top of `content`, the
+/// content after it, and whether it is TOML.
+fn block(content: &str) -> Option<(&str, &str, bool)> {
+    let fence = if content.starts_with("---") {
+        "---"
+    } else if content.starts_with("+++") {
+        "+++"
+    } else {
+        return None;
+    };
+
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end() != fence {
+        return None;
+    }
+    let start = first.len();
+    let mut end = start;
+    for line in lines {
+        if line.trim_end() == fence {
+            return Some((
+                &content[start..end],
+                &content[end + line.len()..],
+                fence == "+++",
+            ));
+        }
+        end += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_yaml() {
+        let content = "---\ntitle: Intro\ntermlink: { disable: true, exclude-terms: [\"API\"] }\n---\n# Intro\n";
+        let (page, rest) = split(content).unwrap();
+        assert!(page.disable);
+        assert_eq!(page.exclude_terms, ["API"]);
+        assert_eq!(rest, "# Intro\n");
+    }
+
+    #[test]
+    fn test_split_toml() {
+        let content = "+++\r\n[termlink]\r\nexclude-terms = [\"REST\"]\r\n+++\r\n\r\nText";
+        let (page, rest) = split(content).unwrap();
+        assert!(!page.disable);
+        assert_eq!(page.exclude_terms, ["REST"]);
+        assert_eq!(rest, "\r\nText");
+    }
+
+    #[test]
+    fn test_split_keeps_other_content() {
+        for content in [
+            "# Intro\n\n---\ntermlink: { disable: true }\n---\n",
+            "---\n\nA thematic break, then prose.\n\n---\n",
+            "---\ntitle: Intro\n---\n# Intro\n",
+            "---\ntermlink: { disable: true }\n",
+            "+++\n[termlink]\n",
+        ] {
+            let (page, rest) = split(content).unwrap();
+            assert_eq!(page, PageConfig::default());
+            assert_eq!(rest, content);
+        }
+    }
+
+    #[test]
+    fn test_split_rejects_invalid_settings() {
+        assert!(split("---\ntermlink: { disabled: true }\n---\n").is_err());
+        assert!(split("+++\n[termlink]\nexclude-terms = \"API\"\n+++\n").is_err());
+    }
+
+    #[test]
+    fn test_strip() {
+        assert_eq!(
+            strip("---\ntermlink: { disabled: true }\n---\n# Intro\n"),
+            "# Intro\n"
+        );
+        assert_eq!(strip("+++\n[termlink]\ndisable = true\n+++\nText"), "Text");
+        assert_eq!(
+            strip("---\ntitle: Intro\n---\n# Intro\n"),
+            "---\ntitle: Intro\n---\n# Intro\n"
+        );
+    }
+}
@@ -1,12 +1,19 @@
 //! Glossary term parsing using pulldown-cmark.
 
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use mdbook_preprocessor::book::{Book, BookItem};
-use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd, html};
+use pulldown_cmark_to_cmark::cmark;
 
-use crate::Config;
+use crate::config::{AnchorStyle, GlossarySource};
+use crate::location::{LineIndex, Position};
+use crate::{Config, paths, term_file};
 
 /// A glossary term extracted from a definition list.
 ///
@@ -18,12 +25,40 @@ pub struct Term {
     name: String,
     /// The URL anchor for this term (lowercase, hyphenated).
     anchor: String,
+    /// Whether the anchor was given explicitly rather than generated from the name.
+    custom_anchor: bool,
     /// Optional short form for terms like "API (Application Programming Interface)".
     short_name: Option<String>,
     /// The definition text for this term (used for tooltip preview).
     definition: Option<String>,
-    /// Additional aliases configured in book.toml.
+    /// The definition rendered to HTML, keeping its formatting (used for popovers).
+    definition_html: Option<String>,
+    /// The definition as markdown (used for generated glossary pages).
+    definition_markdown: Option<String>,
+    /// Where the definition comes from (standard number, document reference).
+    citation: Option<String>,
+    /// The glossary section heading the term is listed under.
+    category: Option<String>,
+    /// Additional aliases configured in book.toml or declared in the glossary.
     aliases: Vec<String>,
+    /// Names from an `Also known as:` line (also among the aliases).
+    also_known_as: Vec<String>,
+    /// The glossary chapter defining this term (relative to `src`).
+    page: Option<PathBuf>,
+    /// Language the term's glossary (or term file entry) is written in.
+    lang: Option<String>,
+    /// URL the term links to instead of its glossary entry.
+    url: Option<String>,
+    /// Deprecation note, present (possibly empty) when the term is deprecated.
+    deprecation: Option<String>,
+    /// Rank of the term's occurrences where they overlap another term's (higher wins).
+    priority: i32,
+    /// Names of related terms, from a `See also:` line.
+    see_also: Vec<String>,
+    /// Whether the term is defined in `book.toml` and has no glossary entry.
+    inline: bool,
+    /// Where the entry starts on its glossary page, for diagnostics.
+    position: Option<Position>,
 }
 
 impl Term {
@@ -45,9 +80,23 @@ impl Term {
         Self {
             name,
             anchor,
+            custom_anchor: false,
             short_name,
             definition: None,
+            definition_html: None,
+            definition_markdown: None,
+            citation: None,
+            category: None,
             aliases: Vec::new(),
+            also_known_as: Vec::new(),
+            page: None,
+            lang: None,
+            url: None,
+            deprecation: None,
+            priority: 0,
+            see_also: Vec::new(),
+            inline: false,
+            position: None,
         }
     }
 
@@ -59,6 +108,100 @@ impl Term {
         term
     }
 
+    /// Sets the definition rendered to HTML.
+    #[must_use]
+    pub fn with_definition_html(mut self, definition_html: Option<String>) -> Self {
+        self.definition_html = definition_html;
+        self
+    }
+
+    /// Sets the definition as markdown.
+    #[must_use]
+    pub fn with_definition_markdown(mut self, definition_markdown: Option<String>) -> Self {
+        self.definition_markdown = definition_markdown;
+        self
+    }
+
+    /// Sets the citation for this term's definition.
+    #[must_use]
+    pub fn with_citation(mut self, citation: Option<String>) -> Self {
+        self.citation = citation;
+        self
+    }
+
+    /// Overrides the generated anchor, for glossary entries with a custom id.
+    #[must_use]
+    pub fn with_anchor(mut self, anchor: impl Into<String>) -> Self {
+        self.anchor = anchor.into();
+        self.custom_anchor = true;
+        self
+    }
+
+    /// Sets the category (glossary section) of this term.
+    #[must_use]
+    pub fn with_category(mut self, category: Option<String>) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Sets the language the term's definition is written in.
+    #[must_use]
+    pub fn with_lang(mut self, lang: Option<String>) -> Self {
+        self.lang = lang;
+        self
+    }
+
+    /// Sets the glossary chapter defining this term.
+    #[must_use]
+    pub fn with_page(mut self, page: impl Into<PathBuf>) -> Self {
+        self.page = Some(page.into());
+        self
+    }
+
+    /// Sets where the term's entry starts on its glossary page.
+    #[must_use]
+    pub(crate) const fn with_position(mut self, position: Position) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Sets the URL the term links to instead of its glossary entry.
+    #[must_use]
+    pub fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// Sets the priority of the term's occurrences over overlapping ones of other terms.
+    #[must_use]
+    pub const fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the names of related terms, listed in a `See also:` line.
+    #[must_use]
+    pub fn with_see_also(mut self, names: Vec<String>) -> Self {
+        self.see_also = names;
+        self
+    }
+
+    /// Sets the other names of the term, listed in an `Also known as:` line, and
+    /// adds them to its aliases.
+    #[must_use]
+    pub fn with_also_known_as(mut self, names: Vec<String>) -> Self {
+        self.aliases.extend(names.iter().cloned());
+        self.also_known_as = names;
+        self
+    }
+
+    /// Marks the term as deprecated, with an optional note such as "Use REST instead."
+    #[must_use]
+    pub fn deprecated(mut self, note: impl Into<String>) -> Self {
+        self.deprecation = Some(note.into());
+        self
+    }
+
     /// Adds aliases to this term.
     #[must_use]
     pub fn with_aliases(mut self, aliases: Vec<String>) -> Self {
@@ -78,6 +221,13 @@ impl Term {
         &self.anchor
     }
 
+    /// Returns whether the anchor was given explicitly, as `REST {#rest-arch}`
+    /// in the glossary or `anchor` in a term file.
+    #[must_use]
+    pub const fn has_custom_anchor(&self) -> bool {
+        self.custom_anchor
+    }
+
     /// Returns the optional short form of the term.
     ///
     /// For example, "API" from "API (Application Programming Interface)".
@@ -86,6 +236,19 @@ impl Term {
         self.short_name.as_deref()
     }
 
+    /// Returns the long form of an acronym term.
+    ///
+    /// For example, "Application Programming Interface" from
+    /// "API (Application Programming Interface)". `None` for terms without a short form.
+    #[must_use]
+    pub fn long_name(&self) -> Option<&str> {
+        self.short_name.as_ref()?;
+        let start = self.name.find('(')? + 1;
+        let end = self.name.rfind(')')?;
+        let long = self.name.get(start..end)?.trim();
+        (!long.is_empty()).then_some(long)
+    }
+
     /// Returns the definition text for this term (if available).
     ///
     /// Used for tooltip preview on hover.
@@ -94,6 +257,117 @@ impl Term {
         self.definition.as_deref()
     }
 
+    /// Returns the definition rendered to HTML (if available).
+    ///
+    /// Unlike [`Self::definition`], this keeps emphasis, code, and links.
+    #[must_use]
+    pub fn definition_html(&self) -> Option<&str> {
+        self.definition_html.as_deref()
+    }
+
+    /// Returns the definition as markdown (if available).
+    #[must_use]
+    pub fn definition_markdown(&self) -> Option<&str> {
+        self.definition_markdown.as_deref()
+    }
+
+    /// Returns the citation for the definition (if available).
+    ///
+    /// For example, "ISO 8601:2019, 3.1.1" from a `: Source: ISO 8601:2019, 3.1.1`
+    /// line in the glossary.
+    #[must_use]
+    pub fn citation(&self) -> Option<&str> {
+        self.citation.as_deref()
+    }
+
+    /// Returns the category of this term (if available).
+    ///
+    /// This is the heading of the glossary section listing the term, for
+    /// example "File formats" for terms below `## File formats`.
+    #[must_use]
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    /// Returns the aliases configured for this term.
+    #[must_use]
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    /// Returns the glossary chapter defining this term (relative to `src`).
+    ///
+    /// `None` for terms not extracted from a book.
+    #[must_use]
+    pub fn page(&self) -> Option<&Path> {
+        self.page.as_deref()
+    }
+
+    /// Returns the language the term's definition is written in, if known.
+    ///
+    /// Links declare it where it differs from the book's language.
+    #[must_use]
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    /// Returns where the term's entry starts on its glossary page, if it was parsed from one.
+    #[must_use]
+    pub(crate) const fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Returns the URL the term links to instead of its glossary entry (if any).
+    #[must_use]
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Returns the priority of the term's occurrences over overlapping ones (0 unless set).
+    #[must_use]
+    pub const fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Returns the names of the related terms listed in its `See also:` line.
+    #[must_use]
+    pub fn see_also(&self) -> &[String] {
+        &self.see_also
+    }
+
+    /// Returns the other names listed in its `Also known as:` line.
+    #[must_use]
+    pub fn also_known_as(&self) -> &[String] {
+        &self.also_known_as
+    }
+
+    /// Marks the term as defined in `inline-terms`, without a glossary entry.
+    #[must_use]
+    pub const fn defined_inline(mut self) -> Self {
+        self.inline = true;
+        self
+    }
+
+    /// Returns true if occurrences link somewhere: to the glossary entry or the term's URL.
+    ///
+    /// Inline terms without a URL only get a tooltip.
+    #[must_use]
+    pub const fn has_target(&self) -> bool {
+        !self.inline || self.url.is_some()
+    }
+
+    /// Returns true if the term is deprecated.
+    #[must_use]
+    pub const fn is_deprecated(&self) -> bool {
+        self.deprecation.is_some()
+    }
+
+    /// Returns the deprecation note of a deprecated term (empty if none was given).
+    #[must_use]
+    pub fn deprecation(&self) -> Option<&str> {
+        self.deprecation.as_deref()
+    }
+
     /// Returns all searchable forms of this term.
     ///
     /// This includes the full name, short name (if present), and any aliases.
@@ -110,28 +384,481 @@ impl Term {
 
 /// Extracts glossary terms from the book.
 ///
+/// When `glossary-path` is a directory, terms are collected from every
+/// chapter below it in book order, each remembering the page defining it.
+/// A glossary that is not a chapter, such as one outside `src` shared between
+/// books, is read from below `src_dir` on disk. With `glossary-source = "file"`,
+/// terms are read from the term file below `root` instead and link to the
+/// `glossary-path` page. Scoped glossaries are always read from their chapters.
+/// The terms of `inline-terms` come last; with them, the glossary may be missing.
+///
 /// # Errors
 ///
-/// Returns an error if the glossary file specified in the config is not found.
-pub fn extract_terms(book: &Book, config: &Config) -> Result<Vec<Term>> {
-    let glossary_content = find_glossary_content(book, config.glossary_path())?;
-    Ok(parse_definition_lists(&glossary_content))
-}
+/// Returns an error if the glossary file specified in the config is not found
+/// (and there are no inline terms), or cannot be parsed.
+pub fn extract_terms(
+    book: &Book,
+    root: &Path,
+    src_dir: &Path,
+    config: &Config,
+) -> Result<Vec<Term>> {
+    let from_file = config.glossary_source() == GlossarySource::File;
+    let mut terms = Vec::new();
+    if from_file {
+        let file_terms =
+            term_file::load_terms(&root.join(config.glossary_file()), config.csv_columns())?;
+        let file_lang = config.glossary_lang(Some(config.glossary_path()));
+        terms.extend(file_terms.into_iter().map(|term| {
+            // An entry's own language wins over the glossary's
+            let lang = term.lang().or(file_lang).map(String::from);
+            term.with_lang(lang).with_page(config.glossary_path())
+        }));
+    }
+    let mut found = from_file;
 
-/// Finds and returns the content of the glossary chapter.
-fn find_glossary_content(book: &Book, glossary_path: &Path) -> Result<String> {
     for item in book.iter() {
         if let BookItem::Chapter(chapter) = item
             && let Some(ref path) = chapter.path
-            && (path == glossary_path || path.ends_with(glossary_path))
+            && config.is_glossary_path(path)
+        {
+            let scoped = config.glossary_scope(path).is_some();
+            if from_file && !scoped {
+                continue;
+            }
+            found |= !scoped;
+            let lang = config.glossary_lang(Some(path)).map(String::from);
+            terms.extend(
+                parse_definition_lists(&chapter.content)
+                    .into_iter()
+                    .map(|term| term.with_lang(lang.clone()).with_page(path)),
+            );
+        }
+    }
+
+    if !found {
+        let glossary_file = src_dir.join(config.glossary_path());
+        if glossary_file.is_file() {
+            log::debug!(
+                "Glossary is not a chapter of the book; reading {}",
+                glossary_file.display()
+            );
+            let content = fs::read_to_string(&glossary_file)
+                .with_context(|| format!("Failed to read {}", glossary_file.display()))?;
+            let lang = config
+                .glossary_lang(Some(config.glossary_path()))
+                .map(String::from);
+            terms.extend(parse_definition_lists(&content).into_iter().map(|term| {
+                term.with_lang(lang.clone())
+                    .with_page(config.glossary_path())
+            }));
+        } else if config.inline_terms().is_empty() {
+            // A book may define all its terms inline instead
+            bail!(
+                "Glossary file not found: {}",
+                config.glossary_path().display()
+            );
+        }
+    }
+    terms.extend(inline_terms(config));
+    Ok(terms)
+}
+
+/// Builds the terms defined in `inline-terms`.
+fn inline_terms(config: &Config) -> impl Iterator<Item = Term> {
+    config.inline_terms().iter().map(|(name, term)| {
+        let definition = term.definition.as_deref();
+        Term::with_definition(name.trim(), definition.and_then(plain_definition))
+            .with_definition_html(definition.map(render_definition))
+            .with_definition_markdown(term.definition.clone())
+            .with_url(term.url.clone())
+            .with_lang(config.glossary_lang(None).map(String::from))
+            .defined_inline()
+    })
+}
+
+/// Extracts glossary terms from the book and applies the aliases configured in `book.toml`.
+///
+/// # Errors
+///
+/// Returns an error if the glossary file is not found, two terms conflict
+/// (see [`check_conflicts`]), or an alias conflicts
+/// with the name of a different term.
+pub fn resolve_terms(
+    book: &Book,
+    root: &Path,
+    src_dir: &Path,
+    config: &Config,
+) -> Result<Vec<Term>> {
+    let terms = restyle_anchors(extract_terms(book, root, src_dir, config)?, config)?;
+    // A scoped glossary may redefine a term for its chapters
+    for (_, scoped) in term_sets(&terms, config) {
+        check_conflicts(&scoped, config.case_sensitive())?;
+    }
+
+    warn_unknown_see_also(&terms);
+
+    // Validate alias conflicts (before applying aliases)
+    let term_names: HashSet<String> = terms.iter().map(|t| t.name().to_lowercase()).collect();
+    let declared = terms.iter().map(|t| (t.name(), t.also_known_as()));
+    let configured = config
+        .all_aliases()
+        .map(|(name, aliases)| (name.as_str(), aliases.as_slice()));
+
+    for (term_name, aliases) in declared.chain(configured) {
+        for alias in aliases {
+            let alias_lower = alias.to_lowercase();
+            // Check if alias conflicts with a different term's name
+            if term_names.contains(&alias_lower) && alias_lower != term_name.to_lowercase() {
+                bail!("Alias '{alias}' for term '{term_name}' conflicts with existing term");
+            }
+        }
+    }
+
+    // Apply aliases, link targets, priorities, and deprecations from config to terms
+    Ok(terms
+        .into_iter()
+        .map(|term| match config.url(term.name()) {
+            Some(url) => term.with_url(Some(url.to_string())),
+            None => term,
+        })
+        .map(|term| match config.priority(term.name()) {
+            Some(priority) => term.with_priority(priority),
+            None => term,
+        })
+        .map(|term| {
+            if config.is_deprecated(term.name()) && !term.is_deprecated() {
+                term.deprecated("")
+            } else {
+                term
+            }
+        })
+        .map(|term| {
+            if let Some(aliases) = config.aliases(term.name()) {
+                // Keep aliases from a term file alongside the configured ones
+                let mut all_aliases = term.aliases().to_vec();
+                all_aliases.extend(aliases.iter().cloned());
+                term.with_aliases(all_aliases)
+            } else {
+                term
+            }
+        })
+        .collect())
+}
+
+/// Derives the anchors of terms without an explicit one in the configured `anchor-style`.
+///
+/// With `custom`, a term linking to its glossary entry must have an explicit anchor.
+fn restyle_anchors(mut terms: Vec<Term>, config: &Config) -> Result<Vec<Term>> {
+    match config.anchor_style() {
+        AnchorStyle::Mdbook => {}
+        AnchorStyle::Github => {
+            for term in terms.iter_mut().filter(|term| !term.custom_anchor) {
+                term.anchor = github_anchor(&term.name);
+            }
+        }
+        AnchorStyle::Custom => {
+            let missing = terms.iter().find(|term| {
+                !term.custom_anchor
+                    && !term.inline
+                    && term.url.is_none()
+                    && config.url(term.name()).is_none()
+            });
+            if let Some(term) = missing {
+                bail!(
+                    "Glossary term {} has no anchor; with anchor-style = \"custom\" each entry needs one, as in `{} {{#id}}`",
+                    describe(term),
+                    term.name()
+                );
+            }
+        }
+    }
+    Ok(terms)
+}
+
+/// Warns about `See also:` references to names that are not glossary terms.
+fn warn_unknown_see_also(terms: &[Term]) {
+    let known: HashSet<String> = terms
+        .iter()
+        .flat_map(|term| [Some(term.name()), term.short_name()])
+        .flatten()
+        .map(str::to_lowercase)
+        .collect();
+    for term in terms {
+        for name in term.see_also() {
+            if !known.contains(&name.to_lowercase()) {
+                log::warn!(
+                    "Glossary term {} refers to '{name}', which is not a glossary term",
+                    describe(term)
+                );
+            }
+        }
+    }
+}
+
+/// Returns the terms applying to the chapters below `dir`, in glossary order.
+///
+/// These are the terms of the global glossary, and of every scoped glossary
+/// whose directory contains `dir`; only the global ones without a `dir`. A
+/// term from a deeper glossary shadows those of wider ones sharing its name
+/// or short form.
+pub fn scope_terms(terms: &[Term], dir: Option<&Path>, config: &Config) -> Vec<Term> {
+    // Depth of the glossary defining each applicable term, 0 for the global one
+    let mut applicable: Vec<(usize, usize, &Term)> = terms
+        .iter()
+        .enumerate()
+        .filter_map(|(index, term)| {
+            match term.page().and_then(|page| config.glossary_scope(page)) {
+                None => Some((0, index, term)),
+                Some(scope) if dir.is_some_and(|dir| dir.starts_with(scope)) => {
+                    Some((scope.components().count() + 1, index, term))
+                }
+                Some(_) => None,
+            }
+        })
+        .collect();
+    applicable.sort_by_key(|&(depth, index, _)| (Reverse(depth), index));
+
+    let mut defined: HashMap<String, usize> = HashMap::new();
+    let mut kept: Vec<(usize, &Term)> = Vec::new();
+    for (depth, index, term) in applicable {
+        let forms: Vec<String> = [Some(term.name()), term.short_name()]
+            .into_iter()
+            .flatten()
+            .map(str::to_lowercase)
+            .collect();
+        if forms
+            .iter()
+            .any(|form| defined.get(form).is_some_and(|&d| d > depth))
         {
-            return Ok(chapter.content.clone());
+            continue;
+        }
+        for form in forms {
+            defined.entry(form).or_insert(depth);
+        }
+        kept.push((index, term));
+    }
+    kept.sort_by_key(|&(index, _)| index);
+    kept.into_iter().map(|(_, term)| term.clone()).collect()
+}
+
+/// Returns the terms of each glossary scope: those of the whole book (`None`), then those below each scoped glossary's directory.
+#[must_use]
+pub fn term_sets(terms: &[Term], config: &Config) -> Vec<(Option<PathBuf>, Vec<Term>)> {
+    std::iter::once(None)
+        .chain(config.scoped_glossaries().iter().map(|(dir, _)| Some(dir)))
+        .map(|dir| {
+            (
+                dir.cloned(),
+                scope_terms(terms, dir.map(PathBuf::as_path), config),
+            )
+        })
+        .collect()
+}
+
+/// Checks that every glossary term can be told apart from the others.
+///
+/// Two entries on one page with the same anchor would both be linked to the
+/// first. A term name equal to another term's name or short form, or two
+/// equal short forms, would leave it to chance which term an occurrence
+/// links to.
+///
+/// # Errors
+///
+/// Returns an error naming both entries of the first conflict found.
+fn check_conflicts(terms: &[Term], case_sensitive: bool) -> Result<()> {
+    let mut anchors: HashMap<(Option<&Path>, &str), &Term> = HashMap::new();
+    // Names and short forms, as they are matched
+    let mut forms: HashMap<String, &Term> = HashMap::new();
+
+    for term in terms {
+        if let Some(first) = anchors.insert((term.page(), term.anchor()), term) {
+            bail!(
+                "Glossary terms {} and {} both have the anchor #{}",
+                describe(first),
+                describe(term),
+                term.anchor()
+            );
+        }
+        for form in [Some(term.name()), term.short_name()].into_iter().flatten() {
+            let key = if case_sensitive {
+                form.to_string()
+            } else {
+                form.to_lowercase()
+            };
+            if let Some(first) = forms.insert(key, term) {
+                bail!(
+                    "Glossary terms {} and {} both match '{form}'",
+                    describe(first),
+                    describe(term)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Describes a term for error messages: its name and the page defining it.
+fn describe(term: &Term) -> String {
+    match (term.page(), term.position()) {
+        (Some(page), Some(position)) => {
+            format!(
+                "'{}' ({}:{position})",
+                term.name(),
+                paths::to_url_path(page)
+            )
         }
+        (Some(page), None) => format!("'{}' ({})", term.name(), paths::to_url_path(page)),
+        (None, _) => format!("'{}'", term.name()),
     }
-    bail!("Glossary file not found: {}", glossary_path.display())
+}
+
+/// Prefix marking a definition as the citation of the term rather than its text.
+const CITATION_PREFIX: &str = "Source:";
+
+/// Prefix marking a definition as the category of the term rather than its text.
+const CATEGORY_PREFIX: &str = "Category:";
+
+/// Prefix marking a definition as a list of related terms rather than its text.
+const SEE_ALSO_PREFIX: &str = "See also:";
+
+/// Prefix marking a definition as a list of other names of the term rather than its text.
+const ALSO_KNOWN_AS_PREFIX: &str = "Also known as:";
+
+/// Word marking a term as deprecated, optionally followed by `:` and a note.
+const DEPRECATED_MARKER: &str = "Deprecated";
+
+/// A definition line that annotates the term instead of defining it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Annotation {
+    /// `Source: ...`, where the definition comes from.
+    Citation(String),
+    /// `Category: ...`, overriding the section heading.
+    Category(String),
+    /// `Deprecated` or `Deprecated: <note>`.
+    Deprecated(String),
+    /// `See also: ...`, related terms separated by commas.
+    SeeAlso(Vec<String>),
+    /// `Also known as: ...`, aliases separated by commas.
+    AlsoKnownAs(Vec<String>),
+}
+
+impl Annotation {
+    /// Returns the annotation if `definition` is a `Source: ...`, `Category: ...`,
+    /// `See also: ...`, `Also known as: ...`, or `Deprecated` line.
+    fn parse(definition: &str) -> Option<Self> {
+        parse_prefixed(definition, CITATION_PREFIX)
+            .map(Self::Citation)
+            .or_else(|| parse_prefixed(definition, CATEGORY_PREFIX).map(Self::Category))
+            .or_else(|| {
+                parse_prefixed(definition, SEE_ALSO_PREFIX)
+                    .map(|names| Self::SeeAlso(name_list(&names)))
+            })
+            .or_else(|| {
+                parse_prefixed(definition, ALSO_KNOWN_AS_PREFIX)
+                    .map(|names| Self::AlsoKnownAs(name_list(&names)))
+            })
+            .or_else(|| parse_deprecated(definition).map(Self::Deprecated))
+    }
+
+    /// Applies the annotation to `term`.
+    fn apply(self, term: &mut Term) {
+        match self {
+            Self::Citation(citation) => term.citation = Some(citation),
+            Self::Category(category) => term.category = Some(category),
+            Self::Deprecated(note) => term.deprecation = Some(note),
+            Self::SeeAlso(names) => term.see_also.extend(names),
+            Self::AlsoKnownAs(names) => {
+                term.aliases.extend(names.iter().cloned());
+                term.also_known_as.extend(names);
+            }
+        }
+    }
+}
+
+/// Splits a comma-separated list of names, dropping a trailing period.
+fn name_list(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(|name| name.trim().trim_end_matches('.').trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Returns the text after `prefix` (matched case-insensitively), if `line` starts with it.
+fn parse_prefixed(line: &str, prefix: &str) -> Option<String> {
+    let start = line.get(..prefix.len())?;
+    if !start.eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+    non_empty(&line[prefix.len()..])
+}
+
+/// Returns the note if `line` is `Deprecated`, `Deprecated.`, or `Deprecated: <note>`.
+fn parse_deprecated(line: &str) -> Option<String> {
+    let start = line.get(..DEPRECATED_MARKER.len())?;
+    if !start.eq_ignore_ascii_case(DEPRECATED_MARKER) {
+        return None;
+    }
+    match &line[DEPRECATED_MARKER.len()..] {
+        "" | "." => Some(String::new()),
+        rest => rest.strip_prefix(':').map(|note| note.trim().to_string()),
+    }
+}
+
+/// Creates a term listed under the `category` heading, with its pending annotations.
+///
+/// `definition` holds the events of the term's definition. A `{#id}` at the
+/// end of `title` sets the term's anchor; `position` is where the title starts.
+fn finish_term(
+    title: &str,
+    position: Position,
+    definition: &[Event],
+    category: Option<&String>,
+    annotations: &mut Vec<Annotation>,
+) -> Term {
+    let (name, anchor) = split_anchor(title);
+    let mut term = Term::with_definition(name, non_empty(&plain_text(definition)))
+        .with_definition_html(render_events(definition))
+        .with_definition_markdown(markdown_events(definition))
+        .with_category(category.cloned())
+        .with_position(position);
+    if let Some(anchor) = anchor {
+        term = term.with_anchor(anchor);
+    }
+    for annotation in annotations.drain(..) {
+        annotation.apply(&mut term);
+    }
+    term
+}
+
+/// Creates the term of a pending title that has no definition, unless the title is empty.
+fn finish_pending(
+    title: Option<String>,
+    position: Position,
+    category: Option<&String>,
+    annotations: &mut Vec<Annotation>,
+) -> Option<Term> {
+    let title = title.filter(|title| !title.is_empty())?;
+    Some(finish_term(&title, position, &[], category, annotations))
 }
 
 /// Parses definition lists from markdown content using pulldown-cmark.
+///
+/// A definition starting with `Source:` is taken as the citation of the term,
+/// one starting with `Category:` as its category, one starting with
+/// `Also known as:` as its aliases, and a `Deprecated` line (optionally
+/// followed by `:` and a note) marks the term as deprecated:
+///
+/// ```markdown
+/// ISO Week
+/// : A week starting on Monday.
+/// : Source: ISO 8601-1:2019, 3.1.2.23
+/// : Category: Dates
+/// : Also known as: business week
+/// ```
+///
+/// Otherwise, terms listed below a section heading (level two or deeper) take
+/// the heading text as their category; the page title does not count.
 fn parse_definition_lists(content: &str) -> Vec<Term> {
     let mut terms = Vec::new();
 
@@ -140,79 +867,98 @@ fn parse_definition_lists(content: &str) -> Vec<Term> {
     options.insert(Options::ENABLE_DEFINITION_LIST);
 
     let parser = Parser::new_ext(content, options);
+    let lines = LineIndex::new(content);
 
     let mut in_definition_list = false;
     let mut in_title = false;
     let mut in_definition = false;
     let mut current_title_text = String::new();
-    let mut current_definition_text = String::new();
+    let mut definition_events: Vec<Event> = Vec::new();
     let mut pending_title: Option<String> = None;
+    // Where the pending title starts
+    let mut title_position = lines.position(0);
+    let mut pending_annotations: Vec<Annotation> = Vec::new();
+    let mut category: Option<String> = None;
+    let mut heading_text: Option<String> = None;
 
-    for event in parser {
+    for (event, range) in parser.into_offset_iter() {
+        if in_definition && event != Event::End(TagEnd::DefinitionListDefinition) {
+            definition_events.push(event.clone());
+        }
         match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                if level == HeadingLevel::H1 {
+                    category = None;
+                } else {
+                    heading_text = Some(String::new());
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(text) = heading_text.take() {
+                    category = non_empty(&text);
+                }
+            }
             Event::Start(Tag::DefinitionList) => {
                 in_definition_list = true;
             }
             Event::End(TagEnd::DefinitionList) => {
                 in_definition_list = false;
                 // Handle any pending term without definition
-                if let Some(title) = pending_title.take()
-                    && !title.is_empty()
-                {
-                    terms.push(Term::new(title));
-                }
+                terms.extend(finish_pending(
+                    pending_title.take(),
+                    title_position,
+                    category.as_ref(),
+                    &mut pending_annotations,
+                ));
             }
-            Event::Start(Tag::DefinitionListTitle) => {
-                if in_definition_list {
-                    // If we have a pending term, save it before starting a new one
-                    if let Some(title) = pending_title.take()
-                        && !title.is_empty()
-                    {
-                        let definition = if current_definition_text.trim().is_empty() {
-                            None
-                        } else {
-                            Some(current_definition_text.trim().to_string())
-                        };
-                        terms.push(Term::with_definition(title, definition));
-                    }
-                    in_title = true;
-                    current_title_text.clear();
-                    current_definition_text.clear();
-                }
+            Event::Start(Tag::DefinitionListTitle) if in_definition_list => {
+                // If we have a pending term, save it before starting a new one
+                terms.extend(finish_pending(
+                    pending_title.take(),
+                    title_position,
+                    category.as_ref(),
+                    &mut pending_annotations,
+                ));
+                pending_annotations.clear();
+                title_position = lines.position(range.start);
+                in_title = true;
+                current_title_text.clear();
             }
-            Event::End(TagEnd::DefinitionListTitle) => {
-                if in_title {
-                    pending_title = Some(current_title_text.trim().to_string());
-                    in_title = false;
-                }
+            Event::End(TagEnd::DefinitionListTitle) if in_title => {
+                pending_title = Some(current_title_text.trim().to_string());
+                in_title = false;
             }
-            Event::Start(Tag::DefinitionListDefinition) => {
-                if in_definition_list {
-                    in_definition = true;
-                }
+            Event::Start(Tag::DefinitionListDefinition) if in_definition_list => {
+                in_definition = true;
             }
-            Event::End(TagEnd::DefinitionListDefinition) => {
-                if in_definition {
-                    in_definition = false;
-                    // Apply definition to pending term and save it
-                    if let Some(title) = pending_title.take()
-                        && !title.is_empty()
-                    {
-                        let definition = if current_definition_text.trim().is_empty() {
-                            None
-                        } else {
-                            Some(current_definition_text.trim().to_string())
-                        };
-                        terms.push(Term::with_definition(title, definition));
-                        current_definition_text.clear();
+            Event::End(TagEnd::DefinitionListDefinition) if in_definition => {
+                in_definition = false;
+                if let Some(annotation) = Annotation::parse(plain_text(&definition_events).trim()) {
+                    // Attach to the term, whether or not its definition came first
+                    if pending_title.is_some() {
+                        pending_annotations.push(annotation);
+                    } else if let Some(term) = terms.last_mut() {
+                        annotation.apply(term);
                     }
+                } else if let Some(title) = pending_title.take()
+                    && !title.is_empty()
+                {
+                    // Apply definition to pending term and save it
+                    terms.push(finish_term(
+                        &title,
+                        title_position,
+                        &definition_events,
+                        category.as_ref(),
+                        &mut pending_annotations,
+                    ));
                 }
+                definition_events.clear();
             }
             Event::Text(text) | Event::Code(text) => {
-                if in_title {
+                if let Some(ref mut heading) = heading_text {
+                    heading.push_str(&text);
+                } else if in_title {
                     current_title_text.push_str(&text);
-                } else if in_definition {
-                    current_definition_text.push_str(&text);
                 }
             }
             _ => {}
@@ -222,6 +968,158 @@ fn parse_definition_lists(content: &str) -> Vec<Term> {
     terms
 }
 
+/// Returns the names of the glossary terms a chapter defines itself, in a definition list of its own.
+///
+/// A local definition title matches a glossary term by any of their names
+/// and short forms, case-insensitively unless `case_sensitive`.
+pub fn local_definitions(content: &str, terms: &[Term], case_sensitive: bool) -> Vec<String> {
+    let key = |form: &str| {
+        if case_sensitive {
+            form.to_string()
+        } else {
+            form.to_lowercase()
+        }
+    };
+    let local: HashSet<String> = parse_definition_lists(content)
+        .iter()
+        .flat_map(|term| [Some(term.name()), term.short_name()])
+        .flatten()
+        .map(key)
+        .collect();
+    if local.is_empty() {
+        return Vec::new();
+    }
+    terms
+        .iter()
+        .filter(|term| {
+            [Some(term.name()), term.short_name()]
+                .into_iter()
+                .flatten()
+                .any(|form| local.contains(&key(form)))
+        })
+        .map(|term| term.name().to_string())
+        .collect()
+}
+
+/// Splits an explicit anchor off a glossary entry title: `REST {#rest-arch}`
+/// is the term `REST` with the anchor `rest-arch`.
+///
+/// The anchor may hold letters, digits, `-`, `_`, `.`, and `:`; anything else
+/// is left as part of the name.
+#[must_use]
+pub fn split_anchor(title: &str) -> (&str, Option<&str>) {
+    let title = title.trim_end();
+    let Some(rest) = title.strip_suffix('}') else {
+        return (title, None);
+    };
+    let Some(start) = rest.rfind("{#") else {
+        return (title, None);
+    };
+    let anchor = &rest[start + 2..];
+    let valid = !anchor.is_empty()
+        && anchor
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    let name = rest[..start].trim_end();
+    if !valid || name.is_empty() {
+        return (title, None);
+    }
+    (name, Some(anchor))
+}
+
+/// Turns the explicit anchors of a glossary page's entries into link targets.
+///
+/// `REST {#rest-arch}` becomes `<a id="rest-arch"></a>REST`, so the entry
+/// shows just its name while links to `#rest-arch` still land on it.
+#[must_use]
+pub fn add_anchor_targets(content: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_DEFINITION_LIST);
+
+    let mut output = String::with_capacity(content.len());
+    let mut copied = 0;
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        let Event::Start(Tag::DefinitionListTitle) = event else {
+            continue;
+        };
+        let source = content[range.clone()].trim_end();
+        if let (name, Some(anchor)) = split_anchor(source) {
+            output.push_str(&content[copied..range.start]);
+            let _ = write!(output, "<a id=\"{anchor}\"></a>{name}");
+            copied = range.start + source.len();
+        }
+    }
+    output.push_str(&content[copied..]);
+    output
+}
+
+/// Renders a markdown definition, such as one from a term file, to HTML.
+pub fn render_definition(markdown: &str) -> String {
+    render_events(&Parser::new(markdown).collect::<Vec<_>>()).unwrap_or_default()
+}
+
+/// Converts a markdown definition, such as one from a term file, to plain text.
+///
+/// Formatting, link targets, and HTML tags are dropped, leaving the text a
+/// reader sees; `None` if nothing is left.
+pub fn plain_definition(markdown: &str) -> Option<String> {
+    non_empty(&plain_text(&Parser::new(markdown).collect::<Vec<_>>()))
+}
+
+/// Returns the text of a definition's events, without formatting.
+///
+/// Paragraphs are separated by a blank line and list items by a line break,
+/// `<br>` becomes a space; image descriptions are kept, other markup and
+/// footnote references are not.
+fn plain_text(events: &[Event]) -> String {
+    let mut text = String::new();
+    for event in events {
+        match event {
+            Event::Text(t) | Event::Code(t) | Event::InlineMath(t) | Event::DisplayMath(t) => {
+                text.push_str(t);
+            }
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::InlineHtml(html) if html.to_ascii_lowercase().starts_with("<br") => {
+                text.push(' ');
+            }
+            // Paragraphs stay apart, for tooltips showing only the first
+            Event::End(TagEnd::Paragraph | TagEnd::CodeBlock) => text.push_str("\n\n"),
+            Event::End(TagEnd::Item) => text.push('\n'),
+            _ => {}
+        }
+    }
+
+    // Trailing spaces and runs of blank lines left by nested blocks
+    let mut cleaned = String::with_capacity(text.len());
+    for line in text.lines().map(str::trim_end) {
+        if !(line.is_empty() && (cleaned.is_empty() || cleaned.ends_with("\n\n"))) {
+            cleaned.push_str(line);
+            cleaned.push('\n');
+        }
+    }
+    cleaned
+}
+
+/// Renders the events of a definition to HTML, or `None` if it renders to nothing.
+fn render_events(events: &[Event]) -> Option<String> {
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events.iter().cloned());
+    non_empty(&rendered)
+}
+
+/// Writes the events of a definition back to markdown, or `None` if nothing is written.
+fn markdown_events(events: &[Event]) -> Option<String> {
+    let mut markdown = String::new();
+    cmark(events.iter(), &mut markdown).ok()?;
+    non_empty(&markdown)
+}
+
+/// Returns the trimmed text, or `None` if it is blank.
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
 /// Generates a URL anchor from a term name.
 ///
 /// Matches mdBook's anchor generation algorithm:
@@ -252,6 +1150,24 @@ pub fn generate_anchor(name: &str) -> String {
     result
 }
 
+/// Generates a URL anchor from a term name the way GitHub does for headings.
+///
+/// The name is lowercased, spaces become hyphens (without collapsing runs of
+/// them), and characters other than letters, digits, `-`, and `_` are dropped.
+#[must_use]
+pub fn github_anchor(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Extracts short name from terms like "API (Application Programming Interface)".
 fn extract_short_name(name: &str) -> Option<String> {
     // Look for pattern: "SHORT (Long Description)"
@@ -267,9 +1183,98 @@ fn extract_short_name(name: &str) -> Option<String> {
 }
 
 /// Converts a markdown path to its HTML equivalent.
+///
+/// A `README.md` is rendered as the `index.html` of its directory.
 #[must_use]
 pub fn get_glossary_html_path(md_path: &Path) -> PathBuf {
-    md_path.with_extension("html")
+    paths::index_page(md_path).with_extension("html")
+}
+
+/// Where links to the glossary point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlossaryTarget {
+    /// An HTML page of this book, relative to the book root.
+    Local(PathBuf),
+    /// A URL outside the book (the glossary was redirected off-site).
+    External(String),
+}
+
+/// Resolves the link target of every glossary page defining one of `terms`.
+///
+/// A glossary split across chapters has one target per page, resolved once
+/// however many terms it defines. Chapters are keyed by their path as it
+/// appears in the book.
+#[must_use]
+pub fn resolve_glossary_targets(
+    terms: &[Term],
+    redirects: &BTreeMap<String, String>,
+) -> BTreeMap<PathBuf, GlossaryTarget> {
+    let pages: BTreeSet<&Path> = terms.iter().filter_map(Term::page).collect();
+    pages
+        .into_iter()
+        .map(|page| (page.to_path_buf(), resolve_glossary_target(page, redirects)))
+        .collect()
+}
+
+/// Resolves the link target of every glossary page defining one of `terms` to its markdown source.
+///
+/// Renderers other than HTML resolve links to chapter sources themselves.
+#[must_use]
+pub fn source_glossary_targets(terms: &[Term]) -> BTreeMap<PathBuf, GlossaryTarget> {
+    terms
+        .iter()
+        .filter_map(Term::page)
+        .map(|page| {
+            (
+                page.to_path_buf(),
+                GlossaryTarget::Local(page.to_path_buf()),
+            )
+        })
+        .collect()
+}
+
+/// Maximum number of redirect hops to follow (guards against redirect cycles).
+const MAX_REDIRECTS: usize = 8;
+
+/// Resolves the link target for the glossary, following `output.html.redirect`.
+///
+/// mdBook serves a redirected page as a stub that bounces the reader on and
+/// drops the `#anchor` on the way, so links go straight to the final location.
+#[must_use]
+pub fn resolve_glossary_target(
+    md_path: &Path,
+    redirects: &BTreeMap<String, String>,
+) -> GlossaryTarget {
+    let mut current = get_glossary_html_path(md_path);
+
+    for _ in 0..MAX_REDIRECTS {
+        let key = format!("/{}", paths::to_url_path(&current));
+        let Some(destination) = redirects.get(&key) else {
+            return GlossaryTarget::Local(current);
+        };
+
+        // Any fragment on the redirect is replaced by the term anchor
+        let destination = destination.split('#').next().unwrap_or_default();
+        log::info!("Glossary page {key} is redirected to {destination}; linking there directly");
+
+        if destination.contains("://") {
+            return GlossaryTarget::External(destination.to_string());
+        }
+        // Absolute destinations are relative to the book root, others to the redirected page
+        current = destination.strip_prefix('/').map_or_else(
+            || {
+                let from_dir = current.parent().unwrap_or_else(|| Path::new(""));
+                paths::normalize(&from_dir.join(destination))
+            },
+            PathBuf::from,
+        );
+    }
+
+    log::warn!(
+        "Stopped following redirects for the glossary after {MAX_REDIRECTS} hops at {}",
+        current.display()
+    );
+    GlossaryTarget::Local(current)
 }
 
 #[cfg(test)]
@@ -295,6 +1300,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_github_anchor() {
+        assert_eq!(
+            github_anchor("API (Application Programming Interface)"),
+            "api-application-programming-interface"
+        );
+        assert_eq!(github_anchor("C++"), "c");
+        assert_eq!(github_anchor("dots.and.stuff"), "dotsandstuff");
+        assert_eq!(github_anchor("under_score"), "under_score");
+        assert_eq!(github_anchor("  Spaced  Text  "), "spaced--text");
+        assert_eq!(github_anchor("Café"), "café");
+    }
+
     #[test]
     fn test_generate_anchor_special_chars() {
         assert_eq!(generate_anchor("  Spaced  Text  "), "spaced-text");
@@ -334,6 +1352,13 @@ mod tests {
         assert_eq!(term.definition(), None);
     }
 
+    #[test]
+    fn test_term_long_name() {
+        let term = Term::new("API (Application Programming Interface)");
+        assert_eq!(term.long_name(), Some("Application Programming Interface"));
+        assert_eq!(Term::new("REST").long_name(), None);
+    }
+
     #[test]
     fn test_term_with_definition() {
         let term =
@@ -411,12 +1436,229 @@ XPT
             terms[1].definition(),
             Some("Representational State Transfer.")
         );
+        assert_eq!(terms[1].position(), Some(Position { line: 7, column: 1 }));
 
         assert_eq!(terms[2].name(), "XPT");
         assert_eq!(terms[2].anchor(), "xpt");
         assert_eq!(terms[2].definition(), Some("SAS Transport file format."));
     }
 
+    #[test]
+    fn test_parse_definition_lists_definition_html() {
+        let content = "XPT\n: The *SAS* `xport` format.\n: Source: SAS\n";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms[0].definition(), Some("The SAS xport format."));
+        assert_eq!(
+            terms[0].definition_html(),
+            Some("The <em>SAS</em> <code>xport</code> format.")
+        );
+        assert_eq!(
+            terms[0].definition_markdown(),
+            Some("The *SAS* `xport` format.")
+        );
+    }
+
+    #[test]
+    fn test_plain_definition() {
+        assert_eq!(
+            plain_definition(
+                "Reads [*XPT*](https://sas.com) files<br>with `read_xpt()`.\n\n- fast\n- small"
+            ),
+            Some("Reads XPT files with read_xpt().\n\nfast\nsmall".into())
+        );
+        assert_eq!(
+            plain_definition("Tom &amp; Jerry"),
+            Some("Tom & Jerry".into())
+        );
+        assert_eq!(plain_definition("<!-- todo -->"), None);
+    }
+
+    #[test]
+    fn test_parse_definition_lists_paragraphs() {
+        let content = "API\n: A set of\n  protocols.\n\n  Also a contract.\n";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(
+            terms[0].definition(),
+            Some("A set of protocols.\n\nAlso a contract.")
+        );
+    }
+
+    #[test]
+    fn test_parse_definition_lists_citation() {
+        let content = r"
+ISO Week
+: A week starting on Monday.
+: Source: ISO 8601-1:2019, 3.1.2.23
+
+SDTM
+: source: CDISC SDTM v2.0
+: Study Data Tabulation Model.
+
+XPT
+: SAS Transport file format.
+";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms.len(), 3);
+        assert_eq!(terms[0].definition(), Some("A week starting on Monday."));
+        assert_eq!(terms[0].citation(), Some("ISO 8601-1:2019, 3.1.2.23"));
+        assert_eq!(terms[1].definition(), Some("Study Data Tabulation Model."));
+        assert_eq!(terms[1].citation(), Some("CDISC SDTM v2.0"));
+        assert_eq!(terms[2].citation(), None);
+    }
+
+    #[test]
+    fn test_parse_definition_lists_see_also() {
+        let content = r"
+REST
+: An architectural style.
+: See also: API, HTTP.
+
+API
+: See also: REST
+: A set of protocols.
+";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms[0].definition(), Some("An architectural style."));
+        assert_eq!(terms[0].see_also(), ["API", "HTTP"]);
+        assert_eq!(terms[1].definition(), Some("A set of protocols."));
+        assert_eq!(terms[1].see_also(), ["REST"]);
+    }
+
+    #[test]
+    fn test_parse_definition_lists_custom_anchor() {
+        let content = r"
+REST {#rest-arch}
+: An architectural style.
+
+API (Application Programming Interface)
+: A set of protocols.
+";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms[0].name(), "REST");
+        assert_eq!(terms[0].anchor(), "rest-arch");
+        assert!(terms[0].has_custom_anchor());
+        assert_eq!(terms[1].anchor(), "api-application-programming-interface");
+        assert!(!terms[1].has_custom_anchor());
+    }
+
+    #[test]
+    fn test_split_anchor() {
+        assert_eq!(
+            split_anchor("REST {#rest-arch}"),
+            ("REST", Some("rest-arch"))
+        );
+        assert_eq!(split_anchor("REST{#rest}  "), ("REST", Some("rest")));
+        assert_eq!(split_anchor("REST"), ("REST", None));
+        assert_eq!(split_anchor("Set {a, b}"), ("Set {a, b}", None));
+        assert_eq!(split_anchor("Bad {#two words}"), ("Bad {#two words}", None));
+        assert_eq!(split_anchor("{#only}"), ("{#only}", None));
+    }
+
+    #[test]
+    fn test_add_anchor_targets() {
+        let content = "# Glossary\n\nREST {#rest-arch}\n: A style.\n\nAPI\n: An interface.\n";
+        assert_eq!(
+            add_anchor_targets(content),
+            "# Glossary\n\n<a id=\"rest-arch\"></a>REST\n: A style.\n\nAPI\n: An interface.\n"
+        );
+        assert_eq!(
+            add_anchor_targets("REST {#rest} is a style.\n"),
+            "REST {#rest} is a style.\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_definition_lists_also_known_as() {
+        let content = r"
+REST
+: Also known as: RESTful, REST API.
+: An architectural style.
+";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms[0].definition(), Some("An architectural style."));
+        assert_eq!(terms[0].aliases(), ["RESTful", "REST API"]);
+        assert_eq!(terms[0].also_known_as(), ["RESTful", "REST API"]);
+        assert!(terms[0].searchable_forms().contains(&"REST API"));
+    }
+
+    #[test]
+    fn test_parse_definition_lists_category() {
+        let content = r"# Glossary
+
+General
+: Not in a section.
+
+## Clinical terms
+
+SDTM
+: Study Data Tabulation Model.
+
+### `File` formats
+
+XPT
+: SAS Transport file format.
+";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms.len(), 3);
+        assert_eq!(terms[0].category(), None);
+        assert_eq!(terms[1].category(), Some("Clinical terms"));
+        assert_eq!(terms[2].category(), Some("File formats"));
+    }
+
+    #[test]
+    fn test_parse_definition_lists_deprecated() {
+        let content = r"
+XML-RPC
+: Remote procedure calls over XML.
+: Deprecated: Use REST instead.
+
+SOAP
+: Deprecated
+
+REST
+: Representational State Transfer.
+: Deprecation is discussed elsewhere.
+";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms.len(), 3);
+        assert_eq!(terms[0].deprecation(), Some("Use REST instead."));
+        assert_eq!(
+            terms[0].definition(),
+            Some("Remote procedure calls over XML.")
+        );
+        assert_eq!(terms[1].deprecation(), Some(""));
+        assert!(!terms[2].is_deprecated());
+    }
+
+    #[test]
+    fn test_parse_definition_lists_category_annotation() {
+        let content = r"## Networking
+
+HTTP
+: Category: Protocol
+: Hypertext Transfer Protocol.
+: Source: RFC 9110
+
+TCP
+: Transmission Control Protocol.
+";
+        let terms = parse_definition_lists(content);
+
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].category(), Some("Protocol"));
+        assert_eq!(terms[0].definition(), Some("Hypertext Transfer Protocol."));
+        assert_eq!(terms[0].citation(), Some("RFC 9110"));
+        assert_eq!(terms[1].category(), Some("Networking"));
+    }
+
     #[test]
     fn test_parse_definition_lists_empty() {
         let content = "# Just a heading\n\nSome paragraph text.";
@@ -424,6 +1666,214 @@ XPT
         assert!(terms.is_empty());
     }
 
+    #[test]
+    fn test_check_conflicts() {
+        let api = Term::new("API (Application Programming Interface)").with_page("glossary.md");
+        assert!(check_conflicts(&[api.clone(), Term::new("REST")], false).is_ok());
+
+        let err = check_conflicts(&[api.clone(), Term::new("api")], false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Glossary terms 'API (Application Programming Interface)' (glossary.md) and 'api' \
+             both match 'api'"
+        );
+        assert!(check_conflicts(&[api, Term::new("api")], true).is_ok());
+
+        let err = check_conflicts(
+            &[
+                Term::new("C++").with_page("glossary.md"),
+                Term::new("C")
+                    .with_page("glossary.md")
+                    .with_position(Position {
+                        line: 12,
+                        column: 1,
+                    }),
+            ],
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("both have the anchor #c"));
+        assert!(err.to_string().contains("'C' (glossary.md:12:1)"));
+
+        // The same anchor on different pages of a split glossary is fine
+        assert!(
+            check_conflicts(
+                &[
+                    Term::new("C++").with_page("glossary/a-m.md"),
+                    Term::new("C").with_page("glossary/n-z.md"),
+                ],
+                false,
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_resolve_glossary_target_without_redirect() {
+        assert_eq!(
+            resolve_glossary_target(Path::new("reference/glossary.md"), &BTreeMap::new()),
+            GlossaryTarget::Local(PathBuf::from("reference/glossary.html"))
+        );
+    }
+
+    #[test]
+    fn test_local_definitions() {
+        let terms = [
+            Term::new("API (Application Programming Interface)"),
+            Term::new("Node"),
+            Term::new("REST"),
+        ];
+        let content = "# Graphs\n\nnode\n: A vertex of the graph.\n\nAPI\n: The graph API.\n\nThe REST API.\n";
+
+        assert_eq!(
+            local_definitions(content, &terms, false),
+            ["API (Application Programming Interface)", "Node"]
+        );
+        assert_eq!(
+            local_definitions(content, &terms, true),
+            ["API (Application Programming Interface)"]
+        );
+        assert!(local_definitions("The API and REST.\n", &terms, false).is_empty());
+    }
+
+    #[test]
+    fn test_restyle_anchors() {
+        let style = |style: &str| {
+            let book_toml: toml::Table =
+                format!("[preprocessor.termlink]\nanchor-style = \"{style}\"\n")
+                    .parse()
+                    .unwrap();
+            Config::from_book_toml(&book_toml).unwrap()
+        };
+        let terms = || {
+            vec![
+                Term::new("Send/Receive"),
+                Term::new("REST").with_anchor("rest-arch"),
+                Term::new("RFC").with_url(Some("https://www.rfc-editor.org/".into())),
+            ]
+        };
+        let anchors = |terms: Vec<Term>| {
+            terms
+                .iter()
+                .map(|t| t.anchor().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mdbook = restyle_anchors(terms(), &style("mdbook")).unwrap();
+        assert_eq!(anchors(mdbook), ["send-receive", "rest-arch", "rfc"]);
+        let github = restyle_anchors(terms(), &style("github")).unwrap();
+        assert_eq!(anchors(github), ["sendreceive", "rest-arch", "rfc"]);
+
+        let err = restyle_anchors(terms(), &style("custom")).unwrap_err();
+        assert!(err.to_string().contains("'Send/Receive' has no anchor"));
+        let declared = vec![terms().remove(1), terms().remove(2)];
+        assert!(restyle_anchors(declared, &style("custom")).is_ok());
+    }
+
+    #[test]
+    fn test_term_sets() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.scoped-glossaries]\napi = \"api/glossary.md\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let terms = [
+            Term::new("Node").with_page("reference/glossary.md"),
+            Term::new("REST").with_page("reference/glossary.md"),
+            Term::new("node").with_page("api/glossary.md"),
+            Term::new("Endpoint").with_page("api/glossary.md"),
+        ];
+
+        let sets = term_sets(&terms, &config);
+        let names = |terms: &[Term]| {
+            terms
+                .iter()
+                .map(|t| t.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sets[0].0, None);
+        assert_eq!(names(&sets[0].1), ["Node", "REST"]);
+        // The scoped "node" shadows the global "Node" below api/
+        assert_eq!(sets[1].0.as_deref(), Some(Path::new("api")));
+        assert_eq!(names(&sets[1].1), ["REST", "node", "Endpoint"]);
+    }
+
+    #[test]
+    fn test_resolve_glossary_target_readme() {
+        for page in ["reference/README.md", "reference/index.md"] {
+            assert_eq!(
+                resolve_glossary_target(Path::new(page), &BTreeMap::new()),
+                GlossaryTarget::Local(PathBuf::from("reference/index.html"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_glossary_target_relative_redirect() {
+        let redirects = BTreeMap::from([(
+            "/reference/glossary.html".to_string(),
+            "../appendix/terms.html".to_string(),
+        )]);
+        assert_eq!(
+            resolve_glossary_target(Path::new("reference/glossary.md"), &redirects),
+            GlossaryTarget::Local(PathBuf::from("appendix/terms.html"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_glossary_target_redirect_chain() {
+        let redirects = BTreeMap::from([
+            ("/glossary.html".to_string(), "/old/terms.html".to_string()),
+            (
+                "/old/terms.html".to_string(),
+                "/new/terms.html#top".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            resolve_glossary_target(Path::new("glossary.md"), &redirects),
+            GlossaryTarget::Local(PathBuf::from("new/terms.html"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_glossary_target_external_redirect() {
+        let redirects = BTreeMap::from([(
+            "/glossary.html".to_string(),
+            "https://example.com/glossary.html".to_string(),
+        )]);
+        assert_eq!(
+            resolve_glossary_target(Path::new("glossary.md"), &redirects),
+            GlossaryTarget::External("https://example.com/glossary.html".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_glossary_target_redirect_cycle() {
+        let redirects = BTreeMap::from([
+            ("/a.html".to_string(), "b.html".to_string()),
+            ("/b.html".to_string(), "a.html".to_string()),
+        ]);
+        assert!(matches!(
+            resolve_glossary_target(Path::new("a.md"), &redirects),
+            GlossaryTarget::Local(_)
+        ));
+    }
+
+    #[test]
+    fn test_resolve_glossary_targets_per_page() {
+        let terms = vec![
+            Term::new("API").with_page("glossary/a-f.md"),
+            Term::new("CSV").with_page("glossary/a-f.md"),
+            Term::new("REST").with_page("glossary/p-z.md"),
+        ];
+        let targets = resolve_glossary_targets(&terms, &BTreeMap::new());
+        assert_eq!(targets.len(), 2);
+        assert_eq!(
+            targets[Path::new("glossary/p-z.md")],
+            GlossaryTarget::Local(PathBuf::from("glossary/p-z.html"))
+        );
+    }
+
     #[test]
     fn test_get_glossary_html_path() {
         assert_eq!(
@@ -0,0 +1,247 @@
+//! A–Z indexes of glossary pages.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+
+use crate::glossary::{Term, generate_anchor};
+
+/// Adds a line of letter links below the title of a glossary page.
+///
+/// Each letter links to the alphabetically first term starting with it and
+/// shows how many terms do: `[A](#api) (3) · [B](#bom) (1)`.
+pub fn add_navigation(content: &str, terms: &[&Term]) -> String {
+    let groups = by_letter(terms);
+    if groups.is_empty() {
+        return content.to_string();
+    }
+    let navigation = letter_links(&groups, |_, first| first.anchor().to_string());
+
+    let title_end = Parser::new(content)
+        .into_offset_iter()
+        .find(|(event, _)| matches!(event, Event::End(TagEnd::Heading(_))))
+        .map_or(0, |(_, range)| {
+            range.start + content[range].trim_end().len()
+        });
+    if title_end == 0 {
+        return format!("{navigation}\n\n{content}");
+    }
+    format!(
+        "{}\n\n{navigation}\n{}",
+        &content[..title_end],
+        &content[title_end..]
+    )
+}
+
+/// Rewrites a glossary page with its terms sorted under letter headings.
+///
+/// The page keeps its title (a level 1 heading at the top), followed by a
+/// line of letter links and a definition list per letter:
+///
+/// ```markdown
+/// # Glossary
+///
+/// [A](#termlink-index-a) (1) · [R](#termlink-index-r) (1)
+///
+/// ## A {#termlink-index-a}
+///
+/// API
+/// : A set of protocols.
+/// ```
+pub fn generate(content: &str, terms: &[&Term]) -> String {
+    let groups = by_letter(terms);
+
+    let mut page = String::new();
+    if let Some(title) = page_title(content) {
+        page.push_str(title);
+        page.push_str("\n\n");
+    }
+    if groups.is_empty() {
+        return page;
+    }
+
+    page.push_str(&letter_links(&groups, |letter, _| letter_id(letter)));
+    page.push('\n');
+    for (letter, terms) in &groups {
+        // A lone `#` would read as the closing sequence of the heading
+        let text = if letter == "#" { "\\#" } else { letter };
+        let _ = write!(page, "\n## {text} {{#{}}}\n", letter_id(letter));
+        for term in terms {
+            page.push('\n');
+            page.push_str(&entry(term));
+        }
+    }
+    page
+}
+
+/// Writes the definition list entry of one term.
+fn entry(term: &Term) -> String {
+    let mut entry = if term.has_custom_anchor() {
+        format!("{} {{#{}}}\n", term.name(), term.anchor())
+    } else {
+        format!("{}\n", term.name())
+    };
+    let mut definitions: Vec<String> = term
+        .definition_markdown()
+        .or_else(|| term.definition())
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    if let Some(citation) = term.citation() {
+        definitions.push(format!("Source: {citation}"));
+    }
+    if !term.see_also().is_empty() {
+        definitions.push(format!("See also: {}", term.see_also().join(", ")));
+    }
+    if !term.also_known_as().is_empty() {
+        definitions.push(format!(
+            "Also known as: {}",
+            term.also_known_as().join(", ")
+        ));
+    }
+    if let Some(note) = term.deprecation() {
+        definitions.push(if note.is_empty() {
+            String::from("Deprecated")
+        } else {
+            format!("Deprecated: {note}")
+        });
+    }
+    if definitions.is_empty() {
+        definitions.push(String::from("—"));
+    }
+
+    for definition in definitions {
+        // Continuation lines are indented to stay inside the definition
+        let mut lines = definition.lines();
+        let _ = writeln!(entry, ": {}", lines.next().unwrap_or_default());
+        for line in lines {
+            if line.is_empty() {
+                entry.push('\n');
+            } else {
+                let _ = writeln!(entry, "  {line}");
+            }
+        }
+    }
+    entry
+}
+
+/// Returns the level 1 heading a page starts with, as written.
+fn page_title(content: &str) -> Option<&str> {
+    let mut events = Parser::new(content).into_offset_iter();
+    match events.next()? {
+        (
+            Event::Start(Tag::Heading {
+                level: HeadingLevel::H1,
+                ..
+            }),
+            range,
+        ) => Some(content[range].trim_end()),
+        _ => None,
+    }
+}
+
+/// Groups terms by the letter they are listed under, each group sorted by name.
+///
+/// Terms starting with a digit or symbol are listed under `#`, which comes first.
+fn by_letter<'a>(terms: &[&'a Term]) -> BTreeMap<String, Vec<&'a Term>> {
+    let mut groups: BTreeMap<String, Vec<&Term>> = BTreeMap::new();
+    for term in terms {
+        groups
+            .entry(index_letter(term.name()))
+            .or_default()
+            .push(term);
+    }
+    for terms in groups.values_mut() {
+        terms.sort_by_cached_key(|term| term.name().to_lowercase());
+    }
+    groups
+}
+
+/// Returns the letter a term is listed under: its first letter, uppercased.
+fn index_letter(name: &str) -> String {
+    match name.chars().find(|c| c.is_alphanumeric()) {
+        Some(c) if c.is_alphabetic() => c.to_uppercase().collect(),
+        _ => String::from("#"),
+    }
+}
+
+/// Returns the id of the heading of a letter on a generated page.
+fn letter_id(letter: &str) -> String {
+    let slug = generate_anchor(letter);
+    format!(
+        "termlink-index-{}",
+        if slug.is_empty() { "other" } else { &slug }
+    )
+}
+
+/// Joins the letter links, each with the number of terms listed under it.
+fn letter_links(
+    groups: &BTreeMap<String, Vec<&Term>>,
+    target: impl Fn(&str, &Term) -> String,
+) -> String {
+    groups
+        .iter()
+        .map(|(letter, terms)| {
+            format!(
+                "[{letter}](#{}) ({})",
+                target(letter, terms[0]),
+                terms.len()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms() -> Vec<Term> {
+        vec![
+            Term::with_definition("REST", Some("A style.".into())),
+            Term::with_definition("api", Some("An interface.".into()))
+                .with_definition_markdown(Some("An *interface*.\n\nSee REST.".into()))
+                .with_citation(Some("ISO".into()))
+                .with_see_also(vec!["REST".into(), "JSON".into()])
+                .with_also_known_as(vec!["APIs".into()]),
+            Term::new("3D"),
+            Term::new("Array"),
+        ]
+    }
+
+    #[test]
+    fn test_add_navigation() {
+        let terms = terms();
+        let terms: Vec<&Term> = terms.iter().collect();
+
+        assert_eq!(
+            add_navigation("# Glossary\nREST\n: A style.\n", &terms),
+            "# Glossary\n\n[#](#3d) (1) · [A](#api) (2) · [R](#rest) (1)\n\nREST\n: A style.\n"
+        );
+        assert!(add_navigation("REST\n: A style.\n", &terms).starts_with("[#](#3d) (1)"));
+    }
+
+    #[test]
+    fn test_generate() {
+        let terms = terms();
+        let terms: Vec<&Term> = terms.iter().collect();
+
+        let page = generate("# Glossary\n\nREST\n: A style.\n", &terms);
+        assert_eq!(
+            page,
+            "# Glossary\n\n\
+             [#](#termlink-index-other) (1) · [A](#termlink-index-a) (2) · [R](#termlink-index-r) (1)\n\
+             \n## \\# {#termlink-index-other}\n\n3D\n: —\n\
+             \n## A {#termlink-index-a}\n\napi\n: An *interface*.\n\n  See REST.\n: Source: ISO\n: See also: REST, JSON\n: Also known as: APIs\n\nArray\n: —\n\
+             \n## R {#termlink-index-r}\n\nREST\n: A style.\n"
+        );
+    }
+
+    #[test]
+    fn test_index_letter() {
+        assert_eq!(index_letter("éclair"), "É");
+        assert_eq!(index_letter("\"Quoted\""), "Q");
+        assert_eq!(index_letter("2FA"), "#");
+    }
+}
@@ -34,24 +34,102 @@
 //! REST
 //! : Representational State Transfer.
 //! ```
+//!
+//! ## Library Use
+//!
+//! The matching engine can be embedded in other preprocessors and static-site
+//! tools. Build the [`Term`]s, a [`TermMatcher`] over them, and pass each
+//! page through [`add_term_links`]:
+//!
+//! ```
+//! use std::collections::HashSet;
+//!
+//! use mdbook_termlink::{Config, GlossaryHrefs, Term, TermMatcher, add_term_links};
+//!
+//! let terms = vec![
+//!     Term::with_definition(
+//!         "API (Application Programming Interface)",
+//!         Some("A set of protocols.".into()),
+//!     )
+//!     .with_aliases(vec!["apis".into()]),
+//! ];
+//! let config = Config::default();
+//! let matcher = TermMatcher::new(&terms, &config)?;
+//! let hrefs = GlossaryHrefs::single("glossary.html");
+//!
+//! let linked = add_term_links("Call the API.", &matcher, &hrefs, &config, &HashSet::new())?;
+//! assert!(linked.content.contains(r#"href="glossary.html#api-application-programming-interface""#));
+//! assert_eq!(linked.linked, ["API (Application Programming Interface)"]);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! [`Config::builder`] sets the options of `book.toml` in code, and
+//! [`Config::from_book_toml`] reads them from a parsed `book.toml`.
 
+mod acronyms;
+mod backlinks;
+mod cache;
+pub mod commands;
 pub mod config;
+mod folding;
+mod front_matter;
 mod glossary;
+mod glossary_index;
 mod linker;
+mod location;
+mod match_filter;
+mod paths;
+mod progress;
+mod raw_html;
+mod report;
+mod source;
+mod term_file;
 
 pub use config::Config;
 pub use glossary::Term;
+pub use linker::{
+    GlossaryHrefs, LinkedChapter, TermLink, TermMatcher, add_term_links, link_chapter,
+};
+pub use location::Position;
+pub use match_filter::{MatchContext, MatchFilter};
+pub use source::BookSource;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
-use mdbook_preprocessor::book::{Book, BookItem};
+use mdbook_preprocessor::book::{Book, BookItem, Chapter};
 use mdbook_preprocessor::{Preprocessor, PreprocessorContext};
 
+use crate::backlinks::ChapterRef;
+use crate::cache::ChapterCache;
+use crate::commands::FileChange;
+use crate::config::{GlossaryIndex, Output, Tooltip};
+use crate::glossary::GlossaryTarget;
+use crate::match_filter::SharedFilter;
+use crate::progress::Progress;
+use crate::report::UsageReport;
+
+/// Renderers the preprocessor runs for; all but HTML get markdown links.
+const SUPPORTED_RENDERERS: &[&str] = &["html", "epub", "markdown", "pandoc"];
+
+/// Returns true if the preprocessor runs for `renderer`.
+#[must_use]
+pub fn supports_renderer(renderer: &str) -> bool {
+    SUPPORTED_RENDERERS.contains(&renderer)
+}
+
 /// mdBook preprocessor that auto-links glossary terms throughout documentation.
 #[derive(Debug)]
 pub struct TermlinkPreprocessor {
     config: Config,
+    /// Decides which occurrences are linked, beyond the configuration.
+    filter: Option<SharedFilter>,
+    /// File to log the links to, in place of `replacement-log`.
+    replacement_log: Option<PathBuf>,
+    /// Whether to print the changes as a diff, whatever `dry-run` is set to.
+    dry_run: bool,
 }
 
 impl TermlinkPreprocessor {
@@ -61,8 +139,357 @@ impl TermlinkPreprocessor {
     ///
     /// Returns an error if the configuration in `book.toml` is invalid.
     pub fn new(ctx: &PreprocessorContext) -> Result<Self> {
-        let config = Config::from_context(ctx)?;
-        Ok(Self { config })
+        Config::from_context(ctx).map(Self::from_config)
+    }
+
+    /// Creates a preprocessor running with `config`, for the subcommands
+    /// that link chapters outside of mdBook.
+    pub(crate) const fn from_config(config: Config) -> Self {
+        Self {
+            config,
+            filter: None,
+            replacement_log: None,
+            dry_run: false,
+        }
+    }
+
+    /// Prints the changes to each chapter as a diff and leaves the book
+    /// unchanged, as with `dry-run` in `book.toml`.
+    #[must_use]
+    pub const fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Logs every link added to `path`, relative to the book root, in place
+    /// of the `replacement-log` set in `book.toml`.
+    #[must_use]
+    pub fn with_replacement_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replacement_log = Some(path.into());
+        self
+    }
+
+    /// Links only the occurrences `filter` allows, for preprocessors that
+    /// wrap termlink with rules of their own.
+    #[must_use]
+    pub fn with_match_filter(mut self, filter: impl MatchFilter + 'static) -> Self {
+        self.filter = Some(SharedFilter::new(filter));
+        self
+    }
+
+    /// Returns the configuration this preprocessor runs with.
+    #[must_use]
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Resolves the link target of every glossary page: its HTML page, or
+    /// its markdown source for renderers other than HTML.
+    fn glossary_targets(&self, terms: &[Term]) -> BTreeMap<PathBuf, GlossaryTarget> {
+        if self.config.link_sources() {
+            glossary::source_glossary_targets(terms)
+        } else {
+            glossary::resolve_glossary_targets(terms, self.config.redirects())
+        }
+    }
+
+    /// Links one chapter, applying the settings of its front matter and `page-exclude-terms`.
+    ///
+    /// The front matter is stripped from the chapter even when its settings
+    /// disable linking. Terms the chapter defines in a definition list of its
+    /// own are not linked to the glossary there.
+    fn link_page(
+        &self,
+        chapter_path: &Path,
+        content: &str,
+        matcher: &linker::TermMatcher,
+        hrefs: &linker::GlossaryHrefs,
+        linked_before: &HashSet<String>,
+    ) -> Result<linker::LinkedChapter> {
+        let (page, body) = front_matter::split(content)?;
+        // Links and errors are located at their offsets in the chapter as written
+        let front_matter_len = content.len() - body.len();
+        let content = body;
+        if page.disable {
+            log::debug!(
+                "Linking disabled by front matter: {}",
+                chapter_path.display()
+            );
+            return Ok(linker::LinkedChapter {
+                content: content.to_string(),
+                ..linker::LinkedChapter::default()
+            });
+        }
+        let exclude_terms: Vec<String> = self
+            .config
+            .page_exclude_terms(chapter_path)
+            .iter()
+            .chain(&page.exclude_terms)
+            .cloned()
+            .chain(glossary::local_definitions(
+                content,
+                matcher.glossary(),
+                self.config.case_sensitive(),
+            ))
+            .collect();
+        let linked = if exclude_terms.is_empty() {
+            linker::link_chapter(content, matcher, hrefs, &self.config, linked_before)
+        } else {
+            let config = self.config.ignoring(&exclude_terms);
+            let matcher = linker::TermMatcher::new(matcher.glossary(), &config)?
+                .with_shared_filter(matcher.filter().cloned());
+            linker::link_chapter(content, &matcher, hrefs, &config, linked_before)
+        };
+        let mut linked = linked.map_err(|mut e| {
+            if let Some(source) = e.downcast_mut::<location::SourceError>() {
+                source.offset += front_matter_len;
+            }
+            e
+        })?;
+        for link in &mut linked.links {
+            link.offset += front_matter_len;
+        }
+        Ok(linked)
+    }
+
+    /// Loads the chapter cache, if `cache` is set.
+    fn chapter_cache(&self, root: &Path, terms: &[Term]) -> Option<ChapterCache> {
+        // A filter's rules can't be fingerprinted, so its results aren't cached
+        self.config
+            .cache()
+            .filter(|_| self.filter.is_none())
+            .map(|path| ChapterCache::load(root.join(path), terms, &self.config))
+    }
+
+    /// Checks whether `chapter_path` is linked: not a glossary page, nor excluded by `include-pages` or `exclude-pages`.
+    fn links_chapter(&self, chapter_path: &Path) -> bool {
+        if self.config.is_glossary_path(chapter_path) {
+            log::debug!("Skipping glossary file: {}", chapter_path.display());
+            return false;
+        }
+        if self.config.should_exclude(chapter_path) {
+            log::debug!("Skipping excluded page: {}", chapter_path.display());
+            return false;
+        }
+        true
+    }
+
+    /// Builds the matcher of each term set, by the directory it is scoped to.
+    fn term_matchers<'t>(
+        &self,
+        term_sets: &'t [(Option<PathBuf>, Vec<Term>)],
+    ) -> Result<Vec<(Option<&'t Path>, linker::TermMatcher<'t>)>> {
+        term_sets
+            .iter()
+            .map(|(dir, terms)| {
+                linker::TermMatcher::new(terms, &self.config).map(|matcher| {
+                    let matcher = matcher.with_shared_filter(self.filter.clone());
+                    (dir.as_deref(), matcher)
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to build the term matcher")
+    }
+
+    /// Returns the matcher for the terms linked in `chapter_path`, if it is linked at all.
+    ///
+    /// Chapters below a scoped glossary's directory link its terms too.
+    fn chapter_matcher<'m, 't>(
+        &self,
+        chapter_path: &Path,
+        matchers: &'m [(Option<&Path>, linker::TermMatcher<'t>)],
+    ) -> Option<&'m linker::TermMatcher<'t>> {
+        if !self.links_chapter(chapter_path) {
+            return None;
+        }
+        let scope = self.config.chapter_scope(chapter_path);
+        matchers
+            .iter()
+            .find(|(dir, _)| *dir == scope)
+            .map(|(_, matcher)| matcher)
+    }
+
+    /// Counts the chapters of `book` that [`Self::links_chapter`] lets through.
+    fn linked_chapters(&self, book: &Book) -> usize {
+        book.iter()
+            .filter(|item| match item {
+                BookItem::Chapter(chapter) => chapter.path.as_deref().is_some_and(|path| {
+                    !self.config.is_glossary_path(path) && !self.config.should_exclude(path)
+                }),
+                _ => false,
+            })
+            .count()
+    }
+
+    /// Reports a chapter that could not be linked, at the place it failed if
+    /// known: collected in `failed` with `strict`, logged otherwise.
+    fn chapter_failed(
+        &self,
+        error: &anyhow::Error,
+        chapter_path: &Path,
+        content: &str,
+        failed: &mut Vec<String>,
+    ) {
+        let location = location::error_location(error, chapter_path, content);
+        if self.config.strict() {
+            failed.push(format!("{location}: {error:#}"));
+        } else {
+            log::error!("Failed to process chapter {location}, leaving it unlinked: {error:#}");
+        }
+    }
+
+    /// Writes the links added to each chapter as JSON lines, if a replacement log is set.
+    fn write_replacement_log(
+        &self,
+        ctx: &PreprocessorContext,
+        chapter_links: &[(PathBuf, Vec<linker::TermLink>)],
+    ) -> Result<()> {
+        let Some(path) = self
+            .replacement_log
+            .as_deref()
+            .or_else(|| self.config.replacement_log())
+        else {
+            return Ok(());
+        };
+        let mut log = String::new();
+        for (chapter_path, links) in chapter_links {
+            report::log_links(&mut log, chapter_path, links)?;
+        }
+        write_file(&ctx.root.join(path), &log)
+    }
+
+    /// Warns about deprecated and unused terms, and writes the usage report if configured.
+    fn report_usage(
+        &self,
+        ctx: &PreprocessorContext,
+        terms: &[Term],
+        term_uses: &HashMap<String, Vec<ChapterRef>>,
+    ) -> Result<()> {
+        warn_deprecated_uses(terms, term_uses);
+
+        let report = UsageReport::new(terms, term_uses);
+        report.log_summary();
+        check_unused_terms(&self.config, terms, &report)?;
+        if let Some(path) = self.config.report() {
+            write_file(
+                &ctx.root.join(path),
+                &serde_json::to_string_pretty(&report)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Links the terms in the definitions of a glossary page if `link-glossary` is set.
+    ///
+    /// The page is left unchanged if linking fails. Links between entries are
+    /// not counted as term uses.
+    fn link_definitions(
+        &self,
+        chapter_path: &Path,
+        content: &mut String,
+        matchers: &[(Option<&Path>, linker::TermMatcher)],
+        glossary_targets: &BTreeMap<PathBuf, GlossaryTarget>,
+    ) {
+        if !self.config.link_glossary() {
+            return;
+        }
+        let scope = self.config.chapter_scope(chapter_path);
+        let Some((_, matcher)) = matchers.iter().find(|(dir, _)| *dir == scope) else {
+            return;
+        };
+
+        let config = self.config.for_glossary_page();
+        let hrefs = linker::GlossaryHrefs::for_chapter(chapter_path, glossary_targets, &config);
+        match linker::link_chapter(content, matcher, &hrefs, &config, &HashSet::new()) {
+            Ok(linked) => *content = linked.content,
+            Err(e) => log::error!(
+                "Failed to link the definitions in {}, leaving them unchanged: {e:#}",
+                location::error_location(&e, chapter_path, content)
+            ),
+        }
+    }
+
+    /// Appends the acronym list chapter to `book` if `generate-acronym-list` is set.
+    ///
+    /// Nothing is added when no term has a short form, or a chapter already
+    /// has the list's path.
+    fn add_acronym_list(
+        &self,
+        book: &mut Book,
+        terms: &[Term],
+        glossary_targets: &BTreeMap<PathBuf, GlossaryTarget>,
+    ) {
+        if !self.config.generate_acronym_list() {
+            return;
+        }
+        let path = Path::new(acronyms::ACRONYM_LIST_PATH);
+        let taken = book.iter().any(|item| {
+            matches!(item, BookItem::Chapter(chapter) if chapter.path.as_deref() == Some(path))
+        });
+        if taken {
+            log::warn!(
+                "Not generating the acronym list: the book already has a chapter {}",
+                path.display()
+            );
+            return;
+        }
+
+        let hrefs = linker::GlossaryHrefs::for_chapter(path, glossary_targets, &self.config);
+        let title = self.config.acronym_list_title();
+        if let Some(content) = acronyms::acronym_list(terms, &hrefs, title) {
+            book.push_item(Chapter::new(title, content, path, Vec::new()));
+        }
+    }
+
+    /// Adds the A–Z index, the links between definitions, and back-links to `chapter` if it is a glossary page.
+    fn finish_glossary_page(
+        &self,
+        chapter: &mut Chapter,
+        terms: &[Term],
+        term_uses: &HashMap<String, Vec<ChapterRef>>,
+        matchers: &[(Option<&Path>, linker::TermMatcher)],
+        glossary_targets: &BTreeMap<PathBuf, GlossaryTarget>,
+    ) {
+        let Some(chapter_path) = chapter.path.as_ref() else {
+            return;
+        };
+        if !self.config.is_glossary_path(chapter_path) {
+            return;
+        }
+
+        // A term file's page is the configured path, which may be the `README.md` of this `index.md`
+        let page = paths::index_page(&paths::normalize(chapter_path));
+        let page_terms: Vec<&Term> = terms
+            .iter()
+            .filter(|term| {
+                term.page()
+                    .is_some_and(|p| paths::index_page(&paths::normalize(p)) == page)
+            })
+            .collect();
+        match self.config.glossary_index() {
+            GlossaryIndex::Off => {}
+            GlossaryIndex::Navigation => {
+                chapter.content = glossary_index::add_navigation(&chapter.content, &page_terms);
+            }
+            GlossaryIndex::Generate => {
+                chapter.content = glossary_index::generate(&chapter.content, &page_terms);
+            }
+        }
+        chapter.content = glossary::add_anchor_targets(&chapter.content);
+        self.link_definitions(
+            chapter_path,
+            &mut chapter.content,
+            matchers,
+            glossary_targets,
+        );
+        if self.config.backlinks() {
+            chapter.content = backlinks::add_backlinks(
+                &chapter.content,
+                chapter_path,
+                term_uses,
+                self.config.backlinks_title(),
+            );
+        }
     }
 }
 
@@ -71,89 +498,261 @@ impl Preprocessor for TermlinkPreprocessor {
         "termlink"
     }
 
-    fn run(&self, _ctx: &PreprocessorContext, mut book: Book) -> Result<Book> {
-        // 1. Extract terms from glossary
-        let terms = glossary::extract_terms(&book, &self.config)
-            .context("Failed to extract glossary terms")?;
+    fn run(&self, ctx: &PreprocessorContext, book: Book) -> Result<Book> {
+        if !(self.dry_run || self.config.dry_run()) {
+            return self.link_book(ctx, book);
+        }
+
+        // The diff goes to stderr, as stdout carries the book back to mdBook
+        let linked = self.link_book(ctx, book.clone())?;
+        let changes = chapter_changes(&ctx.config.book.src, &book, &linked);
+        for change in &changes {
+            eprint!("{}", change.unified_diff());
+        }
+        log::info!(
+            "Dry run: {} chapters would change; the book is left unchanged",
+            changes.len()
+        );
+        Ok(book)
+    }
+}
+
+impl TermlinkPreprocessor {
+    /// Links the terms throughout `book`, and finishes its glossary pages.
+    fn link_book(&self, ctx: &PreprocessorContext, mut book: Book) -> Result<Book> {
+        // 1. Extract terms from glossary and apply configured aliases
+        let terms = glossary::resolve_terms(
+            &book,
+            &ctx.root,
+            &ctx.root.join(&ctx.config.book.src),
+            &self.config,
+        )
+        .context("Failed to extract glossary terms")?;
 
         if terms.is_empty() {
             log::warn!(
                 "No glossary terms found in {}",
                 self.config.glossary_path().display()
             );
+            for_each_chapter_in_order(&mut book.items, &mut |chapter| {
+                strip_front_matter(&mut chapter.content);
+            });
             return Ok(book);
         }
 
         log::info!("Found {} glossary terms", terms.len());
 
-        // 2. Validate alias conflicts (before applying aliases)
-        let term_names: HashSet<String> = terms.iter().map(|t| t.name().to_lowercase()).collect();
+        warn_missing_popover_script(&self.config);
+
+        // 2. Resolve the glossary page(s) to link to
+        let glossary_targets = self.glossary_targets(&terms);
 
-        for (term_name, aliases) in self.config.all_aliases() {
-            for alias in aliases {
-                let alias_lower = alias.to_lowercase();
-                // Check if alias conflicts with a different term's name
-                if term_names.contains(&alias_lower) && alias_lower != term_name.to_lowercase() {
-                    bail!("Alias '{alias}' for term '{term_name}' conflicts with existing term");
+        // 3. Process each chapter in book order, noting the chapters linking to each term
+        let term_sets = glossary::term_sets(&terms, &self.config);
+        let matchers = self.term_matchers(&term_sets)?;
+        let mut term_uses: HashMap<String, Vec<ChapterRef>> = HashMap::new();
+        let mut linked_in_book: HashSet<String> = HashSet::new();
+        let mut failed: Vec<String> = Vec::new();
+        let mut chapter_links: Vec<(PathBuf, Vec<linker::TermLink>)> = Vec::new();
+        let mut cache = self.chapter_cache(&ctx.root, &terms);
+        let chapters = self.linked_chapters(&book);
+        let mut progress = Progress::new(chapters);
+        for_each_chapter_in_order(&mut book.items, &mut |chapter| {
+            // Skip draft chapters, the glossary pages, and excluded pages
+            let matcher = chapter
+                .path
+                .as_deref()
+                .and_then(|p| self.chapter_matcher(p, &matchers));
+            let (Some(chapter_path), Some(matcher)) = (chapter.path.as_ref(), matcher) else {
+                strip_front_matter(&mut chapter.content);
+                return;
+            };
+
+            // Calculate link paths from chapter to the glossary pages
+            let hrefs =
+                linker::GlossaryHrefs::for_chapter(chapter_path, &glossary_targets, &self.config);
+
+            // Add term links and strip the front matter, keeping the original chapter if anything goes wrong
+            let link = || {
+                self.link_page(
+                    chapter_path,
+                    &chapter.content,
+                    matcher,
+                    &hrefs,
+                    &linked_in_book,
+                )
+            };
+            let result = match cache.as_mut() {
+                Some(cache) => cache.link(chapter_path, &chapter.content, &linked_in_book, link),
+                None => link(),
+            };
+            let links = result.as_ref().map_or(0, |linked| linked.links.len());
+            match result {
+                Ok(linked) => {
+                    linked_in_book.extend(linked.linked_anchors);
+                    chapter_links.push((chapter_path.clone(), linked.links));
+                    for name in linked.linked {
+                        let links = linked.link_counts.get(&name).copied().unwrap_or_default();
+                        term_uses.entry(name).or_default().push(ChapterRef {
+                            path: chapter_path.clone(),
+                            name: chapter.name.clone(),
+                            links,
+                        });
+                    }
+                    if linked.content == chapter.content {
+                        log::debug!("No terms linked in {}", chapter_path.display());
+                    } else {
+                        chapter.content = linked.content;
+                    }
+                }
+                Err(e) => {
+                    self.chapter_failed(&e, chapter_path, &chapter.content, &mut failed);
+                    strip_front_matter(&mut chapter.content);
                 }
             }
+            progress.chapter_done(chapter_path, links);
+        });
+        let links = progress.links();
+        progress.finish(&format!(
+            "Inserted {links} links into {chapters} chapter(s)"
+        ));
+
+        if let Some(cache) = cache {
+            cache.save()?;
         }
+        if !failed.is_empty() {
+            bail!("Failed to process chapters:\n{}", failed.join("\n"));
+        }
+
+        self.report_usage(ctx, &terms, &term_uses)?;
+        self.write_replacement_log(ctx, &chapter_links)?;
+
+        // 4. Add the A–Z index, the links between definitions, and the chapters linking to each term
+        for_each_chapter_in_order(&mut book.items, &mut |chapter| {
+            self.finish_glossary_page(chapter, &terms, &term_uses, &matchers, &glossary_targets);
+        });
+
+        // 5. Append the list of acronyms
+        self.add_acronym_list(&mut book, &terms, &glossary_targets);
+
+        Ok(book)
+    }
+}
 
-        // 3. Apply aliases from config to terms
-        let terms: Vec<Term> = terms
-            .into_iter()
-            .map(|term| {
-                if let Some(aliases) = self.config.aliases(term.name()) {
-                    term.with_aliases(aliases.clone())
-                } else {
-                    term
+/// Collects the changes from `before` to `after` of each chapter, with its path below `src`.
+fn chapter_changes(src: &Path, before: &Book, after: &Book) -> Vec<FileChange> {
+    let chapters = |book: &Book| -> Vec<(PathBuf, String)> {
+        book.iter()
+            .filter_map(|item| match item {
+                BookItem::Chapter(chapter) => {
+                    Some((chapter.path.clone()?, chapter.content.clone()))
                 }
+                _ => None,
             })
-            .collect();
+            .collect()
+    };
+    let original: HashMap<PathBuf, String> = chapters(before).into_iter().collect();
+    chapters(after)
+        .into_iter()
+        .filter_map(|(path, content)| {
+            // Chapters added by the preprocessor, like the acronym list, were empty before
+            let before = original.get(&path).cloned().unwrap_or_default();
+            (before != content).then(|| FileChange::new(src.join(path), before, content))
+        })
+        .collect()
+}
 
-        // 4. Calculate glossary HTML path for linking
-        let glossary_html_path = glossary::get_glossary_html_path(self.config.glossary_path());
+/// Removes the termlink front matter from a chapter left unlinked.
+fn strip_front_matter(content: &mut String) {
+    let start = content.len() - front_matter::strip(content).len();
+    content.drain(..start);
+}
 
-        // 5. Process each chapter
-        book.for_each_mut(|item| {
-            if let BookItem::Chapter(chapter) = item {
-                // Skip draft chapters and the glossary itself
-                let Some(chapter_path) = chapter.path.as_ref() else {
-                    return;
-                };
+/// Warns if popovers are used but the popover script is not among the book's additional scripts.
+fn warn_missing_popover_script(config: &Config) {
+    if config.tooltip() == Tooltip::Popover
+        && config.output() != Output::Markdown
+        && !config.termlink_js()
+    {
+        log::warn!(
+            "tooltip = \"popover\" needs termlink.js in output.html.additional-js; \
+             run `mdbook-termlink install` to add it"
+        );
+    }
+}
 
-                if self.config.is_glossary_path(chapter_path) {
-                    log::debug!("Skipping glossary file: {}", chapter_path.display());
-                    return;
-                }
+/// Writes `contents` to `path`, creating its directory.
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
 
-                // Check exclude-pages
-                if self.config.should_exclude(chapter_path) {
-                    log::debug!("Skipping excluded page: {}", chapter_path.display());
-                    return;
-                }
+/// Warns about every deprecated term still linked from chapters, listing them.
+fn warn_deprecated_uses(terms: &[Term], term_uses: &HashMap<String, Vec<ChapterRef>>) {
+    for term in terms.iter().filter(|t| t.is_deprecated()) {
+        let Some(chapters) = term_uses.get(term.name()) else {
+            continue;
+        };
+        let pages: Vec<String> = chapters
+            .iter()
+            .map(|chapter| paths::to_url_path(&chapter.path))
+            .collect();
+        let note = term
+            .deprecation()
+            .filter(|note| !note.is_empty())
+            .map(|note| format!(" ({note})"))
+            .unwrap_or_default();
+        log::warn!(
+            "Deprecated term '{}'{note} is still used on: {}",
+            term.name(),
+            pages.join(", ")
+        );
+    }
+}
 
-                // Calculate relative path from chapter to glossary
-                let relative_glossary =
-                    linker::calculate_relative_path(chapter_path, &glossary_html_path);
+/// Warns about glossary terms no chapter links to, or fails with `strict`.
+///
+/// Terms in `ignore-terms` are never linked on purpose and not reported.
+fn check_unused_terms(config: &Config, terms: &[Term], report: &UsageReport) -> Result<()> {
+    if !config.warn_unused_terms() {
+        return Ok(());
+    }
 
-                // Add term links
-                match linker::add_term_links(
-                    &chapter.content,
-                    &terms,
-                    &relative_glossary,
-                    &self.config,
-                ) {
-                    Ok(new_content) => {
-                        chapter.content = new_content;
-                    }
-                    Err(e) => {
-                        log::error!("Failed to process chapter {}: {e}", chapter_path.display());
-                    }
-                }
-            }
-        });
+    let unused: Vec<&str> = terms
+        .iter()
+        .filter(|term| {
+            report.unused().contains(&term.name())
+                && !config.is_ignored(term.name(), term.short_name())
+        })
+        .map(Term::name)
+        .collect();
+    if unused.is_empty() {
+        return Ok(());
+    }
+    if config.strict() {
+        bail!(
+            "Glossary terms not linked from any chapter: {}",
+            unused.join(", ")
+        );
+    }
+    for name in unused {
+        log::warn!("Glossary term '{name}' is not linked from any chapter");
+    }
+    Ok(())
+}
 
-        Ok(book)
+/// Calls `func` on every chapter in SUMMARY order, each chapter before its sub-chapters.
+///
+/// `Book::for_each_mut` visits sub-chapters first, which would let a
+/// sub-chapter take the first link of a term its parent page uses.
+fn for_each_chapter_in_order(items: &mut [BookItem], func: &mut impl FnMut(&mut Chapter)) {
+    for item in items {
+        if let BookItem::Chapter(chapter) = item {
+            func(chapter);
+            for_each_chapter_in_order(&mut chapter.sub_items, func);
+        }
     }
 }
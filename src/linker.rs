@@ -1,418 +1,3939 @@
 //! Term replacement logic with context tracking.
 
-use std::collections::HashSet;
-use std::path::Path;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::hash::BuildHasher;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
-use anyhow::Result;
-use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, TagEnd};
+use aho_corasick::{AhoCorasick, MatchKind};
+use anyhow::{Context as _, Result, anyhow};
+use pulldown_cmark::{CowStr, Event, HeadingLevel, LinkType, Options, Parser, Tag, TagEnd};
 use pulldown_cmark_to_cmark::cmark;
 use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::config::Config;
-use crate::glossary::Term;
+use crate::config::{
+    Config, LinkScope, Output, PageTerms, SkipContext, TermsSummary, Tooltip, TooltipSource,
+};
+use crate::glossary::{GlossaryTarget, Term, generate_anchor};
+use crate::location::SourceError;
+use crate::match_filter::{MatchContext, MatchFilter, SharedFilter};
+use crate::raw_html::HtmlScanner;
+use crate::{folding, paths};
 
-/// Adds glossary term links to chapter content.
+/// A chapter after linking.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkedChapter {
+    /// The chapter markdown with term links added.
+    pub content: String,
+    /// Names of the terms linked in the chapter, in glossary order.
+    pub linked: Vec<String>,
+    /// Number of links added per term name.
+    pub link_counts: HashMap<String, usize>,
+    /// Anchors of the terms linked in the chapter.
+    pub linked_anchors: HashSet<String>,
+    /// Every link added, in the order of the chapter.
+    pub links: Vec<TermLink>,
+}
+
+/// A link added to a chapter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TermLink {
+    /// Name of the term linked.
+    pub term: String,
+    /// The occurrence, as written.
+    pub matched_text: String,
+    /// Byte offset of the occurrence in the chapter source.
+    pub offset: usize,
+    /// Target of the link; `None` for a term defined inline without a URL.
+    pub href: Option<String>,
+}
+
+/// Adds glossary term links to a chapter, refusing any result that loses prose.
+///
+/// Links are spliced into the chapter source; a panic on unusual markdown,
+/// or a splice that drops text, must never cost the reader content. Either
+/// is reported as an error so the caller can keep the original chapter.
 ///
 /// # Errors
 ///
-/// Returns an error if markdown reconstruction fails.
-pub fn add_term_links(
+/// Returns an error if linking fails, panics, or would drop any prose.
+pub fn link_chapter<S: BuildHasher>(
     content: &str,
-    terms: &[Term],
-    glossary_relative_path: &str,
+    matcher: &TermMatcher,
+    hrefs: &GlossaryHrefs,
     config: &Config,
-) -> Result<String> {
-    // Build term matchers sorted by length (longest first to avoid partial matches)
-    let mut sorted_terms: Vec<&Term> = terms.iter().collect();
-    sorted_terms.sort_by_key(|t| std::cmp::Reverse(t.name().len()));
+    linked_before: &HashSet<String, S>,
+) -> Result<LinkedChapter> {
+    let output = panic::catch_unwind(AssertUnwindSafe(|| {
+        add_term_links(content, matcher, hrefs, config, linked_before)
+    }))
+    .map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        anyhow!("linking panicked: {message}")
+    })??;
 
-    // Track which terms have been linked (for link-first-only mode)
-    let mut linked_terms: HashSet<String> = HashSet::new();
+    // Nothing linked: the chapter is returned exactly as written
+    if output.content == content {
+        return Ok(output);
+    }
+    if let Some(offset) = dropped_prose(content, &output.content) {
+        return Err(
+            SourceError::new(offset, "linking would have dropped text from the chapter").into(),
+        );
+    }
+    Ok(output)
+}
+
+/// Checks that all text of `original` survives, in order, in `output`.
+///
+/// Links only add markup (and the optional screen-reader text), so the
+/// original text must be a subsequence of the output text. Whitespace is
+/// ignored since the markdown writer may reflow it, and backslashes since
+/// escaped terms (`\API`) lose theirs. Returns the byte offset of the first
+/// character missing from `output`, if any.
+fn dropped_prose(original: &str, output: &str) -> Option<usize> {
+    let output = prose_chars(output);
+    let mut output = output.iter();
+    prose_chars(original)
+        .into_iter()
+        .find(|(_, c)| !output.any(|(_, o)| o == c))
+        .map(|(offset, _)| offset)
+}
 
-    // Parse content into events
+/// Returns the non-whitespace characters of the text and code in `content`,
+/// with their byte offsets (that of their whole text for entities and escapes).
+fn prose_chars(content: &str) -> Vec<(usize, char)> {
+    Parser::new_ext(content, parser_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Text(text) | Event::Code(text) => Some((text, range)),
+            _ => None,
+        })
+        .flat_map(|(text, range)| {
+            let verbatim = content[range.clone()] == *text;
+            text.char_indices()
+                .filter(|(_, c)| !c.is_whitespace() && *c != '\\')
+                .map(|(i, c)| {
+                    (
+                        if verbatim {
+                            range.start + i
+                        } else {
+                            range.start
+                        },
+                        c,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Returns the markdown extensions termlink parses chapters with.
+pub fn parser_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_DEFINITION_LIST);
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_FOOTNOTES);
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TASKLISTS);
+    options
+}
+
+/// Adds glossary term links to chapter content.
+///
+/// Links replace the matched bytes of the source; everything else, including
+/// the formatting of tables, line wrapping, and escapes, is kept byte for byte.
+///
+/// `matcher` finds the glossary terms, built once for the whole book.
+/// `linked_before` holds the anchors of the terms linked on earlier pages;
+/// with `link-first-only = "per-book"` they are not linked again.
+///
+/// # Errors
+///
+/// Returns an error if a markdown link cannot be written.
+pub fn add_term_links<S: BuildHasher>(
+    content: &str,
+    matcher: &TermMatcher,
+    hrefs: &GlossaryHrefs,
+    config: &Config,
+    linked_before: &HashSet<String, S>,
+) -> Result<LinkedChapter> {
+    let terms = matcher.glossary();
+
+    // Track which terms have been linked (for link-first-only mode)
+    let mut linked_terms: HashSet<String> = HashSet::new();
+
+    // Parse content into events, keeping their source ranges
+    let (events, ranges): (Vec<Event>, Vec<Range<usize>>) =
+        Parser::new_ext(content, parser_options())
+            .into_offset_iter()
+            .unzip();
 
-    let parser = Parser::new_ext(content, options);
-    let events: Vec<Event> = parser.collect();
+    // Pick the key terms before links replace the text they are counted in
+    let key_terms = key_terms_block(&events, matcher, hrefs, config);
 
     // Process events, tracking context
-    let processed_events = process_events(
-        events,
-        &sorted_terms,
-        glossary_relative_path,
+    let mut links = PageLinks::new(config.max_links_per_page());
+    let mut edits = process_events(
+        content,
+        &events,
+        &ranges,
+        matcher,
+        hrefs,
         config,
+        linked_before,
         &mut linked_terms,
-    );
+        &mut links,
+    )?;
+
+    // Place the key-terms callout below the first heading (or at the top)
+    if let Some(callout) = key_terms {
+        // After the line the heading ends on, with a blank line so the HTML block stands alone
+        let (position, separator) = events
+            .iter()
+            .position(|e| matches!(e, Event::End(TagEnd::Heading(_))))
+            .map_or((0, ""), |index| {
+                let end = ranges[index].end;
+                if content[..end].ends_with('\n') {
+                    (end, "\n")
+                } else {
+                    content[end..]
+                        .find('\n')
+                        .map_or((content.len(), "\n\n"), |i| (end + i + 1, "\n"))
+                }
+            });
+        edits.push((position..position, format!("{separator}{callout}\n")));
+    }
 
-    // Convert back to markdown
-    let mut output = String::new();
-    cmark(processed_events.into_iter(), &mut output)?;
+    let mut output = if edits.is_empty() {
+        content.to_string()
+    } else {
+        splice(content, edits)
+    };
 
-    Ok(output)
+    // Summarize and export the linked terms in glossary order
+    let linked: Vec<&Term> = terms
+        .iter()
+        .filter(|t| linked_terms.contains(t.anchor()))
+        .collect();
+    if let Some(summary) = terms_summary_block(&linked, hrefs, config) {
+        match config.terms_summary() {
+            TermsSummary::Prepend => output.insert_str(0, &format!("{summary}\n\n")),
+            TermsSummary::Append => {
+                output.push_str("\n\n");
+                output.push_str(&summary);
+                output.push('\n');
+            }
+            TermsSummary::Off => {}
+        }
+    }
+    if let Some(block) = page_terms_block(&linked, hrefs, config.page_terms()) {
+        output.push_str("\n\n");
+        output.push_str(&block);
+        output.push('\n');
+    }
+    if let Some(key) = config.glossary_shortcut().filter(|_| config.termlink_js()) {
+        output.push_str("\n\n");
+        output.push_str(&shortcut_target(key, hrefs.first_page()));
+        output.push('\n');
+    }
+
+    Ok(LinkedChapter {
+        content: output,
+        linked: linked.iter().map(|term| term.name().to_string()).collect(),
+        link_counts: terms
+            .iter()
+            .filter_map(|term| {
+                let count = links.counts.get(term.anchor())?;
+                Some((term.name().to_string(), *count))
+            })
+            .collect(),
+        linked_anchors: linked_terms,
+        links: links.added,
+    })
 }
 
-/// Context tracking for what kind of element we're inside.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Context {
-    Normal,
-    CodeBlock,
-    Link,
-    Heading,
-    Image,
+/// Builds the hidden element telling `termlink.js` which key opens the
+/// glossary, and where the glossary is from this chapter.
+fn shortcut_target(key: char, glossary_href: &str) -> String {
+    format!(
+        r#"<span hidden data-termlink-glossary="{}" data-termlink-shortcut="{}"></span>"#,
+        html_escape(glossary_href),
+        html_escape(&key.to_string())
+    )
 }
 
-/// Processes parser events and adds term links where appropriate.
-fn process_events<'a>(
-    events: Vec<Event<'a>>,
-    terms: &[&Term],
-    glossary_path: &str,
+/// Builds the "Key terms" callout from the most frequent terms on a page, if enabled.
+fn key_terms_block(
+    events: &[Event],
+    matcher: &TermMatcher,
+    hrefs: &GlossaryHrefs,
     config: &Config,
-    linked_terms: &mut HashSet<String>,
-) -> Vec<Event<'a>> {
-    let mut result = Vec::with_capacity(events.len());
-    let mut context_stack: Vec<Context> = vec![Context::Normal];
+) -> Option<String> {
+    if config.key_terms() == 0 {
+        return None;
+    }
+
+    let counts = count_occurrences(events, matcher, config);
+    let mut counted: Vec<(&Term, usize)> = matcher
+        .glossary()
+        .iter()
+        .filter_map(|term| Some((term, *counts.get(term.name())?)))
+        .collect();
+    if counted.is_empty() {
+        return None;
+    }
+    // Stable sort keeps glossary order among equally frequent terms
+    counted.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let links: Vec<String> = counted
+        .iter()
+        .take(config.key_terms())
+        .map(|(term, _)| {
+            list_link(
+                term,
+                term.short_name().unwrap_or_else(|| term.name()),
+                hrefs,
+                config,
+            )
+        })
+        .collect();
+    if config.output() == Output::Markdown {
+        return Some(format!(
+            "**{}:** {}\n",
+            config.key_terms_title(),
+            links.join(", ")
+        ));
+    }
+    Some(format!(
+        "<div class=\"termlink-key-terms\"><strong>{}:</strong> {}</div>\n",
+        html_escape(config.key_terms_title()),
+        links.join(", ")
+    ))
+}
 
+/// Counts the occurrences of each term in prose that would be eligible for linking.
+///
+/// Overlapping occurrences count once, for the term that would be linked.
+fn count_occurrences<'a>(
+    events: &[Event],
+    matcher: &TermMatcher<'a>,
+    config: &Config,
+) -> HashMap<&'a str, usize> {
+    let mut skip_depth = 0usize;
+    let mut linking_on = true;
+    let mut raw_html = HtmlScanner::default();
+    let mut counts = HashMap::new();
     for event in events {
-        match &event {
+        match event {
+            Event::Html(html) | Event::InlineHtml(html) => {
+                linking_on = region_switch(html).unwrap_or(linking_on);
+                raw_html.scan(html);
+            }
+            Event::Start(tag) if is_skipped(tag.to_end(), config) => skip_depth += 1,
+            Event::End(end) if is_skipped(*end, config) => {
+                skip_depth = skip_depth.saturating_sub(1);
+            }
+            Event::Text(text)
+                if skip_depth == 0 && linking_on && !raw_html.in_skipped_element() =>
+            {
+                let protected = protected_spans(text, config);
+                let mut taken: Vec<Range<usize>> = Vec::new();
+                for TermMatch { term, range } in matcher.find_all(text) {
+                    if overlaps_any(&range, &taken) || overlaps_any(&range, &protected) {
+                        continue;
+                    }
+                    taken.push(range.clone());
+                    if !is_escaped(text, range.start) {
+                        *counts.entry(term.name()).or_default() += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    counts
+}
+
+/// Builds the "Terms used on this page" paragraph, if enabled.
+fn terms_summary_block(linked: &[&Term], hrefs: &GlossaryHrefs, config: &Config) -> Option<String> {
+    if linked.is_empty() || config.terms_summary() == TermsSummary::Off {
+        return None;
+    }
+
+    let links: Vec<String> = linked
+        .iter()
+        .map(|term| list_link(term, term.name(), hrefs, config))
+        .collect();
+    if config.output() == Output::Markdown {
+        return Some(format!(
+            "**{}:** {}",
+            config.terms_summary_title(),
+            links.join(", ")
+        ));
+    }
+    Some(format!(
+        r#"<p class="termlink-terms-used"><strong>{}:</strong> {}</p>"#,
+        html_escape(config.terms_summary_title()),
+        links.join(", ")
+    ))
+}
+
+/// Builds the link to `term` in a list of terms, such as the terms summary.
+///
+/// A term without a target is listed as text with its class, or as plain
+/// text with `output = "markdown"`.
+fn list_link(term: &Term, text: &str, hrefs: &GlossaryHrefs, config: &Config) -> String {
+    if config.output() == Output::Markdown {
+        let link = markdown_link(term, text, hrefs.term_href(term), config);
+        return inline_markdown(&link).unwrap_or_else(|_| text.to_string());
+    }
+    if !term.has_target() {
+        return format!(
+            r#"<span class="{}">{}</span>"#,
+            link_class(term, config),
+            html_escape(text)
+        );
+    }
+    format!(
+        r#"<a href="{}"{} class="{}">{}</a>"#,
+        html_escape(&hrefs.term_href(term)),
+        external_attrs(term),
+        link_class(term, config),
+        html_escape(text),
+    )
+}
+
+/// Builds the block listing the terms linked on a page, if exporting is enabled.
+fn page_terms_block(linked: &[&Term], hrefs: &GlossaryHrefs, mode: PageTerms) -> Option<String> {
+    if linked.is_empty() {
+        return None;
+    }
+
+    match mode {
+        PageTerms::Off => None,
+        // Anchors never contain `--`, so they are safe inside a comment
+        PageTerms::Comment => {
+            let anchors: Vec<&str> = linked.iter().map(|t| t.anchor()).collect();
+            Some(format!("<!-- termlink-terms: {} -->", anchors.join(", ")))
+        }
+        PageTerms::Json => {
+            let entries: Vec<serde_json::Value> = linked
+                .iter()
+                .map(|t| {
+                    serde_json::json!({
+                        "name": t.name(),
+                        "anchor": t.anchor(),
+                        "href": t.has_target().then(|| hrefs.term_href(t)),
+                    })
+                })
+                .collect();
+            // Escape `</` so a term name cannot close the script element
+            let json = serde_json::Value::Array(entries)
+                .to_string()
+                .replace("</", "<\\/");
+            Some(format!(
+                r#"<script type="application/json" class="termlink-page-terms">{json}</script>"#
+            ))
+        }
+    }
+}
+
+/// Checks whether text inside the element ending with `end` is never linked.
+///
+/// Code blocks, links, and images are always skipped; other elements when
+/// listed in `skip-contexts`.
+pub fn is_skipped(end: TagEnd, config: &Config) -> bool {
+    let context = match end {
+        TagEnd::CodeBlock | TagEnd::Link | TagEnd::Image => return true,
+        TagEnd::Heading(_) => SkipContext::Heading,
+        TagEnd::BlockQuote(_) => SkipContext::Blockquote,
+        TagEnd::Table => SkipContext::Table,
+        TagEnd::TableHead => SkipContext::TableHeader,
+        TagEnd::Item => SkipContext::List,
+        TagEnd::FootnoteDefinition => SkipContext::Footnote,
+        _ => return false,
+    };
+    config.skips(context)
+}
+
+/// Processes parser events and finds where to add term links.
+///
+/// `ranges` holds the source range of each event in `content`. Returns the
+/// edits to the source: the range of each text with term occurrences, and
+/// what it is replaced with. Every term linked on the page is added to
+/// `linked_terms`.
+///
+/// Consecutive text events and the soft breaks between them are matched as
+/// one text, so a term wrapped onto the next line, or split by the parser
+/// around characters like `[`, is still found.
+#[allow(clippy::too_many_arguments)] // the per-page linking state is threaded through
+fn process_events<S: BuildHasher>(
+    content: &str,
+    events: &[Event],
+    ranges: &[Range<usize>],
+    matcher: &TermMatcher,
+    hrefs: &GlossaryHrefs,
+    config: &Config,
+    linked_before: &HashSet<String, S>,
+    linked_terms: &mut HashSet<String>,
+    links: &mut PageLinks,
+) -> Result<Vec<(Range<usize>, String)>> {
+    let mut edits = Vec::new();
+    // Depth of the skipped elements the current event is inside
+    let mut skip_depth = 0usize;
+    let marker_blocks = marker_block_ranges(events, config.protected_markers());
+    // Switched by `<!-- termlink:off -->` / `<!-- termlink:on -->`, regardless of nesting
+    let mut linking_on = true;
+    let mut raw_html = HtmlScanner::default();
+    // Terms linked in the current section (the whole page unless `link-first-only = "per-section"`),
+    // plus those linked on earlier pages with `"per-book"`
+    let mut section_linked: HashSet<String> = if config.link_scope() == LinkScope::PerBook {
+        linked_before.iter().cloned().collect()
+    } else {
+        HashSet::new()
+    };
+
+    // Where the current section ends, and on a glossary page the entry being read
+    let mut sections = Sections::new(config);
+
+    // Source range of the consecutive linkable texts seen last
+    let mut run: Option<Range<usize>> = None;
+    let mut run_start: usize = 0;
+
+    for (index, event) in events.iter().enumerate() {
+        let in_prose = skip_depth == 0
+            && linking_on
+            && !raw_html.in_skipped_element()
+            && !marker_blocks.iter().any(|block| block.contains(&index))
+            && sections.allows_linking();
+        let linkable = match event {
+            // Entities and backslash escapes read differently in the source; they are left alone
+            Event::Text(text) => in_prose && content[ranges[index].clone()] == **text,
+            Event::SoftBreak => run.is_some(),
+            _ => false,
+        };
+        let range = ranges[index].clone();
+        // Only whitespace may lie between the parts, not a `>` of a block quote
+        if let Some(run) = run.as_mut()
+            && linkable
+            && content[run.end..range.start].trim().is_empty()
+        {
+            run.end = range.end;
+            continue;
+        }
+        if let Some(run) = run.take()
+            && !splits_delimiter_run(events, ranges, run_start, index)
+        {
+            edits.extend(link_run(
+                content,
+                run,
+                emphasis_span(events, ranges, run_start, index),
+                matcher,
+                hrefs,
+                config,
+                &mut section_linked,
+                links,
+            )?);
+        }
+        if linkable {
+            // The backslash of an escape (`\*`) is left out of the text it starts
+            let escape = usize::from(content[..range.start].ends_with('\\'));
+            run = Some(range.start - escape..range.end);
+            run_start = index;
+            continue;
+        }
+
+        sections.follow(event, matcher.glossary(), &mut section_linked, linked_terms);
+
+        match event {
             // Track context changes
-            Event::Start(Tag::CodeBlock(_)) => {
-                context_stack.push(Context::CodeBlock);
-                result.push(event);
+            Event::Start(tag) if is_skipped(tag.to_end(), config) => skip_depth += 1,
+            Event::End(end) if is_skipped(*end, config) => {
+                skip_depth = skip_depth.saturating_sub(1);
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                linking_on = region_switch(html).unwrap_or(linking_on);
+                let texts = raw_html.scan(html);
+                // Lines of blocks in list items and block quotes are passed without their indent
+                if matches!(event, Event::Html(_)) && in_prose && content[range.clone()] == **html {
+                    edits.extend(link_html(
+                        content,
+                        range,
+                        texts,
+                        matcher,
+                        hrefs,
+                        config,
+                        &mut section_linked,
+                        links,
+                    ));
+                }
+            }
+
+            // Inline code and all other events are kept as written
+            _ => {}
+        }
+    }
+    if let Some(run) = run {
+        edits.extend(link_run(
+            content,
+            run,
+            None,
+            matcher,
+            hrefs,
+            config,
+            &mut section_linked,
+            links,
+        )?);
+    }
+
+    linked_terms.extend(
+        section_linked
+            .into_iter()
+            .filter(|anchor| !linked_before.contains(anchor)),
+    );
+    Ok(edits)
+}
+
+/// Follows the sections of a page, in which each term is linked once.
+///
+/// A section is the whole page, or the part below an `h1` or `h2` with
+/// `link-first-only = "per-section"`. On a glossary page whose definitions
+/// are linked, each definition is a section, so a `See also:` line links its
+/// terms again. Only the definitions are linked, never to their own term.
+#[derive(Debug)]
+struct Sections<'a> {
+    /// Whether `h1` and `h2` headings start a section.
+    per_heading: bool,
+    /// Whether the page is a glossary page whose definitions are linked.
+    glossary: bool,
+    /// Text of the entry title being read.
+    title: Option<String>,
+    /// Anchor of the term the current entry defines.
+    defining: Option<&'a str>,
+    /// Whether the current event is inside a definition.
+    in_definition: bool,
+}
+
+impl<'a> Sections<'a> {
+    /// Creates the tracker for a page linked with `config`.
+    fn new(config: &Config) -> Self {
+        Self {
+            per_heading: config.link_scope() == LinkScope::PerSection,
+            glossary: config.definitions_only(),
+            title: None,
+            defining: None,
+            in_definition: false,
+        }
+    }
+
+    /// Returns false outside the definitions of a glossary page.
+    const fn allows_linking(&self) -> bool {
+        !self.glossary || self.in_definition
+    }
+
+    /// Updates the state for a non-linkable `event`.
+    ///
+    /// When a section ends, the terms linked in it move from `section_linked`
+    /// to `linked_terms`. The term of a glossary entry counts as linked in its definitions.
+    fn follow(
+        &mut self,
+        event: &Event,
+        terms: &'a [Term],
+        section_linked: &mut HashSet<String>,
+        linked_terms: &mut HashSet<String>,
+    ) {
+        match event {
+            Event::Start(Tag::Heading { level, .. })
+                if self.per_heading && *level <= HeadingLevel::H2 =>
+            {
+                linked_terms.extend(section_linked.drain());
+            }
+            _ if !self.glossary => {}
+            Event::Start(Tag::DefinitionListTitle) => self.title = Some(String::new()),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(title) = self.title.as_mut() {
+                    title.push_str(text);
+                }
+            }
+            Event::End(TagEnd::DefinitionListTitle) => {
+                let title = self.title.take().unwrap_or_default();
+                self.defining = terms
+                    .iter()
+                    .find(|term| term.name() == title.trim())
+                    .map(Term::anchor);
+            }
+            Event::Start(Tag::DefinitionListDefinition) => {
+                self.end_section(section_linked, linked_terms);
+                section_linked.extend(self.defining.map(String::from));
+                self.in_definition = true;
+            }
+            Event::End(TagEnd::DefinitionList) => {
+                self.end_section(section_linked, linked_terms);
+                self.defining = None;
+            }
+            Event::End(TagEnd::DefinitionListDefinition) => self.in_definition = false,
+            _ => {}
+        }
+    }
+
+    /// Moves the terms linked in the section from `section_linked` to
+    /// `linked_terms`, leaving out the term of the current glossary entry.
+    fn end_section(
+        &self,
+        section_linked: &mut HashSet<String>,
+        linked_terms: &mut HashSet<String>,
+    ) {
+        linked_terms.extend(
+            section_linked
+                .drain()
+                .filter(|anchor| Some(anchor.as_str()) != self.defining),
+        );
+    }
+}
+
+/// Links the terms in the text at `run` in `content`, returning the edit if anything was linked.
+///
+/// `span` is the source range of an emphasis the text makes up all of. If
+/// the text is linked as a whole, the link replaces the emphasis and keeps
+/// its delimiters inside, `[*API*](...)`, so they cannot end up next to
+/// the link markup where they would no longer read as emphasis.
+#[allow(clippy::too_many_arguments)] // the per-page linking state is threaded through
+fn link_run(
+    content: &str,
+    run: Range<usize>,
+    span: Option<Range<usize>>,
+    matcher: &TermMatcher,
+    hrefs: &GlossaryHrefs,
+    config: &Config,
+    linked_terms: &mut HashSet<String>,
+    links: &mut PageLinks,
+) -> Result<Option<(Range<usize>, String)>> {
+    let trimmed = trim_math(
+        content,
+        trim_directives(content, run.clone()),
+        config.math_delimiters(),
+    );
+    // The emphasis no longer holds just the text once part of it is left out
+    let span = span.filter(|_| trimmed == run);
+    let run = trimmed;
+    let text = &content[run.clone()];
+    links.offset = run.start;
+    let events = replace_terms_to_events(text, matcher, hrefs, config, linked_terms, links);
+    if let Some(span) = span {
+        let open = &content[span.start..run.start];
+        let close = &content[run.end..span.end];
+        if let Some(link) = emphasized_link(&events, text, open, close)? {
+            return Ok(Some((span, link)));
+        }
+    }
+    let replacement = inline_markdown(&events)?;
+    Ok((replacement != text).then_some((run, replacement)))
+}
+
+/// Trims `run` in `content` to leave out the parts of mdBook directives it cuts through.
+///
+/// A directive written across lines, or split by markup as in
+/// `{{#include *.rs}}`, reaches past the run of text it starts or ends in.
+/// Directives wholly inside the run are left to [`protected_spans`].
+fn trim_directives(content: &str, mut run: Range<usize>) -> Range<usize> {
+    let is_directive = |span: Range<usize>| {
+        DIRECTIVE_REGEX
+            .find(&content[span.clone()])
+            .is_some_and(|directive| directive.len() == span.len())
+    };
+    // One opened before the run
+    if let Some(open) = content[..run.start].rfind("{{")
+        && let Some(close) = content[run.start..].find("}}").map(|i| run.start + i + 2)
+        && is_directive(open..close)
+    {
+        run.start = close.min(run.end);
+    }
+    // One closed after the run
+    if let Some(open) = content[run.clone()].rfind("{{").map(|i| run.start + i)
+        && let Some(close) = content[run.end..].find("}}").map(|i| run.end + i + 2)
+        && is_directive(open..close)
+    {
+        run.end = open;
+    }
+    run
+}
+
+/// Trims `run` in `content` to leave out the parts of math it cuts through.
+///
+/// Math is found on the lines of the run, as the delimiters may lie outside
+/// it: escapes such as `\(` and markup such as `*` end a run of text. Math
+/// wholly inside the run is left to [`protected_spans`].
+fn trim_math(
+    content: &str,
+    mut run: Range<usize>,
+    delimiters: &[(String, String)],
+) -> Range<usize> {
+    if delimiters.is_empty() {
+        return run;
+    }
+    let start = content[..run.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[run.end..]
+        .find('\n')
+        .map_or(content.len(), |i| run.end + i);
+    for math in math_spans(&content[start..end], delimiters) {
+        let math = start + math.start..start + math.end;
+        // Math the run starts or ends inside of
+        if (math.start + 1..math.end).contains(&run.start) {
+            run.start = math.end.min(run.end);
+        }
+        if (math.start + 1..math.end).contains(&run.end) {
+            run.end = math.start.max(run.start);
+        }
+    }
+    run
+}
+
+/// Returns the source range of the emphasis the text run from `run_start`
+/// makes up all of, as in `*API*`, if the run ended at the emphasis' end
+/// event at `end`.
+fn emphasis_span(
+    events: &[Event],
+    ranges: &[Range<usize>],
+    run_start: usize,
+    end: usize,
+) -> Option<Range<usize>> {
+    match (&events[run_start.checked_sub(1)?], &events[end]) {
+        (
+            Event::Start(Tag::Emphasis | Tag::Strong | Tag::Strikethrough),
+            Event::End(TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough),
+        ) => Some(ranges[run_start - 1].clone()),
+        _ => None,
+    }
+}
+
+/// Checks whether the emphasis around the text run from `run_start`, up
+/// to the event at `end`, shares its delimiter run with literal delimiters
+/// or another emphasis, as in `*API**`, `***API****` or `**API***REST*`.
+///
+/// Next to a link the leftover delimiters flank markup rather than the
+/// term and may pair anew, so such runs are left unlinked.
+fn splits_delimiter_run(
+    events: &[Event],
+    ranges: &[Range<usize>],
+    run_start: usize,
+    end: usize,
+) -> bool {
+    let is_delimiter = |c: char| matches!(c, '*' | '_' | '~');
+    let is_start = |event: &Event| {
+        matches!(
+            event,
+            Event::Start(Tag::Emphasis | Tag::Strong | Tag::Strikethrough)
+        )
+    };
+    let is_end = |event: &Event| {
+        matches!(
+            event,
+            Event::End(TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough)
+        )
+    };
+    // The outermost emphasis starting right before the run, and ending right after it
+    let first = (0..run_start)
+        .rev()
+        .take_while(|&i| is_start(&events[i]))
+        .last();
+    let last = (end..events.len())
+        .take_while(|&i| is_end(&events[i]))
+        .last();
+    let opens = first
+        .and_then(|first| Some((first, first.checked_sub(1)?)))
+        .is_some_and(|(first, before)| {
+            ranges[before].end == ranges[first].start
+                && match &events[before] {
+                    Event::Text(text) => text.ends_with(is_delimiter),
+                    event => is_end(event),
+                }
+        });
+    let closes = last.is_some_and(|last| {
+        events.get(last + 1).is_some_and(|after| {
+            ranges[last + 1].start == ranges[last].end
+                && match after {
+                    Event::Text(text) => text.starts_with(is_delimiter),
+                    event => is_start(event),
+                }
+        })
+    });
+    opens || closes
+}
+
+/// Links the terms in `texts` of the raw HTML block line at `line`, returning the edits.
+///
+/// `texts` are ranges within the line, as found by [`HtmlScanner::scan`].
+/// Nothing is linked unless `link-in-html` is set, nor with `output =
+/// "markdown"`, as markdown is not read inside HTML blocks. The text around
+/// the links is kept as written rather than written back as markdown, whose
+/// escapes would show in HTML.
+#[allow(clippy::too_many_arguments)] // the per-page linking state is threaded through
+fn link_html(
+    content: &str,
+    line: Range<usize>,
+    texts: Vec<Range<usize>>,
+    matcher: &TermMatcher,
+    hrefs: &GlossaryHrefs,
+    config: &Config,
+    linked_terms: &mut HashSet<String>,
+    links: &mut PageLinks,
+) -> Vec<(Range<usize>, String)> {
+    if !config.link_in_html() || config.output() == Output::Markdown {
+        return Vec::new();
+    }
+    let mut edits = Vec::new();
+    for text in texts {
+        let text = line.start + text.start..line.start + text.end;
+        links.offset = text.start;
+        let events = replace_terms_to_events(
+            &content[text.clone()],
+            matcher,
+            hrefs,
+            config,
+            linked_terms,
+            links,
+        );
+        if events.iter().any(|event| matches!(event, Event::Html(_))) {
+            let replacement = events
+                .iter()
+                .map(|event| match event {
+                    Event::Text(part) | Event::Html(part) => part.as_ref(),
+                    _ => "",
+                })
+                .collect();
+            edits.push((text, replacement));
+        }
+    }
+    edits
+}
+
+/// Writes a link with the emphasis delimiters `open` and `close` around its text.
+///
+/// Returns `None` unless `events` are a single link to all of `text`.
+fn emphasized_link(
+    events: &[Event],
+    text: &str,
+    open: &str,
+    close: &str,
+) -> Result<Option<String>> {
+    match events {
+        [Event::Html(html)] => {
+            // Attribute values are escaped, so only the link text can sit between `>` and `</`
+            let link_text = format!(">{}</", html_escape(text));
+            let Some(start) = html.find(&link_text).map(|i| i + 1) else {
+                return Ok(None);
+            };
+            let end = start + link_text.len() - 3;
+            Ok(Some(format!(
+                "{}{open}{}{close}{}",
+                &html[..start],
+                &html[start..end],
+                &html[end..]
+            )))
+        }
+        [
+            Event::Start(Tag::Link { .. }),
+            text @ Event::Text(_),
+            Event::End(TagEnd::Link),
+        ] => {
+            let events = [
+                events[0].clone(),
+                Event::InlineHtml(CowStr::from(open.to_string())),
+                text.clone(),
+                Event::InlineHtml(CowStr::from(close.to_string())),
+                events[2].clone(),
+            ];
+            inline_markdown(&events).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Writes the events replacing a text back as markdown.
+///
+/// Text events hold source text, so they are written as is, as is the HTML
+/// of links; markdown links are written by the markdown writer.
+fn inline_markdown(events: &[Event]) -> Result<String> {
+    let mut markdown = String::new();
+    let mut rest = events;
+    while let Some((event, tail)) = rest.split_first() {
+        match event {
+            Event::Text(text) | Event::Html(text) => {
+                markdown.push_str(text);
+                rest = tail;
+            }
+            _ => {
+                // A markdown link, up to its end
+                let end = rest
+                    .iter()
+                    .position(|e| matches!(e, Event::End(TagEnd::Link)))
+                    .map_or(rest.len(), |i| i + 1);
+                cmark(rest[..end].iter(), &mut markdown)?;
+                rest = &rest[end..];
             }
-            Event::Start(Tag::Link { .. }) => {
-                context_stack.push(Context::Link);
-                result.push(event);
+        }
+    }
+    Ok(markdown)
+}
+
+/// Applies `edits` (source range, replacement) to `content`.
+///
+/// The edits may not overlap; insertions have an empty range.
+fn splice(content: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    // Insertions go before a replacement starting at the same position
+    edits.sort_by_key(|(range, _)| (range.start, range.end));
+    let mut output = String::with_capacity(content.len());
+    let mut copied = 0;
+    for (range, replacement) in edits {
+        output.push_str(&content[copied..range.start]);
+        output.push_str(&replacement);
+        copied = range.end;
+    }
+    output.push_str(&content[copied..]);
+    output
+}
+
+/// Returns whether `html` switches linking on or off, if it holds a region comment.
+///
+/// `<!-- termlink:off -->` disables linking until the next
+/// `<!-- termlink:on -->`; the last such comment in `html` wins.
+pub fn region_switch(html: &str) -> Option<bool> {
+    html.split("<!--")
+        .skip(1)
+        .filter_map(|comment| match comment.split("-->").next()?.trim() {
+            "termlink:off" => Some(false),
+            "termlink:on" => Some(true),
+            _ => None,
+        })
+        .last()
+}
+
+/// Finds the event ranges of blocks containing a protected marker comment.
+///
+/// Markers such as `<!-- toc -->` are consumed by later preprocessors, which
+/// expect them to sit in the block exactly as written; nothing in the
+/// enclosing paragraph, list item, or table cell is linked.
+pub fn marker_block_ranges(events: &[Event], markers: &[String]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    // Open containers: (start index, contains a marker)
+    let mut open: Vec<(usize, bool)> = Vec::new();
+
+    for (index, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(_) => open.push((index, false)),
+            Event::End(_) => {
+                if let Some((start, true)) = open.pop() {
+                    ranges.push(start..index + 1);
+                }
             }
-            Event::Start(Tag::Image { .. }) => {
-                context_stack.push(Context::Image);
-                result.push(event);
+            Event::Html(html) | Event::InlineHtml(html)
+                if markers.iter().any(|marker| html.contains(marker.as_str())) =>
+            {
+                if let Some((_, has_marker)) = open.last_mut() {
+                    *has_marker = true;
+                }
             }
-            Event::Start(Tag::Heading { .. }) => {
-                context_stack.push(Context::Heading);
-                result.push(event);
+            _ => {}
+        }
+    }
+
+    ranges
+}
+
+/// Replaces term occurrences, returning a sequence of separate Text and Html events.
+/// This avoids the issue of wrapping mixed content in a single Html event.
+///
+/// An occurrence escaped with a backslash (`\API`) is not linked; the
+/// backslash is dropped. No more links are added than `links` has left, and
+/// each one is counted there.
+fn replace_terms_to_events(
+    text: &str,
+    matcher: &TermMatcher,
+    hrefs: &GlossaryHrefs,
+    config: &Config,
+    linked_terms: &mut HashSet<String>,
+    links: &mut PageLinks,
+) -> Vec<Event<'static>> {
+    let occurrences = matcher.find_all(text);
+
+    // 1. Find the occurrences to replace, skipping protected spans and escaped occurrences
+    let mut protected = protected_spans(text, config);
+    // (start, end, replacement, whether it is the first link of its term)
+    let mut replacements: Vec<(usize, usize, Replacement, bool)> = Vec::new();
+
+    for TermMatch { range, .. } in &occurrences {
+        if is_escaped(text, range.start) && !overlaps_any(range, &protected) {
+            let escaped = range.start - 1..range.end;
+            replacements.push((escaped.start, escaped.end, Replacement::Escaped, false));
+            protected.push(escaped);
+        }
+    }
+
+    // Anchors of the terms given their first link in this text
+    let mut linked_here: HashSet<&str> = HashSet::new();
+    let mut taken: Vec<Range<usize>> = Vec::new();
+    for TermMatch { term, range } in occurrences {
+        if overlaps_any(&range, &taken)
+            || overlaps_any(&range, &protected)
+            || !matcher.allows(term, text, &range, hrefs.chapter())
+        {
+            continue;
+        }
+        let matched_text = &text[range.clone()];
+        let already_linked = linked_here.contains(term.anchor())
+            || (config.links_first_only(term.name()) && linked_terms.contains(term.anchor()));
+
+        let (link, href, first) = if !already_linked {
+            let href = hrefs.term_href(term);
+            let link = first_link(term, matched_text, href.clone(), config, linked_terms);
+            linked_terms.insert(term.anchor().to_string());
+            linked_here.insert(term.anchor());
+            (link, term.has_target().then_some(href), true)
+        } else if config.link_to_first_mention() && term.has_target() {
+            // Later occurrences point back to the first mention on the page
+            let link = build_mention_link(term, matched_text, config);
+            let href = format!("#{}", first_mention_id(term));
+            (vec![Event::Html(CowStr::from(link))], Some(href), false)
+        } else {
+            // Left unlinked, and not for a shorter term to match within
+            taken.push(range);
+            continue;
+        };
+        replacements.push((
+            range.start,
+            range.end,
+            Replacement::Link {
+                events: link,
+                term,
+                href,
+            },
+            first,
+        ));
+        taken.push(range);
+    }
+
+    // 2. Sort by position
+    replacements.sort_by_key(|(start, _, _, _)| *start);
+
+    // 3. Build events (using owned strings to avoid lifetime issues)
+    let mut events = Vec::new();
+    let mut last_end = 0;
+    let mut after_link = false;
+
+    for (start, end, replacement, first) in replacements {
+        // Skip overlapping replacements
+        if start < last_end {
+            continue;
+        }
+
+        // Emit text before this match (owned)
+        let linking = matches!(replacement, Replacement::Link { .. }) && links.left > 0;
+        if start > last_end {
+            let before = &text[last_end..start];
+            events.push(Event::Text(CowStr::from(escape_delimiters(
+                before, after_link, linking,
+            ))));
+        }
+        after_link = linking;
+
+        match replacement {
+            // The backslash is dropped, the occurrence stays plain text
+            Replacement::Escaped => {
+                events.push(Event::Text(CowStr::from(text[start + 1..end].to_string())));
             }
-            Event::End(TagEnd::CodeBlock | TagEnd::Link | TagEnd::Image | TagEnd::Heading(_)) => {
-                context_stack.pop();
-                result.push(event);
+            // Past `max-links-per-page`, occurrences stay plain text and their terms unlinked
+            Replacement::Link { term, .. } if links.left == 0 => {
+                events.push(Event::Text(CowStr::from(text[start..end].to_string())));
+                if first {
+                    linked_terms.remove(term.anchor());
+                }
             }
-            Event::Code(_) => {
-                // Inline code - pass through unchanged
-                result.push(event);
+            Replacement::Link {
+                events: link,
+                term,
+                href,
+            } => {
+                links.add(term, &text[start..end], start, href);
+                events.extend(link);
             }
+        }
+
+        last_end = end;
+    }
+
+    // Emit remaining text (owned)
+    if last_end < text.len() {
+        let rest = &text[last_end..];
+        events.push(Event::Text(CowStr::from(escape_delimiters(
+            rest, after_link, false,
+        ))));
+    }
+
+    // If no replacements, return original text as single event (owned)
+    if events.is_empty() {
+        events.push(Event::Text(CowStr::from(text.to_string())));
+    }
+
+    events
+}
+
+/// Escapes the emphasis delimiters (`*`, `~`) at the ends of `text` that touch a link.
+///
+/// A literal delimiter next to a term (`API*!*`) flanks a letter, and so
+/// can only close emphasis; once the term is markup it flanks punctuation
+/// and may pair with another delimiter, emphasizing text it never did.
+fn escape_delimiters(text: &str, after_link: bool, before_link: bool) -> String {
+    let is_delimiter = |c: char| c == '*' || c == '~';
+    let lead = if after_link {
+        text.len() - text.trim_start_matches(is_delimiter).len()
+    } else {
+        0
+    };
+    let trail = if before_link {
+        text.trim_end_matches(is_delimiter).len().max(lead)
+    } else {
+        text.len()
+    };
+    let mut escaped = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        if (i < lead || i >= trail) && !is_escaped(text, i) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// What a matched occurrence is replaced with.
+enum Replacement<'t> {
+    /// An occurrence escaped with a backslash, emitted without it.
+    Escaped,
+    /// The events of a link to a term, and its target.
+    Link {
+        events: Vec<Event<'static>>,
+        term: &'t Term,
+        href: Option<String>,
+    },
+}
+
+/// The links added to a page so far.
+#[derive(Debug)]
+struct PageLinks {
+    /// Links that may still be added, per `max-links-per-page`.
+    left: usize,
+    /// Number of links added per term anchor.
+    counts: HashMap<String, usize>,
+    /// Byte offset in the chapter of the text being linked.
+    offset: usize,
+    /// The links added so far.
+    added: Vec<TermLink>,
+}
+
+impl PageLinks {
+    /// Starts a page on which at most `max` links are added (unlimited when `None`).
+    fn new(max: Option<usize>) -> Self {
+        Self {
+            left: max.unwrap_or(usize::MAX),
+            counts: HashMap::new(),
+            offset: 0,
+            added: Vec::new(),
+        }
+    }
+
+    /// Records a link to `term` from `matched_text`, at `start` in the text being linked.
+    fn add(&mut self, term: &Term, matched_text: &str, start: usize, href: Option<String>) {
+        self.left -= 1;
+        *self.counts.entry(term.anchor().to_string()).or_default() += 1;
+        self.added.push(TermLink {
+            term: term.name().to_string(),
+            matched_text: matched_text.to_string(),
+            offset: self.offset + start,
+            href,
+        });
+    }
+}
+
+/// Visually hides the `sr-expansion` span while keeping it readable by screen
+/// readers; inline, since mdBook's theme has no `sr-only` class.
+const SR_ONLY_STYLE: &str = "position: absolute; width: 1px; height: 1px; overflow: hidden; \
+                             clip: rect(0, 0, 0, 0); white-space: nowrap;";
+
+/// Builds the link events for the first linked occurrence of a term.
+///
+/// An acronym linked for the first time on the page is followed by its long
+/// form: in parentheses with `expand-acronyms`, or for assistive technology
+/// only with `sr-expansion`.
+fn first_link(
+    term: &Term,
+    matched_text: &str,
+    href: String,
+    config: &Config,
+    linked_terms: &HashSet<String>,
+) -> Vec<Event<'static>> {
+    let long_name = term
+        .long_name()
+        .filter(|_| !linked_terms.contains(term.anchor()))
+        .filter(|_| {
+            term.short_name()
+                .is_some_and(|short| short.eq_ignore_ascii_case(matched_text))
+        });
+    let expansion = long_name
+        .filter(|_| config.expand_acronyms())
+        .map(|long_name| format!(" ({long_name})"));
+
+    if config.output() == Output::Markdown {
+        let mut events = markdown_link(term, matched_text, href, config);
+        events.extend(expansion.map(|text| Event::Text(CowStr::from(text))));
+        return events;
+    }
+
+    let mut link = build_link(term, matched_text, &href, config);
+    if let Some(expansion) = expansion {
+        link.push_str(&html_escape(&expansion));
+    } else if let Some(long_name) = long_name.filter(|_| config.sr_expansion()) {
+        let _ = write!(
+            link,
+            r#"<span class="sr-only" style="{SR_ONLY_STYLE}"> ({})</span>"#,
+            html_escape(long_name)
+        );
+    }
+    vec![Event::Html(CowStr::from(link))]
+}
+
+/// Builds the markdown link for one matched occurrence of a term, for `output = "markdown"`.
+///
+/// The tooltip becomes the link title; classes and other attributes need HTML
+/// and are left out. A term without a target is left as written.
+fn markdown_link(
+    term: &Term,
+    matched_text: &str,
+    href: String,
+    config: &Config,
+) -> Vec<Event<'static>> {
+    if !term.has_target() {
+        return vec![Event::Text(CowStr::from(matched_text.to_string()))];
+    }
+    vec![
+        Event::Start(Tag::Link {
+            link_type: LinkType::Inline,
+            dest_url: CowStr::from(href),
+            title: CowStr::from(tooltip_text(term, config).unwrap_or_default()),
+            id: CowStr::from(""),
+        }),
+        Event::Text(CowStr::from(matched_text.to_string())),
+        Event::End(TagEnd::Link),
+    ]
+}
+
+/// Builds the HTML link for one matched occurrence of a term.
+///
+/// With `output = "span"` or `"bold"` the term is marked up in place instead,
+/// keeping the class and tooltip but not linking anywhere.
+fn build_link(term: &Term, matched_text: &str, href: &str, config: &Config) -> String {
+    let tooltip_attrs = tooltip_attrs(term, config);
+    let element = match config.output() {
+        // Terms without a target only get their tooltip
+        Output::Link | Output::Abbr | Output::Markdown if !term.has_target() => Some("span"),
+        Output::Link | Output::Abbr | Output::Markdown => None,
+        Output::Span => Some("span"),
+        Output::Bold => Some("strong"),
+    };
+    if let Some(element) = element {
+        let lang_attr = config
+            .link_lang(term.lang())
+            .map(|lang| format!(r#" lang="{}""#, html_escape(lang)))
+            .unwrap_or_default();
+        return format!(
+            r#"<{element}{tooltip_attrs}{lang_attr} class="{}">{}</{element}>"#,
+            link_class(term, config),
+            html_escape(matched_text),
+        );
+    }
+
+    // The first mention is the target of later occurrences' in-page links
+    let id_attr = if config.link_to_first_mention() {
+        format!(r#" id="{}""#, first_mention_id(term))
+    } else {
+        String::new()
+    };
+    let lang_attrs = config
+        .link_lang(term.lang())
+        .map(|lang| {
+            let lang = html_escape(lang);
+            format!(r#" lang="{lang}" hreflang="{lang}""#)
+        })
+        .unwrap_or_default();
+
+    format!(
+        r#"<a href="{}"{}{}{}{} class="{}">{}</a>"#,
+        html_escape(href),
+        id_attr,
+        tooltip_attrs,
+        lang_attrs,
+        external_attrs(term),
+        link_class(term, config),
+        link_text(term, matched_text, config),
+    )
+}
+
+/// Returns the HTML text of a link to `term`.
+///
+/// With `output = "abbr"`, an occurrence of the term's short form is wrapped
+/// in `<abbr>` titled with the long form, e.g. `<abbr title="Application
+/// Programming Interface">API</abbr>`. Other occurrences are plain text.
+fn link_text(term: &Term, matched_text: &str, config: &Config) -> String {
+    if config.output() == Output::Abbr
+        && term
+            .short_name()
+            .is_some_and(|short| short.eq_ignore_ascii_case(matched_text))
+        && let Some(long_name) = term.long_name()
+    {
+        return format!(
+            r#"<abbr title="{}">{}</abbr>"#,
+            html_escape(long_name),
+            html_escape(matched_text)
+        );
+    }
+    html_escape(matched_text)
+}
+
+/// Returns the attributes for links leaving the book, for terms with their own URL.
+///
+/// External pages open in a new tab without access to the book's window.
+fn external_attrs(term: &Term) -> &'static str {
+    if term
+        .url()
+        .is_some_and(|url| url.contains("://") || url.starts_with("//"))
+    {
+        r#" target="_blank" rel="noopener noreferrer""#
+    } else {
+        ""
+    }
+}
+
+/// Returns the class attribute value for links to `term`.
+///
+/// Terms with a category also get a per-category class: the one configured
+/// in `category-classes`, or a modifier such as `glossary-term--file-formats`
+/// for a term listed under `## File formats`. Deprecated terms get a
+/// `glossary-term--deprecated` modifier. A term's own `css-class` in `terms`
+/// is added last.
+fn link_class(term: &Term, config: &Config) -> String {
+    let mut class = config.css_class().to_string();
+    if let Some(category) = term.category() {
+        if let Some(category_class) = config.category_class(category) {
+            let _ = write!(class, " {category_class}");
+        } else {
+            let slug = generate_anchor(category);
+            if !slug.is_empty() {
+                let _ = write!(class, " {}--{slug}", config.css_class());
+            }
+        }
+    }
+    if term.is_deprecated() {
+        let _ = write!(class, " {}--deprecated", config.css_class());
+    }
+    if let Some(term_class) = config
+        .term_override(term.name())
+        .and_then(|term| term.css_class.as_deref())
+    {
+        let _ = write!(class, " {term_class}");
+    }
+    class
+}
+
+/// Builds the in-page link from a later occurrence of a term to its first mention.
+fn build_mention_link(term: &Term, matched_text: &str, config: &Config) -> String {
+    format!(
+        r##"<a href="#{}"{} class="{}">{}</a>"##,
+        first_mention_id(term),
+        tooltip_attrs(term, config),
+        link_class(term, config),
+        link_text(term, matched_text, config),
+    )
+}
+
+/// Returns the element id of a term's first mention on a page.
+///
+/// Prefixed so it cannot collide with the heading ids mdBook generates.
+fn first_mention_id(term: &Term) -> String {
+    format!("termlink-{}", term.anchor())
+}
+
+/// Builds the attributes holding the tooltip of a term, if it has one.
+///
+/// With `tooltip = "popover"`, the definition rendered to HTML is added as
+/// `data-termlink-definition` for `termlink.js`, which shows it in a hover
+/// card in place of the `title` tooltip.
+fn tooltip_attrs(term: &Term, config: &Config) -> String {
+    let mut attrs = title_attr(term, config);
+    if config.tooltip() == Tooltip::Popover
+        && let Some(definition) = term.definition_html()
+    {
+        let citation = term
+            .citation()
+            .map(|citation| {
+                format!(
+                    r#"<p class="termlink-popover-source">Source: {}</p>"#,
+                    html_escape(citation)
+                )
+            })
+            .unwrap_or_default();
+        let _ = write!(
+            attrs,
+            r#" data-termlink-definition="{}""#,
+            html_escape(&format!("{definition}{citation}")).replace('\n', "&#10;")
+        );
+    }
+    attrs
+}
+
+/// Returns the plain-text tooltip of a term: its definition and citation.
+///
+/// The definition is shortened according to `tooltip-source` and `tooltip-max-length`.
+fn tooltip_text(term: &Term, config: &Config) -> Option<String> {
+    let definition = term
+        .definition()
+        .map(|definition| tooltip_excerpt(definition, config));
+    match (definition, term.citation()) {
+        (Some(definition), Some(citation)) => Some(format!("{definition}\nSource: {citation}")),
+        (Some(definition), None) => Some(definition),
+        (None, Some(citation)) => Some(format!("Source: {citation}")),
+        (None, None) => None,
+    }
+}
+
+/// Returns the part of a definition shown in its `title` tooltip.
+fn tooltip_excerpt(definition: &str, config: &Config) -> String {
+    let excerpt = match config.tooltip_source() {
+        TooltipSource::Full => definition,
+        TooltipSource::FirstParagraph => first_paragraph(definition),
+        TooltipSource::FirstSentence => first_sentence(first_paragraph(definition)),
+    };
+    match config.tooltip_max_length() {
+        Some(max) if excerpt.chars().count() > max => truncate(excerpt, max),
+        _ => excerpt.to_string(),
+    }
+}
+
+/// Returns the text up to the first blank line.
+fn first_paragraph(text: &str) -> &str {
+    text.split("\n\n").next().unwrap_or(text).trim_end()
+}
+
+/// Returns the text up to the end of its first sentence.
+///
+/// A sentence ends at `.`, `!`, or `?` followed by whitespace and an
+/// uppercase letter, so abbreviations such as "e.g. a" don't end it, or at
+/// a full-width stop (`。`).
+fn first_sentence(text: &str) -> &str {
+    for (index, c) in text.char_indices() {
+        let end = index + c.len_utf8();
+        let rest = &text[end..];
+        let ends_sentence = match c {
+            '。' | '！' | '？' => true,
+            '.' | '!' | '?' => {
+                rest.starts_with(char::is_whitespace)
+                    && rest
+                        .trim_start()
+                        .chars()
+                        .next()
+                        .is_none_or(char::is_uppercase)
+            }
+            _ => false,
+        };
+        if ends_sentence {
+            return &text[..end];
+        }
+    }
+    text
+}
+
+/// Shortens text to at most `max` characters, ending with an ellipsis.
+///
+/// The cut is made at a word boundary when the kept text has one.
+fn truncate(text: &str, max: usize) -> String {
+    // Leave room for the ellipsis
+    let cut = text
+        .char_indices()
+        .nth(max.saturating_sub(1))
+        .map_or(text.len(), |(index, _)| index);
+    let mut kept = &text[..cut];
+    if !text[cut..].starts_with(char::is_whitespace)
+        && let Some(space) = kept.rfind(char::is_whitespace)
+    {
+        kept = &kept[..space];
+    }
+    format!("{}…", kept.trim_end())
+}
+
+/// Builds the `title` attribute holding the tooltip of a term, if it has one.
+fn title_attr(term: &Term, config: &Config) -> String {
+    // The line break is written as an entity so the link stays on one markdown line
+    tooltip_text(term, config)
+        .map(|t| format!(r#" title="{}""#, html_escape(&t).replace('\n', "&#10;")))
+        .unwrap_or_default()
+}
+
+/// Matches template placeholders such as `{{variable}}` or `{{#template file.md}}`.
+static PLACEHOLDER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{.*?\}\}").expect("placeholder regex is valid"));
+
+/// Matches mdBook's own directives such as `{{#include file.rs}}` or
+/// `{{#playground example.rs editable}}`, which may span lines.
+static DIRECTIVE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*#[a-zA-Z0-9_]+\s*[^}]+\}\}").expect("directive regex is valid")
+});
+
+/// Returns the spans of `text` that must never be linked.
+///
+/// These are `{{ ... }}` placeholders left for other preprocessors
+/// (mdbook-variables, mdbook-template) and mdBook's own directives
+/// (`{{#include}}`, `{{#playground}}`); inserting a link inside one would
+/// corrupt it. So would a link inside math between `math-delimiters`.
+pub fn protected_spans(text: &str, config: &Config) -> Vec<Range<usize>> {
+    PLACEHOLDER_REGEX
+        .find_iter(text)
+        .chain(DIRECTIVE_REGEX.find_iter(text))
+        .map(|m| m.start()..m.end())
+        .chain(math_spans(text, config.math_delimiters()))
+        .collect()
+}
+
+/// Returns the spans of math in `text`, delimiters included.
+///
+/// Where several delimiters open at the same place, the longest wins, so
+/// `$$` is read before `$`. A delimiter escaped with a backslash (`\$`)
+/// opens nothing, nor does one that is never closed.
+fn math_spans(text: &str, delimiters: &[(String, String)]) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    while let Some((start, open, close)) = delimiters
+        .iter()
+        .filter_map(|(open, close)| Some((pos + text[pos..].find(open.as_str())?, open, close)))
+        .min_by_key(|&(start, open, _)| (start, Reverse(open.len())))
+    {
+        let body = start + open.len();
+        match text[body..].find(close.as_str()) {
+            Some(end) if !is_escaped(text, start) => {
+                let end = body + end + close.len();
+                spans.push(start..end);
+                pos = end;
+            }
+            _ => pos = body,
+        }
+    }
+    spans
+}
+
+/// Checks whether the occurrence at `start` is escaped with a backslash (`\API`).
+pub fn is_escaped(text: &str, start: usize) -> bool {
+    text[..start].ends_with('\\')
+}
+
+/// Checks whether `range` overlaps any of the `spans`.
+fn overlaps_any(range: &Range<usize>, spans: &[Range<usize>]) -> bool {
+    spans
+        .iter()
+        .any(|span| range.start < span.end && span.start < range.end)
+}
+
+/// Finds the occurrences of glossary terms in text, all terms in one pass.
+///
+/// Every searchable form of every term goes into one Aho-Corasick
+/// automaton, so a text is scanned once however large the glossary is. The
+/// automaton is built once per book and shared by all chapters.
+///
+/// Occurrences must stand as whole words: a match may not touch a word
+/// character on either side. Unlike `\b`, this holds for terms starting or
+/// ending in a symbol too, so "C++", "C#", and ".NET" are found before
+/// punctuation and spaces, but ".NET" is not found in "ASP.NET" nor "C++" in
+/// "C++20". Either side may touch a script written without spaces between
+/// words (Chinese, Japanese, Korean, Thai): there, "関数" is found in
+/// "この関数は", and "API" in "使用API接口".
+#[derive(Debug)]
+pub struct TermMatcher<'a> {
+    /// Every glossary term, in glossary order.
+    glossary: &'a [Term],
+    /// The matchable terms, longest name first.
+    terms: Vec<&'a Term>,
+    /// Automaton over the (normalized) forms of the terms.
+    automaton: AhoCorasick,
+    /// Index into `terms` of each pattern of the automaton.
+    pattern_terms: Vec<usize>,
+    /// For each pattern of a case-sensitive term in a case-folded automaton,
+    /// the form an occurrence must normalize to without case folding.
+    exact_forms: Vec<Option<String>>,
+    /// Regexes from `patterns`, with the index into `terms` of their term.
+    regexes: Vec<(Regex, usize)>,
+    /// Whether text is case-folded before matching (any term is case-insensitive).
+    fold_case: bool,
+    /// Whether diacritics are stripped before matching.
+    ignore_diacritics: bool,
+    /// Whether hyphens count as whitespace in matching.
+    flexible_separators: bool,
+    /// Custom rule an occurrence must pass to be linked.
+    filter: Option<SharedFilter>,
+}
+
+/// One whole-word occurrence of a term.
+#[derive(Debug, Clone)]
+pub struct TermMatch<'a> {
+    /// The term found.
+    term: &'a Term,
+    /// Byte range of the occurrence in the text.
+    pub range: Range<usize>,
+}
+
+impl<'a> TermMatcher<'a> {
+    /// Builds the matcher for the glossary `terms`.
+    ///
+    /// At the same position, occurrences of longer terms are preferred, so
+    /// "REST API" wins over "REST", unless a term has a higher `priority`.
+    /// Terms listed in `ignore-terms` are never found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the automaton exceeds its size limits, or a
+    /// term's regex in `patterns` is invalid.
+    pub fn new(terms: &'a [Term], config: &Config) -> Result<Self> {
+        let ignore_diacritics = config.ignore_diacritics();
+        let flexible_separators = config.flexible_separators();
+        let mut matchable: Vec<&Term> = terms
+            .iter()
+            .filter(|term| !config.is_ignored(term.name(), term.short_name()))
+            .collect();
+        matchable.sort_by_key(|t| std::cmp::Reverse(t.name().len()));
+        // Case-sensitive terms are checked again after matching, see `exact_forms`
+        let fold_case = matchable
+            .iter()
+            .any(|term| !config.is_case_sensitive(term.name()));
+
+        let mut patterns = Vec::new();
+        let mut pattern_terms = Vec::new();
+        let mut exact_forms = Vec::new();
+        for (index, term) in matchable.iter().enumerate() {
+            let exact = fold_case && config.is_case_sensitive(term.name());
+            for form in term_forms(term, config) {
+                patterns
+                    .push(normalize(&form, fold_case, ignore_diacritics, flexible_separators).0);
+                pattern_terms.push(index);
+                exact_forms
+                    .push(exact.then(|| {
+                        normalize(&form, false, ignore_diacritics, flexible_separators).0
+                    }));
+            }
+        }
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(&patterns)?;
+        let regexes = matchable
+            .iter()
+            .enumerate()
+            .filter_map(|(index, term)| Some((index, term.name(), config.pattern(term.name())?)))
+            .map(|(index, name, pattern)| {
+                let regex = RegexBuilder::new(pattern)
+                    .case_insensitive(!config.is_case_sensitive(name))
+                    .build()
+                    .with_context(|| format!("Invalid pattern for term '{name}'"))?;
+                Ok((regex, index))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            glossary: terms,
+            terms: matchable,
+            automaton,
+            pattern_terms,
+            exact_forms,
+            regexes,
+            fold_case,
+            ignore_diacritics,
+            flexible_separators,
+            filter: None,
+        })
+    }
+
+    /// Links only the occurrences `filter` allows.
+    #[must_use]
+    pub fn with_filter(self, filter: impl MatchFilter + 'static) -> Self {
+        self.with_shared_filter(Some(SharedFilter::new(filter)))
+    }
+
+    /// Links only the occurrences `filter` allows, if there is one.
+    #[must_use]
+    pub(crate) fn with_shared_filter(mut self, filter: Option<SharedFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the filter occurrences must pass, if any.
+    #[must_use]
+    pub(crate) const fn filter(&self) -> Option<&SharedFilter> {
+        self.filter.as_ref()
+    }
+
+    /// Returns whether the filter, if any, allows linking `term` at `range` in `text`.
+    fn allows(&self, term: &Term, text: &str, range: &Range<usize>, chapter: &Path) -> bool {
+        self.filter.as_ref().is_none_or(|filter| {
+            filter.allows(&MatchContext {
+                term,
+                matched_text: &text[range.clone()],
+                text,
+                range: range.clone(),
+                chapter,
+            })
+        })
+    }
+
+    /// Returns every glossary term, in glossary order.
+    #[must_use]
+    pub const fn glossary(&self) -> &'a [Term] {
+        self.glossary
+    }
+
+    /// Returns every whole-word occurrence of every term in `text`.
+    ///
+    /// Occurrences may overlap. They are sorted in the order they are
+    /// preferred where they do: by the priority of their term, highest first,
+    /// then by position, longer ones first, then by the length of the term
+    /// name. Where priorities are equal, this is leftmost-longest.
+    pub(crate) fn find_all(&self, text: &str) -> Vec<TermMatch<'a>> {
+        let (haystack, offsets) = if self.fold_case
+            || self.ignore_diacritics
+            || self.flexible_separators
+            || has_whitespace_runs(text)
+        {
+            let (normalized, offsets) = normalize(
+                text,
+                self.fold_case,
+                self.ignore_diacritics,
+                self.flexible_separators,
+            );
+            (Cow::Owned(normalized), Some(offsets))
+        } else {
+            (Cow::Borrowed(text), None)
+        };
+
+        let mut found: Vec<(usize, TermMatch)> = Vec::new();
+        let mut push_whole_word = |index: usize, range: Range<usize>| {
+            let matched = &text[range.clone()];
+            let before = text[..range.start].chars().next_back();
+            let after = text[range.end..].chars().next();
+            if is_word_edge(before, matched.chars().next())
+                && is_word_edge(after, matched.chars().next_back())
+            {
+                found.push((
+                    index,
+                    TermMatch {
+                        term: self.terms[index],
+                        range,
+                    },
+                ));
+            }
+        };
+        for mat in self.automaton.find_overlapping_iter(haystack.as_ref()) {
+            let range = match &offsets {
+                // A match must cover whole characters of the original text
+                Some(offsets) => {
+                    let on_char =
+                        |i: usize| i == 0 || i == offsets.len() - 1 || offsets[i - 1] != offsets[i];
+                    if !on_char(mat.start()) || !on_char(mat.end()) {
+                        continue;
+                    }
+                    offsets[mat.start()]..offsets[mat.end()]
+                }
+                None => mat.range(),
+            };
+            if let Some(exact) = &self.exact_forms[mat.pattern().as_usize()]
+                && normalize(
+                    &text[range.clone()],
+                    false,
+                    self.ignore_diacritics,
+                    self.flexible_separators,
+                )
+                .0 != *exact
+            {
+                continue;
+            }
+            push_whole_word(self.pattern_terms[mat.pattern().as_usize()], range);
+        }
+        for (regex, index) in &self.regexes {
+            for mat in regex.find_iter(text).filter(|mat| !mat.is_empty()) {
+                push_whole_word(*index, mat.range());
+            }
+        }
+
+        found.sort_by_key(|(index, m)| {
+            (
+                Reverse(m.term.priority()),
+                m.range.start,
+                Reverse(m.range.end),
+                *index,
+            )
+        });
+        found.dedup_by(|(a_index, a), (b_index, b)| a_index == b_index && a.range == b.range);
+        found.into_iter().map(|(_, m)| m).collect()
+    }
+}
+
+/// Checks whether a match edge at `inside` may border `outside` (`None` at the text edge).
+///
+/// The edge character itself may be a symbol, as in "C++"; only what is
+/// outside the match decides.
+pub fn is_word_edge(outside: Option<char>, inside: Option<char>) -> bool {
+    let (Some(outside), Some(inside)) = (outside, inside) else {
+        return true;
+    };
+    !is_word_char(outside) || is_unspaced_script(outside) || is_unspaced_script(inside)
+}
+
+/// Checks whether `c` is part of a word, as `\w` defines it.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Checks whether `c` belongs to a script written without spaces between words.
+const fn is_unspaced_script(c: char) -> bool {
+    matches!(c,
+        '\u{0E00}'..='\u{0EFF}'     // Thai, Lao
+        | '\u{1000}'..='\u{109F}'   // Myanmar
+        | '\u{1780}'..='\u{17FF}'   // Khmer
+        | '\u{1100}'..='\u{11FF}'   // Hangul Jamo
+        | '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{3130}'..='\u{318F}'   // Hangul Compatibility Jamo
+        | '\u{31F0}'..='\u{31FF}'   // Katakana Phonetic Extensions
+        | '\u{3400}'..='\u{4DBF}'   // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}'   // Hangul Syllables
+        | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{FF66}'..='\u{FF9F}'   // Halfwidth Katakana
+        | '\u{20000}'..='\u{2FA1F}' // CJK Extensions B and later
+    )
+}
+
+/// Returns the forms of a term matched in chapters.
+///
+/// These are the searchable forms long enough to match, plus their plurals
+/// with `match-inflections`.
+fn term_forms(term: &Term, config: &Config) -> Vec<String> {
+    let mut forms: Vec<String> = term
+        .searchable_forms()
+        .into_iter()
+        .filter(|f| !f.is_empty() && f.chars().count() >= config.min_term_length())
+        .map(String::from)
+        .collect();
+    if config.match_inflections() {
+        let inflected: Vec<String> = forms.iter().flat_map(|f| inflections(f)).collect();
+        forms.extend(inflected);
+    }
+    forms.sort();
+    forms.dedup();
+    forms
+}
+
+/// Normalizes `text` for matching, mapping the result back to `text`.
+///
+/// Each run of whitespace, line breaks included, becomes one space, as it
+/// renders. Characters are case-folded with `fold_case` and lose their
+/// diacritics with `ignore_diacritics`, one by one (see
+/// [`folding::push_folded`]); with `flexible_separators`, hyphens count as
+/// whitespace. Returns the
+/// normalized text and, for each of its bytes plus its end, the offset in
+/// `text` of the character the byte came from.
+fn normalize(
+    text: &str,
+    fold_case: bool,
+    ignore_diacritics: bool,
+    flexible_separators: bool,
+) -> (String, Vec<usize>) {
+    let mut normalized = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    let mut in_separator = false;
+    for (offset, c) in text.char_indices() {
+        if c.is_whitespace() || (flexible_separators && c == '-') {
+            if !in_separator {
+                normalized.push(' ');
+                offsets.push(offset);
+            }
+            in_separator = true;
+            continue;
+        }
+        in_separator = false;
+        folding::push_folded(&mut normalized, c, fold_case, ignore_diacritics);
+        offsets.resize(normalized.len(), offset);
+    }
+    offsets.push(text.len());
+    (normalized, offsets)
+}
+
+/// Checks whether `text` would change in [`normalize`] without case folding or hyphens.
+fn has_whitespace_runs(text: &str) -> bool {
+    text.contains(|c: char| c.is_whitespace() && c != ' ') || text.contains("  ")
+}
+
+/// Returns the plural forms of a term, inflecting its last word.
+///
+/// Covers the regular English rules: `-s`, `-es` after sibilants, `-y` to
+/// `-ies`, `-is` to `-es` ("analyses"), and the Latin `-ex`/`-ix` to `-ices`
+/// ("indices"). Irregular plurals still need aliases.
+fn inflections(form: &str) -> Vec<String> {
+    let Some(last) = form.chars().last() else {
+        return Vec::new();
+    };
+    if !last.is_alphabetic() {
+        return Vec::new();
+    }
+
+    let lower = form.to_lowercase();
+    let stem = |suffix_len: usize| &form[..form.len() - suffix_len];
+    let before_last = lower.chars().rev().nth(1);
+    let mut plurals = Vec::new();
+
+    if lower.ends_with('y') && before_last.is_some_and(|c| !"aeiou".contains(c)) {
+        plurals.push(format!("{}ies", stem(1)));
+    } else if lower.ends_with("is") && lower.len() > 3 {
+        plurals.push(format!("{}es", stem(2)));
+    } else if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|s| lower.ends_with(s))
+    {
+        plurals.push(format!("{form}es"));
+    } else {
+        plurals.push(format!("{form}s"));
+    }
+    if lower.ends_with("ex") || lower.ends_with("ix") {
+        plurals.push(format!("{}ices", stem(2)));
+    }
+    plurals
+}
+
+/// The hrefs of the glossary pages, as seen from one chapter.
+///
+/// A glossary split across several chapters has one href per page; each term
+/// links to the page defining it.
+#[derive(Debug, Clone, Default)]
+pub struct GlossaryHrefs {
+    /// The chapter linking, relative to `src`.
+    chapter: PathBuf,
+    /// Href per glossary page (keyed by the page's chapter path).
+    pages: HashMap<PathBuf, String>,
+    /// Href for terms without a known page.
+    fallback: String,
+}
+
+impl GlossaryHrefs {
+    /// Uses the same href for every term, such as `glossary.html` or
+    /// `/docs/glossary.html`.
+    #[must_use]
+    pub fn single(href: impl Into<String>) -> Self {
+        Self {
+            chapter: PathBuf::new(),
+            pages: HashMap::new(),
+            fallback: href.into(),
+        }
+    }
+
+    /// Calculates the hrefs of every glossary page from `from_chapter`.
+    #[must_use]
+    pub fn for_chapter(
+        from_chapter: &Path,
+        targets: &BTreeMap<PathBuf, GlossaryTarget>,
+        config: &Config,
+    ) -> Self {
+        let pages: HashMap<PathBuf, String> = targets
+            .iter()
+            .map(|(page, target)| (page.clone(), glossary_href(from_chapter, target, config)))
+            .collect();
+        let fallback = targets
+            .values()
+            .next()
+            .map(|target| glossary_href(from_chapter, target, config))
+            .unwrap_or_default();
+        Self {
+            chapter: from_chapter.to_path_buf(),
+            pages,
+            fallback,
+        }
+    }
+
+    /// Returns the path of the chapter these hrefs are for (empty for [`Self::single`]).
+    #[must_use]
+    pub fn chapter(&self) -> &Path {
+        &self.chapter
+    }
+
+    /// Returns the href of the first glossary page.
+    #[must_use]
+    pub fn first_page(&self) -> &str {
+        &self.fallback
+    }
+
+    /// Returns the full link target of `term`: its own URL, or its glossary entry.
+    #[must_use]
+    pub fn term_href(&self, term: &Term) -> String {
+        term.url().map_or_else(
+            || format!("{}#{}", self.href(term), term.anchor()),
+            str::to_string,
+        )
+    }
+
+    /// Returns the href of the glossary page defining `term`.
+    #[must_use]
+    pub fn href(&self, term: &Term) -> &str {
+        term.page()
+            .and_then(|page| self.pages.get(page))
+            .unwrap_or(&self.fallback)
+    }
+}
+
+/// Calculates the href used to link from a chapter to the glossary.
+///
+/// Site-absolute (`/docs/reference/glossary.html`) when `use-site-url` is
+/// enabled, relative to the chapter otherwise. External targets are used as is.
+#[must_use]
+pub fn glossary_href(from_chapter: &Path, target: &GlossaryTarget, config: &Config) -> String {
+    match target {
+        GlossaryTarget::External(url) => url.clone(),
+        GlossaryTarget::Local(to_glossary) => config.site_url().map_or_else(
+            || calculate_relative_path(from_chapter, to_glossary),
+            |site_url| {
+                format!(
+                    "{}/{}",
+                    site_url.trim_end_matches('/'),
+                    paths::to_url_path(to_glossary)
+                )
+            },
+        ),
+    }
+}
+
+/// Calculates the relative path from a chapter to the glossary.
+#[must_use]
+pub fn calculate_relative_path(from_chapter: &Path, to_glossary: &Path) -> String {
+    // Count directory depth of the chapter, as rendered (`./` and `..` resolved)
+    let from_chapter = paths::normalize(from_chapter);
+    let depth = from_chapter.parent().map_or(0, |p| p.components().count());
+
+    // Build relative path
+    let prefix = "../".repeat(depth);
+    format!("{}{}", prefix, paths::to_url_path(to_glossary))
+}
+
+/// Escapes HTML special characters.
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn default_config() -> Config {
+        Config::default()
+    }
+
+    #[test]
+    fn test_calculate_relative_path_same_dir() {
+        assert_eq!(
+            calculate_relative_path(Path::new("intro.md"), Path::new("glossary.html")),
+            "glossary.html"
+        );
+    }
+
+    #[test]
+    fn test_calculate_relative_path_one_level() {
+        assert_eq!(
+            calculate_relative_path(Path::new("chapter/intro.md"), Path::new("glossary.html")),
+            "../glossary.html"
+        );
+    }
+
+    #[test]
+    fn test_calculate_relative_path_unnormalized_chapter() {
+        assert_eq!(
+            calculate_relative_path(Path::new("./guide/intro.md"), Path::new("glossary.html")),
+            "../glossary.html"
+        );
+        assert_eq!(
+            calculate_relative_path(
+                Path::new("guide/../appendix/faq.md"),
+                Path::new("glossary.html")
+            ),
+            "../glossary.html"
+        );
+    }
+
+    #[test]
+    fn test_calculate_relative_path_two_levels() {
+        assert_eq!(
+            calculate_relative_path(
+                Path::new("part1/chapter1/intro.md"),
+                Path::new("glossary.html")
+            ),
+            "../../glossary.html"
+        );
+    }
+
+    #[test]
+    fn test_glossary_href_relative_by_default() {
+        assert_eq!(
+            glossary_href(
+                Path::new("chapter/intro.md"),
+                &GlossaryTarget::Local(PathBuf::from("reference/glossary.html")),
+                &default_config()
+            ),
+            "../reference/glossary.html"
+        );
+    }
+
+    #[test]
+    fn test_glossary_href_site_url() {
+        for (site_url, href) in [
+            ("/docs/", "/docs/reference/glossary.html"),
+            ("/docs", "/docs/reference/glossary.html"),
+            ("/", "/reference/glossary.html"),
+            (
+                "https://example.com/book/",
+                "https://example.com/book/reference/glossary.html",
+            ),
+        ] {
+            let book_toml: toml::Table = format!(
+                "[preprocessor.termlink]\nuse-site-url = true\n[output.html]\nsite-url = \"{site_url}\"\n"
+            )
+            .parse()
+            .unwrap();
+            let config = Config::from_book_toml(&book_toml).unwrap();
+            assert_eq!(
+                glossary_href(
+                    Path::new("chapter/intro.md"),
+                    &GlossaryTarget::Local(PathBuf::from("reference/glossary.html")),
+                    &config
+                ),
+                href
+            );
+        }
+    }
+
+    #[test]
+    fn test_glossary_href_print_page() {
+        // The same href from every chapter, and from print.html at the root
+        let book_toml: toml::Table = "[preprocessor.termlink]\nprint-page = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        for chapter in ["intro.md", "chapter/intro.md", "a/b/c.md"] {
+            assert_eq!(
+                glossary_href(
+                    Path::new(chapter),
+                    &GlossaryTarget::Local(PathBuf::from("reference/glossary.html")),
+                    &config
+                ),
+                "/reference/glossary.html"
+            );
+        }
+    }
+
+    #[test]
+    fn test_glossary_href_external() {
+        assert_eq!(
+            glossary_href(
+                Path::new("chapter/intro.md"),
+                &GlossaryTarget::External("https://example.com/glossary.html".to_string()),
+                &default_config()
+            ),
+            "https://example.com/glossary.html"
+        );
+    }
+
+    /// Returns the occurrences of `terms` in `text`, as matched.
+    fn find<'t>(terms: &[Term], config: &Config, text: &'t str) -> Vec<&'t str> {
+        TermMatcher::new(terms, config)
+            .unwrap()
+            .find_all(text)
+            .into_iter()
+            .map(|m| &text[m.range])
+            .collect()
+    }
+
+    #[test]
+    fn test_term_matcher_case_insensitive() {
+        let terms = [Term::new("XPT")];
+        let config = default_config();
+
+        assert_eq!(find(&terms, &config, "The XPT format"), ["XPT"]);
+        assert_eq!(find(&terms, &config, "The xpt format"), ["xpt"]);
+        assert_eq!(find(&terms, &config, "The Xpt format"), ["Xpt"]);
+    }
+
+    #[test]
+    fn test_term_matcher_case_folding() {
+        let config = default_config();
+
+        assert_eq!(
+            find(&[Term::new("Ärger")], &config, "viel ÄRGER hier"),
+            ["ÄRGER"]
+        );
+        // Folding "İ" adds a combining dot; a match may not end inside it
+        assert!(find(&[Term::new("i")], &config, "İ").is_empty());
+        assert_eq!(find(&[Term::new("API")], &config, "İ API"), ["API"]);
+        assert_eq!(
+            find(&[Term::new("Straße")], &config, "die STRASSE"),
+            ["STRASSE"]
+        );
+        assert!(find(&[Term::new("résumé")], &config, "a resume").is_empty());
+    }
+
+    #[test]
+    fn test_term_matcher_term_case_sensitivity() {
+        let terms = [Term::new("Go"), Term::new("API")];
+        let book_toml: toml::Table = "[preprocessor.termlink.terms.Go]\ncase-sensitive = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(
+            find(&terms, &config, "go on, the api is in Go, not GO"),
+            ["api", "Go"]
+        );
+
+        // The other way around in a case-sensitive book
+        let book_toml: toml::Table = "[preprocessor.termlink]\ncase-sensitive = true\n\n\
+                                      [preprocessor.termlink.terms.API]\ncase-sensitive = false\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(
+            find(&terms, &config, "go on, the api is in Go"),
+            ["api", "Go"]
+        );
+    }
+
+    #[test]
+    fn test_term_matcher_ignore_diacritics() {
+        let terms = [Term::new("résumé"), Term::new("Ångström")];
+        let book_toml: toml::Table = "[preprocessor.termlink]\nignore-diacritics = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert_eq!(
+            find(
+                &terms,
+                &config,
+                "A RÉSUMÉ, a resume, a re\u{301}sume\u{301}, in angstrom"
+            ),
+            ["RÉSUMÉ", "resume", "re\u{301}sume\u{301}", "angstrom"]
+        );
+    }
+
+    #[test]
+    fn test_term_matcher_flexible_separators() {
+        let terms = [Term::new("load balancer"), Term::new("e-mail")];
+        assert_eq!(
+            find(&terms, &default_config(), "A load-balancer, an email"),
+            Vec::<&str>::new()
+        );
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nflexible-separators = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(
+            find(
+                &terms,
+                &config,
+                "A Load-Balancer, a load \t balancer, and an e mail, not an email"
+            ),
+            ["Load-Balancer", "load \t balancer", "e mail"]
+        );
+    }
+
+    #[test]
+    fn test_term_matcher_whitespace_runs() {
+        let terms = [Term::new("load balancer")];
+        let book_toml: toml::Table = "[preprocessor.termlink]\ncase-sensitive = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert_eq!(
+            find(&terms, &config, "A load\n  balancer, a load  balancer"),
+            ["load\n  balancer", "load  balancer"]
+        );
+    }
+
+    #[test]
+    fn test_term_matcher_case_sensitive() {
+        let terms = [Term::new("XPT")];
+        let book_toml: toml::Table = "[preprocessor.termlink]\ncase-sensitive = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert_eq!(find(&terms, &config, "The XPT format"), ["XPT"]);
+        assert!(find(&terms, &config, "The xpt format").is_empty());
+    }
+
+    #[test]
+    fn test_term_matcher_word_boundary() {
+        let terms = [Term::new("API")];
+        let config = default_config();
+
+        assert_eq!(find(&terms, &config, "The API is"), ["API"]);
+        assert!(find(&terms, &config, "The APIs are").is_empty()); // Word boundary prevents partial match
+    }
+
+    #[test]
+    fn test_term_matcher_inflections() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nmatch-inflections = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert_eq!(find(&[Term::new("API")], &config, "The APIs are"), ["APIs"]);
+        assert_eq!(
+            find(&[Term::new("index")], &config, "two indices"),
+            ["indices"]
+        );
+        assert_eq!(
+            find(&[Term::new("index")], &config, "two indexes"),
+            ["indexes"]
+        );
+    }
+
+    #[test]
+    fn test_term_matcher_unspaced_scripts() {
+        let config = default_config();
+        let api = [Term::new("API")];
+
+        assert_eq!(
+            find(&[Term::new("関数")], &config, "この関数は値を返す").len(),
+            1
+        );
+        assert_eq!(find(&api, &config, "使用API接口").len(), 1);
+        assert_eq!(find(&api, &config, "APIを呼ぶ").len(), 1);
+        assert!(find(&api, &config, "ÄAPI and API_KEY").is_empty());
+    }
+
+    #[test]
+    fn test_term_matcher_noise_filtering() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nmin-term-length = 3\nignore-terms = [\"OK\"]\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert!(find(&[Term::new("OK")], &config, "It is OK").is_empty());
+        assert!(find(&[Term::new("ID")], &config, "an ID").is_empty());
+
+        // Only the short form is too short; the full name still matches
+        let terms = [Term::new("ID (Identifier)")];
+        assert!(find(&terms, &config, "an ID").is_empty());
+        assert_eq!(
+            find(&terms, &config, "an ID (Identifier)"),
+            ["ID (Identifier)"]
+        );
+    }
+
+    #[test]
+    fn test_term_matcher_all_terms() {
+        let terms = [Term::new("REST API"), Term::new("REST"), Term::new("API")];
+        let config = default_config();
+
+        // Overlapping occurrences are all found, longest first at each position
+        assert_eq!(
+            find(&terms, &config, "A REST API."),
+            ["REST API", "REST", "API"]
+        );
+        // A longer form failing the word check does not hide a shorter one
+        assert_eq!(find(&terms, &config, "REST APIx"), ["REST"]);
+    }
+
+    #[test]
+    fn test_term_matcher_symbol_edges() {
+        let terms = [Term::new("C++"), Term::new("C#"), Term::new(".NET")];
+        let config = default_config();
+
+        assert_eq!(
+            find(&terms, &config, "Use C++, C# (or .NET)."),
+            ["C++", "C#", ".NET"]
+        );
+        assert!(find(&terms, &config, "ASP.NET and C++20 or MC#").is_empty());
+    }
+
+    #[test]
+    fn test_term_matcher_possessives_and_punctuation() {
+        let terms = [Term::new("API"), Term::new("C++")];
+        let config = default_config();
+
+        // The suffix stays outside the match, and so outside the link text
+        assert_eq!(
+            find(
+                &terms,
+                &config,
+                "The API's response, the API’s body; use the API. C++'s rules!"
+            ),
+            ["API", "API", "API", "C++"]
+        );
+
+        let book_toml: toml::Table = "[preprocessor.termlink]\nmatch-inflections = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(find(&terms, &config, "The APIs' owners."), ["APIs"]);
+    }
+
+    #[test]
+    fn test_term_matcher_patterns() {
+        let terms = [Term::new("HTTP"), Term::new("API")];
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.patterns]\nHTTP = 'HTTP/\\d\\.\\d'\nAPI = 'v\\d+ API'\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        assert_eq!(
+            find(
+                &terms,
+                &config,
+                "HTTP/1.1 or http/2.0, not HTTP/1.x or XHTTP/1.1."
+            ),
+            ["HTTP/1.1", "HTTP", "http/2.0", "http", "HTTP"]
+        );
+        // Regex matches compete with the other forms, longest first
+        assert_eq!(find(&terms, &config, "The v2 API."), ["v2 API", "API"]);
+
+        let book_toml: toml::Table = "[preprocessor.termlink.patterns]\nAPI = '('\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert!(TermMatcher::new(&terms, &config).is_err());
+    }
+
+    #[test]
+    fn test_inflections() {
+        assert_eq!(inflections("cache"), ["caches"]);
+        assert_eq!(inflections("policy"), ["policies"]);
+        assert_eq!(inflections("key"), ["keys"]);
+        assert_eq!(inflections("bus"), ["buses"]);
+        assert_eq!(inflections("batch"), ["batches"]);
+        assert_eq!(inflections("analysis"), ["analyses"]);
+        assert_eq!(inflections("matrix"), ["matrixes", "matrices"]);
+        assert_eq!(inflections("load balancer"), ["load balancers"]);
+        assert!(inflections("C++").is_empty());
+    }
+
+    #[test]
+    fn test_term_matcher_with_short_name() {
+        let terms = [Term::new("API (Application Programming Interface)")];
+        let config = default_config();
+
+        assert_eq!(find(&terms, &config, "Use the API"), ["API"]);
+        assert_eq!(
+            find(
+                &terms,
+                &config,
+                "API (Application Programming Interface) is"
+            )[0],
+            "API (Application Programming Interface)"
+        );
+    }
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(html_escape("a < b"), "a &lt; b");
+        assert_eq!(html_escape("a & b"), "a &amp; b");
+        assert_eq!(html_escape("<script>"), "&lt;script&gt;");
+        assert_eq!(html_escape(r#"say "hello""#), "say &quot;hello&quot;");
+    }
+
+    /// Helper to convert events to a concatenated string for test assertions
+    fn events_to_string(events: &[Event]) -> String {
+        events
+            .iter()
+            .map(|e| match e {
+                Event::Text(s) | Event::Html(s) => s.to_string(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_replace_terms_link_first_only() {
+        let term = Term::new("XPT");
+        let terms = [term];
+        let config = default_config();
+        let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
+
+        let events = replace_terms_to_events(
+            "XPT is great. XPT is used.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("g.html"),
+            &config,
+            &mut linked,
+            &mut links,
+        );
+        let result = events_to_string(&events);
+
+        // Should only link first occurrence
+        assert!(result.contains(r#"<a href="g.html#xpt""#));
+        assert_eq!(result.matches("glossary-term").count(), 1);
+    }
+
+    #[test]
+    fn test_replace_terms_with_tooltip() {
+        let term =
+            Term::with_definition("API", Some("Application Programming Interface".to_string()));
+        let terms = [term];
+        let config = default_config();
+        let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
+
+        let events = replace_terms_to_events(
+            "Use the API for data access.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &mut linked,
+            &mut links,
+        );
+        let result = events_to_string(&events);
+
+        // Should include title attribute with definition
+        assert!(result.contains(r#"title="Application Programming Interface""#));
+        assert!(result.contains(r#"<a href="glossary.html#api""#));
+        assert!(result.contains("class=\"glossary-term\""));
+    }
+
+    #[test]
+    fn test_replace_terms_no_nested_links() {
+        let terms = [
+            Term::with_definition("API", Some("A REST or GraphQL interface.".to_string())),
+            Term::new("REST"),
+            Term::new("interface"),
+        ];
+        let config = default_config();
+        let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
+
+        let events = replace_terms_to_events(
+            "The API and REST.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &mut linked,
+            &mut links,
+        );
+        let result = events_to_string(&events);
+
+        // Terms in the tooltip of an inserted link are not matched again
+        assert!(result.contains(r#"title="A REST or GraphQL interface.""#));
+        assert_eq!(result.matches("<a ").count(), 2);
+        assert!(!result.contains("glossary.html#interface"));
+    }
+
+    #[test]
+    fn test_replace_terms_without_tooltip() {
+        let term = Term::new("API"); // No definition
+        let terms = [term];
+        let config = default_config();
+        let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
+
+        let events = replace_terms_to_events(
+            "Use the API for data access.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &mut linked,
+            &mut links,
+        );
+        let result = events_to_string(&events);
+
+        // Should NOT include title attribute
+        assert!(!result.contains("title="));
+        assert!(result.contains(r#"<a href="glossary.html#api""#));
+    }
+
+    #[test]
+    fn test_tooltip_excerpt() {
+        let definition = "An interface, e.g. a web API. It has endpoints.\n\nSee also REST.";
+        let excerpt = |toml: &str| {
+            let book_toml: toml::Table =
+                format!("[preprocessor.termlink]\n{toml}").parse().unwrap();
+            tooltip_excerpt(definition, &Config::from_book_toml(&book_toml).unwrap())
+        };
+
+        assert_eq!(excerpt(""), definition);
+        assert_eq!(
+            excerpt("tooltip-source = \"first-paragraph\""),
+            "An interface, e.g. a web API. It has endpoints."
+        );
+        assert_eq!(
+            excerpt("tooltip-source = \"first-sentence\""),
+            "An interface, e.g. a web API."
+        );
+        assert_eq!(
+            excerpt("tooltip-source = \"first-sentence\"\ntooltip-max-length = 20"),
+            "An interface, e.g.…"
+        );
+        assert_eq!(first_sentence("一つ目。二つ目。"), "一つ目。");
+        assert_eq!(truncate("Application", 5), "Appl…");
+    }
+
+    #[test]
+    fn test_replace_terms_skips_template_placeholders() {
+        let term = Term::new("API");
+        let terms = [term];
+        let config = default_config();
+        let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
+
+        let events = replace_terms_to_events(
+            "See {{API}} and {{#template api.md name=API}} for the API.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &mut linked,
+            &mut links,
+        );
+        let result = events_to_string(&events);
+
+        assert!(result.contains("{{API}}"));
+        assert!(result.contains("{{#template api.md name=API}}"));
+        assert!(result.contains(r#"class="glossary-term">API</a>."#));
+    }
+
+    #[test]
+    fn test_add_term_links_preserves_directives() {
+        let terms = [Term::new("API"), Term::new("src")];
+        let config = default_config();
+        let link = |content: &str| {
+            add_term_links(
+                content,
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+
+        for content in [
+            "{{#include ../src/api.rs}}\n",
+            "{{#playground API.rs\neditable}}\n",
+            "{{#include src/*API*.rs}}\n",
+            "{{ #rustdoc_include src/API.rs:2 }}\n",
+        ] {
+            assert_eq!(link(content), content);
+        }
+        let output = link("The API: {{#include src/*api*.rs}} and src\n");
+        assert!(output.starts_with("The <a "), "{output}");
+        assert!(
+            output.contains("{{#include src/*api*.rs}} and <a "),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_math_spans() {
+        let delimiters = [
+            ("$".to_string(), "$".to_string()),
+            ("$$".to_string(), "$$".to_string()),
+            ("\\(".to_string(), "\\)".to_string()),
+        ];
+        let spans = |text: &str| {
+            math_spans(text, &delimiters)
+                .into_iter()
+                .map(|span| text[span].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(spans("The $API_{max}$ and API"), ["$API_{max}$"]);
+        assert_eq!(spans("$$a $ b$$ \\(API\\)"), ["$$a $ b$$", "\\(API\\)"]);
+        assert_eq!(spans("Costs \\$5, API $x$"), ["$x$"]);
+        assert_eq!(spans("Costs $5"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_add_term_links_skips_math() {
+        let terms = [Term::new("API")];
+        let config = Config::from_book_toml(
+            &"[preprocessor.termlink]\nlink-first-only = false\nmath-delimiters = [[\"$\", \"$\"], [\"\\\\(\", \"\\\\)\"]]\n"
+                .parse()
+                .unwrap(),
+        )
+        .unwrap();
+        let link = |content: &str| {
+            add_term_links(
+                content,
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+
+        for content in [
+            "The $API_{max}$ value.\n",
+            "The \\(API\\) value.\n",
+            "The $a *API* b$ value.\n",
+            "The $API\nrate$ value.\n",
+        ] {
+            assert_eq!(link(content), content);
+        }
+        let output = link("The $x$ API.\n");
+        assert!(output.starts_with("The $x$ <a "), "{output}");
+    }
+
+    #[test]
+    fn test_add_term_links_preserves_placeholders() {
+        let term = Term::new("API");
+        let config = default_config();
+
+        let output = add_term_links(
+            "The {{ api_version }} of the {{API}} docs.\n",
+            &TermMatcher::new(&[term], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+
+        assert!(output.contains("{{ api_version }}"));
+        assert!(output.contains("{{API}}"));
+        assert!(!output.contains("glossary-term"));
+    }
+
+    #[test]
+    fn test_link_chapter_escaped_occurrence() {
+        let output = link_chapter(
+            "Call the \\API here, then the API there.\n",
+            &TermMatcher::new(&[Term::new("API")], &default_config()).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &default_config(),
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+
+        assert!(output.starts_with("Call the API here, then the <a href=\"glossary.html#api\""));
+        assert!(!output.contains('\\'));
+    }
+
+    #[test]
+    fn test_add_term_links_escapes_touching_delimiters() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlink-first-only = false\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let output = add_term_links(
+            "The API*!*, ~API, \\*API, *emphasized* API but not *API**[here](u)*.\n",
+            &TermMatcher::new(&[Term::new("API")], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+
+        assert!(output.contains("</a>\\*!*"));
+        assert!(output.contains(" \\~<a "));
+        assert!(output.contains(" \\*<a "));
+        assert!(output.contains(" *emphasized* <a "));
+        assert!(output.ends_with(" not *API**[here](u)*.\n"));
+    }
+
+    #[test]
+    fn test_add_term_links_skips_marker_blocks() {
+        let term = Term::new("API");
+        let config = default_config();
+
+        let output = add_term_links(
+            "API overview <!-- toc --> here.\n\nThe API is documented.\n",
+            &TermMatcher::new(&[term], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+
+        assert!(output.contains("API overview <!-- toc --> here."));
+        assert!(output.contains(r#"class="glossary-term">API</a> is documented."#));
+    }
+
+    #[test]
+    fn test_add_term_links_across_text_events() {
+        let config = default_config();
+        let terms = [Term::new("load balancer"), Term::new("REST API")];
+        let matcher = TermMatcher::new(&terms, &config).unwrap();
+        let link = |content: &str| {
+            add_term_links(
+                content,
+                &matcher,
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+
+        // Wrapped onto the next line, and split by the parser at `[`
+        assert_eq!(
+            link("Put a load\nbalancer [in front] of the REST\n  API.\n"),
+            "Put a <a href=\"glossary.html#load-balancer\" class=\"glossary-term\">load\nbalancer</a> \
+             [in front] of the <a href=\"glossary.html#rest-api\" class=\"glossary-term\">REST\n  API</a>.\n"
+        );
+        // The `>` of a block quote is not text
+        assert_eq!(link("> a load\n> balancer\n"), "> a load\n> balancer\n");
+    }
+
+    #[test]
+    fn test_add_term_links_skip_contexts() {
+        let terms = [Term::new("API")];
+        let content =
+            "# The API\n\n> An API quote.\n\n| API |\n|-----|\n| API |\n\n- An API item\n";
+        let linked_lines = |book_toml: &str| {
+            let config = Config::from_book_toml(&book_toml.parse().unwrap()).unwrap();
+            let output = add_term_links(
+                content,
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content;
+            output
+                .lines()
+                .filter(|line| line.contains("<a "))
+                .map(|line| line.split_whitespace().next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let all = "[preprocessor.termlink]\nlink-first-only = false\n";
+        assert_eq!(linked_lines(all), [">", "|", "|", "-"]);
+        assert_eq!(
+            linked_lines(&format!("{all}skip-contexts = []\n")),
+            ["#", ">", "|", "|", "-"]
+        );
+        assert_eq!(
+            linked_lines(&format!(
+                "{all}skip-contexts = [\"heading\", \"blockquote\", \"table-header\", \"list\"]\n"
+            )),
+            ["|"]
+        );
+    }
+
+    #[test]
+    fn test_add_term_links_raw_html() {
+        let terms = [Term::new("API")];
+        let content = "<div title=\"API\">\nThe API and <code>API</code>\n</div>\n\n\
+                       Inline <b>API</b>, <a href=\"x.html\">API</a> and <kbd>API</kbd>.\n";
+        let link = |book_toml: &str| {
+            let config = Config::from_book_toml(&book_toml.parse().unwrap()).unwrap();
+            add_term_links(
+                content,
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+
+        let all = "[preprocessor.termlink]\nlink-first-only = false\n";
+        let output = link(all);
+        assert!(output.starts_with("<div title=\"API\">\nThe API and <code>API</code>\n"));
+        assert_eq!(output.matches("<a ").count(), 2, "{output}");
+        assert!(output.contains("<b><a "));
+
+        let output = link(&format!("{all}link-in-html = true\n"));
+        assert!(
+            output.starts_with("<div title=\"API\">\nThe <a "),
+            "{output}"
+        );
+        assert!(output.contains("</a> and <code>API</code>\n</div>"));
+        assert_eq!(output.matches("<a ").count(), 3, "{output}");
+    }
+
+    #[test]
+    fn test_add_term_links_skips_preprocessor_blocks() {
+        let terms = [Term::new("API")];
+        let config = default_config();
+        let link = |content: &str| {
+            add_term_links(
+                content,
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+
+        for content in [
+            "```admonish note title=\"API\"\nThe API.\n```\n",
+            "~~~mermaid\ngraph TD; API-->REST\n~~~\n",
+            "- Item\n\n  ```admonish\n  The API.\n  ```\n",
+            "<div id=\"admonition-note\" class=\"admonition admonish-note\" role=\"note\">\n\
+             <div class=\"admonition-title\">\n\nNote\n\n</div>\n<div>\n\nThe *API*.\n\n</div>\n</div>\n",
+            "<pre class=\"mermaid\">graph TD;\n    API-->REST\n</pre>\n",
+        ] {
+            assert_eq!(link(content), content);
+        }
+        let output = link("<div class=\"admonition\">\n\nNote\n\n</div>\n\nThe API.\n");
+        assert!(
+            output
+                .ends_with("The <a href=\"glossary.html#api\" class=\"glossary-term\">API</a>.\n"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_add_term_links_emphasized_terms() {
+        let terms = [Term::new("API"), Term::new("REST")];
+        let link = |content: &str, book_toml: &str| {
+            let config = Config::from_book_toml(&book_toml.parse().unwrap()).unwrap();
+            add_term_links(
+                content,
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+
+        // The emphasis moves inside the link, where intraword `*` still reads as emphasis
+        assert_eq!(
+            link("The*API*call and __REST__.\n", ""),
+            "The<a href=\"glossary.html#api\" class=\"glossary-term\">*API*</a>call and \
+             <a href=\"glossary.html#rest\" class=\"glossary-term\">__REST__</a>.\n"
+        );
+        assert_eq!(
+            link(
+                "Use **REST**.\n",
+                "[preprocessor.termlink]\noutput = \"markdown\"\n"
+            ),
+            "Use [**REST**](glossary.html#rest).\n"
+        );
+        // Emphasis around more than the term stays outside
+        assert_eq!(
+            link("*The API*\n", ""),
+            "*The <a href=\"glossary.html#api\" class=\"glossary-term\">API</a>*\n"
+        );
+    }
+
+    #[test]
+    fn test_add_term_links_skips_disabled_regions() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlink-first-only = false\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let content = "API one.\n\n<!-- termlink:off -->\n\n> The API as quoted.\n\n<!-- termlink:on -->\n\nThe REST API.\n";
+        let output = add_term_links(
+            content,
+            &TermMatcher::new(&[Term::new("API"), Term::new("REST")], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+
+        assert!(output.contains("> The API as quoted."));
+        assert!(output.contains("<!-- termlink:off -->"));
+        assert_eq!(output.matches("glossary.html#api").count(), 2);
+        assert!(output.contains("glossary.html#rest"));
+    }
+
+    #[test]
+    fn test_add_term_links_markdown_output() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\noutput = \"markdown\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let term = Term::with_definition("API", Some("A \"contract\" between programs.".into()));
+        let output = add_term_links(
+            "The API and the API.\n",
+            &TermMatcher::new(&[term], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        assert_eq!(output.linked, ["API"]);
+        assert!(!output.content.contains('<'));
+        let events: Vec<Event> = Parser::new(&output.content).collect();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            Event::Start(Tag::Link { dest_url, title, .. })
+                if dest_url.as_ref() == "glossary.html#api"
+                    && title.as_ref() == "A \"contract\" between programs."
+        )));
+    }
+
+    #[test]
+    fn test_add_term_links_markdown_extras() {
+        let book_toml: toml::Table = concat!(
+            "[preprocessor.termlink]\noutput = \"markdown\"\nkey-terms = 1\n",
+            "terms-summary = \"append\"\npage-terms = \"json\"\n",
+        )
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml)
+            .unwrap()
+            .for_renderer("epub");
+        let output = add_term_links(
+            "# Intro\n\nThe API and REST.\n",
+            &TermMatcher::new(&[Term::new("API"), Term::new("REST")], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.md"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+
+        assert!(output.contains("**Key terms:** [API](glossary.md#api)\n"));
+        assert!(output.contains(
+            "**Terms used on this page:** [API](glossary.md#api), [REST](glossary.md#rest)"
+        ));
+        assert!(output.contains("<!-- termlink-terms: api, rest -->"));
+        assert!(!output.contains("<div") && !output.contains("<script") && !output.contains("<p"));
+    }
+
+    #[test]
+    fn test_add_term_links_max_links_per_page() {
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nmax-links-per-page = 2\nterms-summary = \"append\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let terms = [Term::new("API"), Term::new("REST"), Term::new("JSON")];
+
+        let linked = add_term_links(
+            "The API and REST.\n\nJSON and API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        assert!(linked.content.contains("glossary.html#api"));
+        assert!(linked.content.contains("glossary.html#rest"));
+        assert!(!linked.content.contains("glossary.html#json"));
+        assert!(linked.content.contains("JSON and API."));
+        assert_eq!(linked.linked, ["API", "REST"]);
+    }
+
+    #[test]
+    fn test_add_term_links_overlapping_terms() {
+        let terms = [Term::new("API"), Term::new("REST API")];
+
+        let linked = add_term_links(
+            "The REST API wraps the API.\n",
+            &TermMatcher::new(&terms, &default_config()).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &default_config(),
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        // "API" inside the longer term does not use up its first link
+        assert!(linked.content.contains(">REST API</a>"));
+        assert!(linked.content.contains("the <a href=\"glossary.html#api\""));
+        assert_eq!(linked.linked, ["API", "REST API"]);
+    }
+
+    #[test]
+    fn test_add_term_links_priority() {
+        let terms = [
+            Term::new("REST API"),
+            Term::new("REST").with_priority(1),
+            Term::new("API"),
+        ];
+        let config = default_config();
+
+        // "REST" wins over the longer "REST API" overlapping it; "API" is left to link on its own
+        let linked = add_term_links(
+            "The REST API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert!(
+            linked
+                .content
+                .contains(">REST</a> <a href=\"glossary.html#api\"")
+        );
+        assert_eq!(linked.linked, ["REST", "API"]);
+
+        // A later occurrence gives way to the same term, not to "REST API"
+        let linked = add_term_links(
+            "The REST API. The REST API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert!(linked.content.ends_with(". The REST API.\n"));
+        assert_eq!(linked.linked, ["REST", "API"]);
+    }
+
+    #[test]
+    fn test_add_term_links_repeated_longer_term() {
+        let terms = [Term::new("REST API"), Term::new("API")];
+        let config = default_config();
+
+        let linked = add_term_links(
+            "REST API and REST API and API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+        // "API" never links inside a later "REST API"
+        assert!(
+            linked
+                .content
+                .contains("</a> and REST API and <a href=\"glossary.html#api\"")
+        );
+        assert_eq!(linked.linked, ["REST API", "API"]);
+    }
+
+    #[test]
+    fn test_add_term_links_glossary_definitions() {
+        let terms = [Term::new("API"), Term::new("REST")];
+        let config = default_config().for_glossary_page();
+
+        let linked = add_term_links(
+            "# Glossary\n\nAPI\n: An API for REST services. Also REST.\n: See also: REST\n\nREST\n: A style of API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            linked.content,
+            "# Glossary\n\nAPI\n: An API for <a href=\"glossary.html#rest\" class=\"glossary-term\">REST</a> services. Also REST.\n\
+             : See also: <a href=\"glossary.html#rest\" class=\"glossary-term\">REST</a>\n\n\
+             REST\n: A style of <a href=\"glossary.html#api\" class=\"glossary-term\">API</a>.\n"
+        );
+    }
+
+    #[test]
+    fn test_add_term_links_term_overrides() {
+        let terms = [Term::new("API"), Term::new("REST"), Term::new("JSON")];
+        let book_toml: toml::Table = r#"
+[preprocessor.termlink.terms.API]
+css-class = "api-term"
+link-first-only = false
+
+[preprocessor.termlink.terms.JSON]
+disabled = true
+"#
+        .parse()
+        .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let linked = add_term_links(
+            "The API and REST.\n\nThe API, REST, and JSON.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            linked
+                .content
+                .matches(r#"class="glossary-term api-term">API</a>"#)
+                .count(),
+            2
+        );
+        assert_eq!(linked.content.matches(">REST</a>").count(), 1);
+        assert!(!linked.content.contains(">JSON</a>"));
+    }
+
+    #[test]
+    fn test_add_term_links_inline_terms() {
+        let terms = [
+            Term::with_definition("API", Some("A set of protocols.".into())).defined_inline(),
+            Term::new("RFC")
+                .with_url(Some("https://www.rfc-editor.org/".into()))
+                .defined_inline(),
+        ];
+        let config = Config::default();
+
+        let linked = add_term_links(
+            "The API and the RFC.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert!(
+            linked
+                .content
+                .contains(r#"<span title="A set of protocols." class="glossary-term">API</span>"#)
+        );
+        assert!(
+            linked
+                .content
+                .contains(r#"href="https://www.rfc-editor.org/""#)
+        );
+        assert!(!linked.content.contains("glossary.html"));
+    }
+
+    #[test]
+    fn test_add_term_links_match_filter() {
+        let terms = [Term::new("API").with_page("glossary.md")];
+        let config = Config::default();
+        let targets = BTreeMap::from([(
+            PathBuf::from("glossary.md"),
+            GlossaryTarget::Local(PathBuf::from("glossary.html")),
+        )]);
+        let matcher = TermMatcher::new(&terms, &config)
+            .unwrap()
+            .with_filter(|m: &MatchContext| {
+                assert_eq!(m.chapter, Path::new("guide/intro.md"));
+                assert_eq!(m.matched_text, "API");
+                !m.before().ends_with("REST ")
+            });
+
+        let linked = add_term_links(
+            "A REST API, then an API.\n",
+            &matcher,
+            &GlossaryHrefs::for_chapter(Path::new("guide/intro.md"), &targets, &config),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
+        // The rejected occurrence doesn't use up the first link
+        assert!(linked.content.contains("A REST API, then an <a href"));
+        assert_eq!(linked.link_counts, HashMap::from([("API".to_string(), 1)]));
+    }
 
-            // Process text in safe contexts
-            Event::Text(text) => {
-                let current_context = context_stack.last().copied().unwrap_or(Context::Normal);
+    #[test]
+    fn test_add_term_links_counts_links() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlink-first-only = false\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let terms = [Term::new("API"), Term::new("REST"), Term::new("JSON")];
 
-                if current_context == Context::Normal {
-                    // Safe to process - replace terms with links, emitting split events
-                    let events =
-                        replace_terms_to_events(text, terms, glossary_path, config, linked_terms);
-                    result.extend(events);
-                } else {
-                    // Inside code/link/heading - pass through unchanged
-                    result.push(event);
-                }
-            }
+        let linked = add_term_links(
+            "The API, REST, and \\API.\n\nAnother API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
 
-            // Pass through all other events
-            _ => {
-                result.push(event);
-            }
-        }
+        assert_eq!(
+            linked.link_counts,
+            HashMap::from([("API".to_string(), 2), ("REST".to_string(), 1)])
+        );
+        let offsets: Vec<(&str, usize)> = linked
+            .links
+            .iter()
+            .map(|link| (link.matched_text.as_str(), link.offset))
+            .collect();
+        assert_eq!(offsets, [("API", 4), ("REST", 9), ("API", 34)]);
+        assert_eq!(linked.links[0].href.as_deref(), Some("glossary.html#api"));
     }
 
-    result
-}
+    #[test]
+    fn test_add_term_links_link_first_per_section() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlink-first-only = \"per-section\"\nterms-summary = \"append\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let content = "# Title\n\nAPI and API.\n\n## Usage\n\nThe API.\n\n### Details\n\nThe API.\n\n## REST\n\nREST.\n";
 
-/// Replaces term occurrences, returning a sequence of separate Text and Html events.
-/// This avoids the issue of wrapping mixed content in a single Html event.
-fn replace_terms_to_events(
-    text: &str,
-    terms: &[&Term],
-    glossary_path: &str,
-    config: &Config,
-    linked_terms: &mut HashSet<String>,
-) -> Vec<Event<'static>> {
-    // 1. Find all matches with positions
-    let mut matches: Vec<(usize, usize, String)> = Vec::new(); // (start, end, html_link)
+        let linked = add_term_links(
+            content,
+            &TermMatcher::new(&[Term::new("API"), Term::new("REST")], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap();
 
-    for term in terms {
-        if config.link_first_only() && linked_terms.contains(term.anchor()) {
-            continue;
-        }
+        // Intro, "Usage", and the summary, but not again under the "Details" subsection
+        assert_eq!(linked.content.matches("glossary.html#api\"").count(), 3);
+        assert!(linked.content.contains("The API.\n\n## REST"));
+        assert_eq!(linked.linked, ["API", "REST"]);
+    }
 
-        let Some(regex) = build_term_regex(term, config.case_sensitive()) else {
-            continue;
+    #[test]
+    fn test_add_term_links_link_first_per_book() {
+        let terms = [Term::new("API"), Term::new("REST")];
+        let linked_before = HashSet::from([String::from("api")]);
+        let link = |config: &Config| {
+            add_term_links(
+                "The API and REST.\n",
+                &TermMatcher::new(&terms, config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                config,
+                &linked_before,
+            )
+            .unwrap()
         };
 
-        if let Some(mat) = regex.find(text) {
-            let matched_text = &text[mat.start()..mat.end()];
-            let title_attr = term
-                .definition()
-                .map(|d| format!(r#" title="{}""#, html_escape(d)))
-                .unwrap_or_default();
-            let link = format!(
-                r#"<a href="{}#{}"{} class="{}">{}</a>"#,
-                glossary_path,
-                term.anchor(),
-                title_attr,
-                config.css_class(),
-                html_escape(matched_text),
-            );
+        // Terms linked on earlier pages are ignored per page
+        assert!(
+            link(&default_config())
+                .content
+                .contains("glossary.html#api")
+        );
 
-            matches.push((mat.start(), mat.end(), link));
-            linked_terms.insert(term.anchor().to_string());
-        }
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlink-first-only = \"per-book\"\n"
+            .parse()
+            .unwrap();
+        let linked = link(&Config::from_book_toml(&book_toml).unwrap());
+        assert!(!linked.content.contains("glossary.html#api"));
+        assert!(linked.content.contains("glossary.html#rest"));
+        assert_eq!(linked.linked, ["REST"]);
+        assert_eq!(linked.linked_anchors, HashSet::from([String::from("rest")]));
     }
 
-    // 2. Sort by position
-    matches.sort_by_key(|(start, _, _)| *start);
+    #[test]
+    fn test_region_switch() {
+        assert_eq!(region_switch("<!-- termlink:off -->\n"), Some(false));
+        assert_eq!(region_switch("<!--termlink:on-->"), Some(true));
+        assert_eq!(
+            region_switch("<!-- termlink:on --> <!-- termlink:off -->"),
+            Some(false)
+        );
+        assert_eq!(region_switch("<!-- toc -->"), None);
+    }
 
-    // 3. Build events (using owned strings to avoid lifetime issues)
-    let mut events = Vec::new();
-    let mut last_end = 0;
+    #[test]
+    fn test_marker_block_ranges_custom_marker() {
+        let events: Vec<Event> =
+            Parser::new("Intro\n\n- item <!-- keep --> API\n- other API\n").collect();
+        let ranges = marker_block_ranges(&events, &["<!-- keep -->".to_string()]);
 
-    for (start, end, link) in matches {
-        // Skip overlapping matches
-        if start < last_end {
-            continue;
-        }
+        assert_eq!(ranges.len(), 1);
+        let protected_text: String = events[ranges[0].clone()]
+            .iter()
+            .filter_map(|e| match e {
+                Event::Text(t) => Some(t.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(protected_text, "item  API");
+    }
 
-        // Emit text before this match (owned)
-        if start > last_end {
-            events.push(Event::Text(CowStr::from(text[last_end..start].to_string())));
-        }
+    #[test]
+    fn test_build_link_with_glossary_lang() {
+        let book_toml: toml::Table = "[book]\nlanguage = \"nl\"\n".parse().unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
 
-        // Emit the link as HTML (owned)
-        events.push(Event::Html(CowStr::from(link)));
+        let term = Term::new("API").with_lang(Some("en".to_string()));
+        let link = build_link(&term, "API", "glossary.html", &config);
+        assert!(link.contains(r#" lang="en" hreflang="en""#));
 
-        last_end = end;
+        // A term in the book's own language declares nothing
+        let term = Term::new("API").with_lang(Some("nl".to_string()));
+        let link = build_link(&term, "API", "glossary.html", &config);
+        assert!(!link.contains("lang="));
     }
 
-    // Emit remaining text (owned)
-    if last_end < text.len() {
-        events.push(Event::Text(CowStr::from(text[last_end..].to_string())));
+    #[test]
+    fn test_build_link_without_glossary_lang() {
+        let link = build_link(&Term::new("API"), "API", "glossary.html", &default_config());
+        assert!(!link.contains("lang="));
     }
 
-    // If no matches, return original text as single event (owned)
-    if events.is_empty() {
-        events.push(Event::Text(CowStr::from(text.to_string())));
-    }
+    #[test]
+    fn test_glossary_hrefs_per_page() {
+        let targets = BTreeMap::from([
+            (
+                PathBuf::from("glossary/a-f.md"),
+                GlossaryTarget::Local(PathBuf::from("glossary/a-f.html")),
+            ),
+            (
+                PathBuf::from("glossary/p-z.md"),
+                GlossaryTarget::Local(PathBuf::from("glossary/p-z.html")),
+            ),
+        ]);
+        let hrefs =
+            GlossaryHrefs::for_chapter(Path::new("guide/intro.md"), &targets, &default_config());
 
-    events
-}
+        let api = Term::new("API").with_page("glossary/a-f.md");
+        let rest = Term::new("REST").with_page("glossary/p-z.md");
+        assert_eq!(hrefs.href(&api), "../glossary/a-f.html");
+        assert_eq!(hrefs.href(&rest), "../glossary/p-z.html");
+        assert_eq!(hrefs.href(&Term::new("XPT")), "../glossary/a-f.html");
+    }
 
-/// Builds a regex pattern for matching a term.
-fn build_term_regex(term: &Term, case_sensitive: bool) -> Option<Regex> {
-    // Get all forms to match
-    let forms: Vec<&str> = term.searchable_forms();
+    #[test]
+    fn test_add_term_links_terms_summary() {
+        let terms = vec![Term::new("API"), Term::new("REST"), Term::new("XPT")];
+        let book_toml: toml::Table = "[preprocessor.termlink]\nterms-summary = \"append\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
 
-    // Escape and join with alternation
-    let pattern_parts: Vec<String> = forms.iter().map(|f| regex::escape(f)).collect();
+        let output = add_term_links(
+            "Use REST and the API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
 
-    // Word boundary pattern
-    let pattern = format!(r"\b({})\b", pattern_parts.join("|"));
+        assert!(output.ends_with(concat!(
+            r#"<p class="termlink-terms-used"><strong>Terms used on this page:</strong> "#,
+            r#"<a href="glossary.html#api" class="glossary-term">API</a>, "#,
+            r#"<a href="glossary.html#rest" class="glossary-term">REST</a></p>"#,
+            "\n"
+        )));
+    }
 
-    RegexBuilder::new(&pattern)
-        .case_insensitive(!case_sensitive)
-        .build()
-        .ok()
-}
+    #[test]
+    fn test_add_term_links_terms_summary_prepend() {
+        let book_toml: toml::Table = "[preprocessor.termlink]\nterms-summary = \"prepend\"\nterms-summary-title = \"Begrippen\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
 
-/// Calculates the relative path from a chapter to the glossary.
-#[must_use]
-pub fn calculate_relative_path(from_chapter: &Path, to_glossary: &Path) -> String {
-    // Count directory depth of the chapter
-    let depth = from_chapter.parent().map_or(0, |p| p.components().count());
+        let output = add_term_links(
+            "Use the API.\n",
+            &TermMatcher::new(&[Term::new("API")], &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
 
-    // Build relative path
-    let prefix = "../".repeat(depth);
-    format!("{}{}", prefix, to_glossary.display())
-}
+        assert!(
+            output.starts_with(r#"<p class="termlink-terms-used"><strong>Begrippen:</strong>"#)
+        );
+    }
 
-/// Escapes HTML special characters.
-fn html_escape(text: &str) -> String {
-    text.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-}
+    #[test]
+    fn test_add_term_links_key_terms() {
+        let terms = vec![
+            Term::new("API (Application Programming Interface)"),
+            Term::new("REST"),
+            Term::new("XPT"),
+        ];
+        let book_toml: toml::Table = "[preprocessor.termlink]\nkey-terms = 2\n".parse().unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let output = add_term_links(
+            "# Intro\n\nXPT files. REST calls, more REST, and REST again. One API.\n",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
 
-    fn default_config() -> Config {
-        Config::default()
+        let heading = output.find("# Intro").unwrap();
+        let callout = output.find(r#"<div class="termlink-key-terms">"#).unwrap();
+        let body = output.find("files.").unwrap();
+        assert!(heading < callout && callout < body);
+        assert!(output.contains(concat!(
+            r#"<strong>Key terms:</strong> <a href="glossary.html#rest" class="glossary-term">REST</a>, "#,
+            r#"<a href="glossary.html#api-application-programming-interface" class="glossary-term">API</a></div>"#,
+        )));
     }
 
     #[test]
-    fn test_calculate_relative_path_same_dir() {
-        assert_eq!(
-            calculate_relative_path(Path::new("intro.md"), Path::new("glossary.html")),
-            "glossary.html"
-        );
+    fn test_add_term_links_glossary_shortcut() {
+        let link = |config: &Config| {
+            add_term_links(
+                "No terms here.\n",
+                &TermMatcher::new(&[Term::new("API")], config).unwrap(),
+                &GlossaryHrefs::single("../glossary.html"),
+                config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+        let with_js = Config::builder()
+            .additional_js(vec!["termlink.js".to_string()])
+            .build()
+            .unwrap();
+
+        assert!(link(&with_js).trim_end().ends_with(
+            r#"<span hidden data-termlink-glossary="../glossary.html" data-termlink-shortcut="g"></span>"#
+        ));
+        // Without the script to handle it, and outside HTML, nothing is added
+        assert_eq!(link(&Config::default()), "No terms here.\n");
+        assert_eq!(link(&with_js.for_renderer("epub")), "No terms here.\n");
+        assert!(!link(&with_js).contains("<script"));
     }
 
     #[test]
-    fn test_calculate_relative_path_one_level() {
+    fn test_page_terms_block() {
+        let api = Term::new("API (Application Programming Interface)");
+        let rest = Term::new("REST");
+        let linked = vec![&api, &rest];
+
         assert_eq!(
-            calculate_relative_path(Path::new("chapter/intro.md"), Path::new("glossary.html")),
-            "../glossary.html"
+            page_terms_block(
+                &linked,
+                &GlossaryHrefs::single("glossary.html"),
+                PageTerms::Comment
+            )
+            .as_deref(),
+            Some("<!-- termlink-terms: api-application-programming-interface, rest -->")
         );
-    }
-
-    #[test]
-    fn test_calculate_relative_path_two_levels() {
+        let json = page_terms_block(
+            &linked,
+            &GlossaryHrefs::single("glossary.html"),
+            PageTerms::Json,
+        )
+        .unwrap();
+        assert!(
+            json.starts_with(r#"<script type="application/json" class="termlink-page-terms">"#)
+        );
+        assert!(json.contains(r#""href":"glossary.html#rest""#));
         assert_eq!(
-            calculate_relative_path(
-                Path::new("part1/chapter1/intro.md"),
-                Path::new("glossary.html")
+            page_terms_block(
+                &linked,
+                &GlossaryHrefs::single("glossary.html"),
+                PageTerms::Off
             ),
-            "../../glossary.html"
+            None
+        );
+        assert_eq!(
+            page_terms_block(
+                &[],
+                &GlossaryHrefs::single("glossary.html"),
+                PageTerms::Json
+            ),
+            None
         );
     }
 
     #[test]
-    fn test_build_term_regex_case_insensitive() {
-        let term = Term::new("XPT");
-        let regex = build_term_regex(&term, false).unwrap();
-
-        assert!(regex.is_match("The XPT format"));
-        assert!(regex.is_match("The xpt format"));
-        assert!(regex.is_match("The Xpt format"));
+    fn test_build_link_with_citation() {
+        let term = Term::with_definition("ISO Week", Some("A week starting on Monday.".into()))
+            .with_citation(Some("ISO 8601-1:2019".into()));
+        let link = build_link(&term, "ISO Week", "glossary.html", &default_config());
+        assert!(link.contains(r#"title="A week starting on Monday.&#10;Source: ISO 8601-1:2019""#));
     }
 
     #[test]
-    fn test_build_term_regex_case_sensitive() {
-        let term = Term::new("XPT");
-        let regex = build_term_regex(&term, true).unwrap();
+    fn test_build_link_popover() {
+        let term = Term::with_definition("XPT", Some("The SAS format.".into()))
+            .with_definition_html(Some("The <em>SAS</em> format.".into()))
+            .with_citation(Some("SAS".into()));
+        let book_toml: toml::Table = "[preprocessor.termlink]\ntooltip = \"popover\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let link = build_link(&term, "XPT", "glossary.html", &config);
+        assert!(link.contains(r#"title="The SAS format.&#10;Source: SAS""#));
+        assert!(link.contains(
+            r#"data-termlink-definition="The &lt;em&gt;SAS&lt;/em&gt; format.&lt;p class=&quot;termlink-popover-source&quot;&gt;Source: SAS&lt;/p&gt;""#
+        ));
 
-        assert!(regex.is_match("The XPT format"));
-        assert!(!regex.is_match("The xpt format"));
+        let link = build_link(&term, "XPT", "glossary.html", &default_config());
+        assert!(!link.contains("data-termlink-definition"));
     }
 
     #[test]
-    fn test_build_term_regex_word_boundary() {
-        let term = Term::new("API");
-        let regex = build_term_regex(&term, false).unwrap();
+    fn test_build_link_span() {
+        let term = Term::with_definition("API", Some("A set of protocols.".into()));
+        let book_toml: toml::Table = "[preprocessor.termlink]\noutput = \"span\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
 
-        assert!(regex.is_match("The API is"));
-        assert!(!regex.is_match("The APIs are")); // Word boundary prevents partial match
+        let link = build_link(&term, "API", "glossary.html", &config);
+        assert_eq!(
+            link,
+            r#"<span title="A set of protocols." class="glossary-term">API</span>"#
+        );
     }
 
     #[test]
-    fn test_build_term_regex_with_short_name() {
+    fn test_build_link_abbr() {
         let term = Term::new("API (Application Programming Interface)");
-        let regex = build_term_regex(&term, false).unwrap();
+        let book_toml: toml::Table = "[preprocessor.termlink]\noutput = \"abbr\"\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let link = build_link(&term, "API", "glossary.html", &config);
+        assert!(link.starts_with(r#"<a href="glossary.html""#));
+        assert!(
+            link.ends_with(r#"><abbr title="Application Programming Interface">API</abbr></a>"#)
+        );
+
+        // The long form is already spelled out
+        let link = build_link(
+            &term,
+            "Application Programming Interface",
+            "glossary.html",
+            &config,
+        );
+        assert!(!link.contains("<abbr"));
 
-        assert!(regex.is_match("Use the API"));
-        assert!(regex.is_match("API (Application Programming Interface) is"));
+        let link = build_link(&term, "API", "glossary.html", &default_config());
+        assert!(!link.contains("<abbr"));
     }
 
     #[test]
-    fn test_html_escape() {
-        assert_eq!(html_escape("a < b"), "a &lt; b");
-        assert_eq!(html_escape("a & b"), "a &amp; b");
-        assert_eq!(html_escape("<script>"), "&lt;script&gt;");
-        assert_eq!(html_escape(r#"say "hello""#), "say &quot;hello&quot;");
-    }
+    fn test_replace_terms_sr_expansion() {
+        let term = Term::new("API (Application Programming Interface)");
+        let terms = [term];
+        let book_toml: toml::Table = "[preprocessor.termlink]\nsr-expansion = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
 
-    /// Helper to convert events to a concatenated string for test assertions
-    fn events_to_string(events: &[Event]) -> String {
-        events
-            .iter()
-            .map(|e| match e {
-                Event::Text(s) | Event::Html(s) => s.to_string(),
-                _ => String::new(),
-            })
-            .collect()
+        let first = events_to_string(&replace_terms_to_events(
+            "Call the API.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &mut linked,
+            &mut links,
+        ));
+        assert!(first.contains(&format!(
+            r#"API</a><span class="sr-only" style="{SR_ONLY_STYLE}"> (Application Programming Interface)</span>."#
+        )));
+
+        // Only the first linked occurrence on the page is expanded
+        let second = events_to_string(&replace_terms_to_events(
+            "The API again.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &mut linked,
+            &mut links,
+        ));
+        assert!(!second.contains("sr-only"));
     }
 
     #[test]
-    fn test_replace_terms_link_first_only() {
-        let term = Term::new("XPT");
-        let terms: Vec<&Term> = vec![&term];
-        let config = default_config();
+    fn test_replace_terms_expand_acronyms() {
+        let terms = [Term::new("API (Application Programming Interface)")];
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nexpand-acronyms = true\nsr-expansion = true\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
         let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
 
-        let events = replace_terms_to_events(
-            "XPT is great. XPT is used.",
-            &terms,
-            "g.html",
+        let first = events_to_string(&replace_terms_to_events(
+            "Call the API.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
             &config,
             &mut linked,
-        );
-        let result = events_to_string(&events);
+            &mut links,
+        ));
+        assert!(first.ends_with("API</a> (Application Programming Interface)."));
+        assert!(!first.contains("sr-only"));
 
-        // Should only link first occurrence
-        assert!(result.contains(r#"<a href="g.html#xpt""#));
-        assert_eq!(result.matches("glossary-term").count(), 1);
+        // Later occurrences on the page stay the bare acronym
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nexpand-acronyms = true\nlink-first-only = false\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let matcher = TermMatcher::new(&terms, &config).unwrap();
+        let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
+        let mut link = |text: &str| {
+            events_to_string(&replace_terms_to_events(
+                text,
+                &matcher,
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &mut linked,
+                &mut links,
+            ))
+        };
+        assert!(link("The API.").contains("API</a> (Application Programming Interface)."));
+        assert!(link("The API again.").ends_with("API</a> again."));
     }
 
     #[test]
-    fn test_replace_terms_with_tooltip() {
-        let term =
-            Term::with_definition("API", Some("Application Programming Interface".to_string()));
-        let terms: Vec<&Term> = vec![&term];
-        let config = default_config();
+    fn test_replace_terms_expand_acronyms_markdown() {
+        let terms = [Term::new("API (Application Programming Interface)")];
+        let book_toml: toml::Table =
+            "[preprocessor.termlink]\nexpand-acronyms = true\noutput = \"markdown\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+
+        let result = events_to_string(&replace_terms_to_events(
+            "Call the API.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &config,
+            &mut HashSet::new(),
+            &mut PageLinks::new(None),
+        ));
+        assert!(result.contains("(Application Programming Interface)."));
+    }
+
+    #[test]
+    fn test_replace_terms_link_to_first_mention() {
+        let term = Term::new("API");
+        let terms = [term];
+        let book_toml: toml::Table = "[preprocessor.termlink]\nlink-to-first-mention = true\n"
+            .parse()
+            .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        let hrefs = GlossaryHrefs::single("glossary.html");
         let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
 
-        let events = replace_terms_to_events(
-            "Use the API for data access.",
-            &terms,
-            "glossary.html",
+        let first = events_to_string(&replace_terms_to_events(
+            "The API and the API again.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &hrefs,
             &config,
             &mut linked,
+            &mut links,
+        ));
+        assert!(first.contains(r#"<a href="glossary.html#api" id="termlink-api""#));
+        assert!(
+            first.contains(r##"the <a href="#termlink-api" class="glossary-term">API</a> again"##)
         );
-        let result = events_to_string(&events);
 
-        // Should include title attribute with definition
-        assert!(result.contains(r#"title="Application Programming Interface""#));
-        assert!(result.contains(r#"<a href="glossary.html#api""#));
-        assert!(result.contains("class=\"glossary-term\""));
+        let second = events_to_string(&replace_terms_to_events(
+            "More API.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &hrefs,
+            &config,
+            &mut linked,
+            &mut links,
+        ));
+        assert_eq!(
+            second,
+            r##"More <a href="#termlink-api" class="glossary-term">API</a>."##
+        );
     }
 
     #[test]
-    fn test_replace_terms_without_tooltip() {
-        let term = Term::new("API"); // No definition
-        let terms: Vec<&Term> = vec![&term];
+    fn test_replace_terms_sr_expansion_disabled() {
+        let term = Term::new("API (Application Programming Interface)");
+        let terms = [term];
         let config = default_config();
         let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
 
-        let events = replace_terms_to_events(
-            "Use the API for data access.",
-            &terms,
-            "glossary.html",
+        let result = events_to_string(&replace_terms_to_events(
+            "Call the API.",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
             &config,
             &mut linked,
+            &mut links,
+        ));
+        assert!(!result.contains("sr-only"));
+    }
+
+    #[test]
+    fn test_output_span_and_bold() {
+        let terms = vec![Term::with_definition(
+            "API",
+            Some("A set of protocols.".to_string()),
+        )];
+        let render = |output: &str| {
+            let book_toml: toml::Table =
+                format!("[preprocessor.termlink]\noutput = \"{output}\"\n")
+                    .parse()
+                    .unwrap();
+            let config = Config::from_book_toml(&book_toml).unwrap();
+            add_term_links(
+                "The API and the API.",
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::new(),
+            )
+            .unwrap()
+            .content
+        };
+
+        let span = render("span");
+        assert_eq!(
+            span,
+            r#"The <span title="A set of protocols." class="glossary-term">API</span> and the API."#
         );
-        let result = events_to_string(&events);
+        assert!(!span.contains("href"));
 
-        // Should NOT include title attribute
-        assert!(!result.contains("title="));
-        assert!(result.contains(r#"<a href="glossary.html#api""#));
+        let bold = render("bold");
+        assert!(
+            bold.contains(
+                r#"<strong title="A set of protocols." class="glossary-term">API</strong>"#
+            )
+        );
     }
 
     #[test]
     fn test_replace_terms_with_aliases() {
         let term = Term::new("REST").with_aliases(vec!["RESTful".to_string()]);
-        let terms: Vec<&Term> = vec![&term];
+        let terms = [term];
         let config = default_config();
         let mut linked = HashSet::new();
+        let mut links = PageLinks::new(None);
 
         let events = replace_terms_to_events(
             "This is a RESTful service.",
-            &terms,
-            "glossary.html",
+            &TermMatcher::new(&terms, &config).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
             &config,
             &mut linked,
+            &mut links,
         );
         let result = events_to_string(&events);
 
@@ -420,4 +3941,252 @@ mod tests {
         assert!(result.contains(r#"<a href="glossary.html#rest""#));
         assert!(result.contains("RESTful</a>"));
     }
+
+    #[test]
+    fn test_term_with_url_links_to_url() {
+        let terms = vec![
+            Term::new("RFC 7231").with_url(Some("https://example.com/rfc?a=1&b=2".to_string())),
+        ];
+        let output = add_term_links(
+            "See RFC 7231.",
+            &TermMatcher::new(&terms, &default_config()).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &default_config(),
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+        assert_eq!(
+            output,
+            r#"See <a href="https://example.com/rfc?a=1&amp;b=2" target="_blank" rel="noopener noreferrer" class="glossary-term">RFC 7231</a>."#
+        );
+    }
+
+    #[test]
+    fn test_link_class_with_category() {
+        let config = default_config();
+        let term = Term::new("XPT").with_category(Some("File formats".to_string()));
+        assert_eq!(
+            link_class(&term, &config),
+            "glossary-term glossary-term--file-formats"
+        );
+        assert_eq!(link_class(&Term::new("API"), &config), "glossary-term");
+
+        let book_toml: toml::Table =
+            "[preprocessor.termlink.category-classes]\n\"File formats\" = \"format\"\n"
+                .parse()
+                .unwrap();
+        let config = Config::from_book_toml(&book_toml).unwrap();
+        assert_eq!(link_class(&term, &config), "glossary-term format");
+
+        let term = Term::new("XML-RPC").deprecated("");
+        assert_eq!(
+            link_class(&term, &config),
+            "glossary-term glossary-term--deprecated"
+        );
+    }
+
+    #[test]
+    fn test_link_chapter_keeps_prose() {
+        let terms = vec![Term::new("API")];
+        let content = "# Intro\n\nThe API is *here*.\n\n```\nAPI\n```\n";
+        let result = link_chapter(
+            content,
+            &TermMatcher::new(&terms, &default_config()).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &default_config(),
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert!(result.content.contains(r#"<a href="glossary.html#api""#));
+        assert_eq!(dropped_prose(content, &result.content), None);
+        assert_eq!(result.linked, ["API"]);
+    }
+
+    #[test]
+    fn test_dropped_prose() {
+        assert_eq!(
+            dropped_prose("The API is here.", "The <a>API</a> is here."),
+            None
+        );
+        assert_eq!(dropped_prose("The API is here.", "The is here."), Some(4));
+        assert_eq!(
+            dropped_prose("# Intro\n\nThe `API`.\n", "# Intro\n\nThe .\n"),
+            Some(13)
+        );
+    }
+
+    #[test]
+    fn test_link_chapter_without_matches() {
+        // Formatting the markdown writer would normalize
+        let content = "Title\n=====\n\n* item\n+ other\n\n| a |b|\n|---|-|\n\n1) one\n\n***\n";
+        let linked = link_chapter(
+            content,
+            &TermMatcher::new(&[Term::new("API")], &default_config()).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &default_config(),
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        assert_eq!(linked.content, content);
+        assert!(linked.linked.is_empty());
+    }
+
+    #[test]
+    fn test_link_chapter_keeps_markdown_as_written() {
+        // Rewriting this chapter used to indent the lazy footnote continuation,
+        // turning the definition list into footnote text
+        let content = "API[^1]\n\n[^1]: API note\nAPI\n: A definition.\n";
+        let output = link_chapter(
+            content,
+            &TermMatcher::new(&[Term::new("API")], &default_config()).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &default_config(),
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+        assert_eq!(
+            output.replace(
+                r#"<a href="glossary.html#api" class="glossary-term">API</a>"#,
+                "API"
+            ),
+            content
+        );
+
+        // Tables, escapes, and line wrapping are kept byte for byte
+        let content = "| a |  b  |\n|:--|----:|\n| 1 | \\* API |\n\nSome *text*\nwrapped  here.\n";
+        let output = link_chapter(
+            content,
+            &TermMatcher::new(&[Term::new("API")], &default_config()).unwrap(),
+            &GlossaryHrefs::single("glossary.html"),
+            &default_config(),
+            &HashSet::new(),
+        )
+        .unwrap()
+        .content;
+        assert_eq!(
+            output,
+            content.replace(
+                " API ",
+                r#" <a href="glossary.html#api" class="glossary-term">API</a> "#
+            )
+        );
+    }
+
+    /// Markdown fragments that exercise every construct the linker treats specially.
+    const FRAGMENTS: &[&str] = &[
+        "API",
+        "REST",
+        "RESTful APIs",
+        "the API docs",
+        " ",
+        "\n",
+        "\n\n",
+        "`API`",
+        "```\nAPI in code\n```\n",
+        "    indented API\n",
+        "[API](https://example.com)",
+        "[API][ref]\n\n[ref]: https://example.com\n",
+        "![API](api.png)",
+        "<https://api.example.com>",
+        "# API heading\n",
+        "Heading\n---\n",
+        "{{#include api.md}}",
+        "{{API}}",
+        "<!-- API -->",
+        "<div>API</div>\n",
+        "<span>API</span>",
+        "*API*",
+        "**REST**",
+        "~~API~~",
+        "\\*not emphasis\\*",
+        "*",
+        "~",
+        "&amp; &lt;API&gt;",
+        "| API | REST |\n|-----|------|\n| a | API |\n",
+        "API[^1]\n\n[^1]: API note\n",
+        "- API\n- REST\n",
+        "1. API\n2. REST\n",
+        "- [ ] API task\n",
+        "> API quote\n",
+        "API\n: A definition.\n",
+        "$API$",
+        "\u{e9}\u{4e2d}API\u{1f600}",
+    ];
+
+    fn fuzz_terms() -> Vec<Term> {
+        vec![
+            Term::with_definition("API", Some("A set of <protocols>.".to_string())),
+            Term::new("REST").with_aliases(vec!["RESTful".to_string()]),
+        ]
+    }
+
+    /// Checks whether `content` is a known case where linking can change how
+    /// emphasis pairs, so that `link_chapter` keeps the chapter as written.
+    fn known_fallback(content: &str) -> bool {
+        // A delimiter run between punctuation and a term (`)*API`) can only
+        // open emphasis, and one inside a word (`API*s`) both open and close;
+        // next to the markup of a link either can do something else
+        let is_delimiter = |c: char| matches!(c, '*' | '_' | '~');
+        let punctuation = |c: Option<&char>| c.is_some_and(char::is_ascii_punctuation);
+        let word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric());
+        let chars: Vec<char> = content.chars().collect();
+        (0..chars.len())
+            .filter(|&i| is_delimiter(chars[i]) && (i == 0 || !is_delimiter(chars[i - 1])))
+            .any(|start| {
+                let end = start
+                    + chars[start..]
+                        .iter()
+                        .take_while(|&&c| is_delimiter(c))
+                        .count();
+                let (before, after) = (start.checked_sub(1).map(|i| &chars[i]), chars.get(end));
+                (word(before) && (word(after) || punctuation(after)))
+                    || (punctuation(before) && word(after))
+            })
+    }
+
+    fn fuzz_config(variant: u8) -> Config {
+        let options = match variant {
+            0 => "",
+            1 => "link-first-only = false\n",
+            2 => "link-to-first-mention = true\nterms-summary = \"append\"\n",
+            3 => "output = \"markdown\"\nterms-summary = \"prepend\"\n",
+            _ => concat!(
+                "key-terms = 2\npage-terms = \"json\"\n",
+                "[output.html]\nadditional-js = [\"termlink.js\"]\n",
+            ),
+        };
+        let book_toml: toml::Table = format!("[preprocessor.termlink]\n{options}")
+            .parse()
+            .unwrap();
+        Config::from_book_toml(&book_toml).unwrap()
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn fuzz_add_term_links_never_drops_prose(
+            fragments in proptest::collection::vec(proptest::sample::select(FRAGMENTS), 0..24),
+            noise in "\\PC{0,16}",
+            variant in 0u8..5,
+        ) {
+            // The linking itself, not `link_chapter`, which would hide a loss behind its fallback
+            let content = format!("{}{noise}", fragments.concat());
+            let terms = fuzz_terms();
+            let config = fuzz_config(variant);
+            let output = add_term_links(
+                &content,
+                &TermMatcher::new(&terms, &config).unwrap(),
+                &GlossaryHrefs::single("glossary.html"),
+                &config,
+                &HashSet::<String>::new(),
+            );
+            proptest::prop_assert!(output.is_ok(), "linking failed: {:?}", output.err());
+            let output = output.unwrap().content;
+            if !known_fallback(&content) {
+                proptest::prop_assert_eq!(dropped_prose(&content, &output), None, "{}", output);
+            }
+        }
+    }
 }
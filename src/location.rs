@@ -0,0 +1,127 @@
+//! Line and column positions in source files, for diagnostics.
+
+use std::fmt;
+use std::path::Path;
+
+/// A line and column in a text, both counted from 1; columns count characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Line number.
+    pub line: usize,
+    /// Column number.
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// The line starts of a text, to find the positions of byte offsets in it.
+#[derive(Debug)]
+pub struct LineIndex<'a> {
+    /// The text indexed.
+    text: &'a str,
+    /// Byte offset of the start of each line.
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Indexes the lines of `text`.
+    #[must_use]
+    pub fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, starts }
+    }
+
+    /// Returns the position of byte `offset`, clamped to the text.
+    #[must_use]
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let start = self.starts[line - 1];
+        Position {
+            line,
+            column: self.text[start..offset].chars().count() + 1,
+        }
+    }
+}
+
+/// An error about the text at a byte offset of a chapter.
+///
+/// Raised as the root of an [`anyhow::Error`], so the caller holding the
+/// whole chapter can report where it happened with [`error_location`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceError {
+    /// Byte offset in the chapter of the text the error is about.
+    pub offset: usize,
+    /// What went wrong.
+    pub message: String,
+}
+
+impl SourceError {
+    /// Creates an error about the text at `offset`.
+    pub fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SourceError {}
+
+/// Describes where `error` happened in the chapter at `path` with `content`:
+/// `chapter.md:42:17` for a [`SourceError`], the path alone otherwise.
+#[must_use]
+pub fn error_location(error: &anyhow::Error, path: &Path, content: &str) -> String {
+    error.downcast_ref::<SourceError>().map_or_else(
+        || path.display().to_string(),
+        |source| {
+            let position = LineIndex::new(content).position(source.offset);
+            format!("{}:{position}", path.display())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let index = LineIndex::new("# Intro\n\nThe café API.\n");
+        assert_eq!(index.position(0), Position { line: 1, column: 1 });
+        assert_eq!(index.position(7), Position { line: 1, column: 8 });
+        assert_eq!(index.position(8), Position { line: 2, column: 1 });
+        // Columns count characters, not bytes
+        assert_eq!(index.position(19).to_string(), "3:10");
+        assert_eq!(index.position(100).to_string(), "4:1");
+    }
+
+    #[test]
+    fn test_error_location() {
+        let content = "# Intro\n\nThe API.\n";
+        let error = anyhow::Error::new(SourceError::new(13, "dropped text"));
+        assert_eq!(
+            error_location(&error, Path::new("intro.md"), content),
+            "intro.md:3:5"
+        );
+        assert_eq!(
+            error_location(&anyhow::anyhow!("panicked"), Path::new("intro.md"), content),
+            "intro.md"
+        );
+    }
+}
@@ -1,36 +1,271 @@
 //! CLI entry point for mdbook-termlink preprocessor.
 
+use std::env;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::process;
 
-use anyhow::Result;
+use anyhow::{Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use mdbook_preprocessor::{Preprocessor, parse_input};
 
-use mdbook_termlink::TermlinkPreprocessor;
+use mdbook_termlink::commands::{self, RenameOptions};
+use mdbook_termlink::{BookSource, Config, TermlinkPreprocessor};
 
-fn main() {
-    env_logger::init();
+/// mdBook preprocessor that auto-links glossary terms throughout documentation.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Log every link added to this file as JSON lines, in place of `replacement-log`
+    #[arg(long, value_name = "FILE")]
+    replacement_log: Option<PathBuf>,
+    /// Print the changes to each chapter as a diff on stderr and leave the book unchanged
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Check whether a renderer is supported (called by mdBook)
+    Supports {
+        /// Name of the renderer
+        renderer: String,
+    },
+    /// Rename a glossary term in the glossary, book.toml aliases, and optionally chapters
+    RenameTerm {
+        /// Current name of the term, exactly as in the glossary
+        old: String,
+        /// New name for the term
+        new: String,
+        #[command(flatten)]
+        book: BookArgs,
+        /// Also rewrite occurrences of the old name in chapter prose
+        #[arg(long)]
+        rewrite_chapters: bool,
+        /// Print a diff of the changes instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the resolved term, anchor, and link target of every glossary entry
+    Anchors {
+        #[command(flatten)]
+        book: BookArgs,
+    },
+    /// Check that the rendered glossary has every anchor termlink links to
+    CheckAnchors {
+        #[command(flatten)]
+        book: BookArgs,
+    },
+    /// Explain why each occurrence of a term in a chapter is or isn't linked
+    Explain {
+        /// The term, by name, short form, or alias
+        term: String,
+        /// The chapter, relative to the current directory or to src
+        file: PathBuf,
+        #[command(flatten)]
+        book: BookArgs,
+    },
+    /// Print glossary terms as JSON for editor completion and hover previews
+    LspData {
+        #[command(flatten)]
+        book: BookArgs,
+    },
+    /// Print the parsed glossary as JSON or CSV for other tools
+    Export {
+        #[command(flatten)]
+        book: BookArgs,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+    },
+    /// Set a book up for termlink: config section, starter glossary, and hover card assets
+    Install {
+        #[command(flatten)]
+        book: BookArgs,
+        /// Print a diff of the changes instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
 
-    let args: Vec<String> = std::env::args().collect();
+/// Output formats of the `export` subcommand.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// A versioned JSON document
+    Json,
+    /// One row per term, with the `csv-columns` headers
+    Csv,
+}
+
+/// Options locating the book for the standalone subcommands.
+#[derive(Debug, Args)]
+struct BookArgs {
+    /// Root directory of the book (containing book.toml)
+    #[arg(long, default_value = ".")]
+    book: PathBuf,
+    /// Read the termlink configuration from this file instead of book.toml
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
 
-    // Handle "supports <renderer>" check
-    if args.len() >= 3 && args[1] == "supports" {
-        let renderer = &args[2];
-        // Only support HTML renderer
-        process::exit(i32::from(renderer != "html"));
+impl BookArgs {
+    /// Opens the book these options point at.
+    fn open(&self) -> Result<BookSource> {
+        let source = BookSource::open(&self.book, self.config.as_deref())?;
+        apply_log_setting(source.config());
+        Ok(source)
     }
+}
+
+fn main() {
+    // Without RUST_LOG, the `log` setting in book.toml picks the level once the config is read
+    let mut logger = env_logger::Builder::from_default_env();
+    if env::var_os("RUST_LOG").is_none() {
+        logger.filter_module("mdbook_termlink", log::LevelFilter::Trace);
+    }
+    logger.init();
+    apply_log_setting(&Config::default());
+
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        // Handle "supports <renderer>" check
+        Some(Command::Supports { renderer }) => {
+            process::exit(i32::from(!mdbook_termlink::supports_renderer(&renderer)));
+        }
+        Some(Command::RenameTerm {
+            old,
+            new,
+            book,
+            rewrite_chapters,
+            dry_run,
+        }) => rename_term(
+            &book,
+            &old,
+            &new,
+            RenameOptions { rewrite_chapters },
+            dry_run,
+        ),
+        Some(Command::Anchors { book }) => anchors(&book),
+        Some(Command::CheckAnchors { book }) => check_anchors(&book),
+        Some(Command::Explain { term, file, book }) => explain(&book, &term, &file),
+        Some(Command::LspData { book }) => lsp_data(&book),
+        Some(Command::Export { book, format }) => export(&book, format),
+        Some(Command::Install { book, dry_run }) => install(&book, dry_run),
+        // Run preprocessing
+        None => run(cli.replacement_log, cli.dry_run),
+    };
 
-    // Run preprocessing
-    if let Err(e) = run() {
+    if let Err(e) = result {
         eprintln!("Error: {e:?}");
         process::exit(1);
     }
 }
 
-fn run() -> Result<()> {
+fn run(replacement_log: Option<PathBuf>, dry_run: bool) -> Result<()> {
     let (ctx, book) = parse_input(io::stdin())?;
-    let preprocessor = TermlinkPreprocessor::new(&ctx)?;
+    let mut preprocessor = TermlinkPreprocessor::new(&ctx)?;
+    if let Some(path) = replacement_log {
+        preprocessor = preprocessor.with_replacement_log(path);
+    }
+    if dry_run {
+        preprocessor = preprocessor.with_dry_run();
+    }
+    apply_log_setting(preprocessor.config());
     let processed = preprocessor.run(&ctx, book)?;
     serde_json::to_writer(io::stdout(), &processed)?;
     Ok(())
 }
+
+/// Applies the `log` setting, unless `RUST_LOG` takes precedence.
+fn apply_log_setting(config: &Config) {
+    if env::var_os("RUST_LOG").is_none() {
+        log::set_max_level(config.log().filter());
+    }
+}
+
+fn rename_term(
+    book: &BookArgs,
+    old: &str,
+    new: &str,
+    options: RenameOptions,
+    dry_run: bool,
+) -> Result<()> {
+    let source = book.open()?;
+    let changes = commands::rename_term(&source, old, new, options)?;
+
+    for change in &changes {
+        if dry_run {
+            print!("{}", change.unified_diff());
+        } else {
+            change.write()?;
+            println!("Updated {}", change.path().display());
+        }
+    }
+
+    Ok(())
+}
+
+fn anchors(book: &BookArgs) -> Result<()> {
+    let source = book.open()?;
+    print!("{}", commands::anchor_table(&source)?);
+    Ok(())
+}
+
+fn check_anchors(book: &BookArgs) -> Result<()> {
+    let source = book.open()?;
+    let missing = commands::check_anchors(&source)?;
+    for anchor in &missing {
+        println!("{anchor}");
+    }
+    if !missing.is_empty() {
+        bail!(
+            "{} glossary anchor(s) missing from the rendered glossary",
+            missing.len()
+        );
+    }
+    println!("All glossary anchors found");
+    Ok(())
+}
+
+fn explain(book: &BookArgs, term: &str, file: &Path) -> Result<()> {
+    let source = book.open()?;
+    println!("{}", commands::explain(&source, term, file)?);
+    Ok(())
+}
+
+fn export(book: &BookArgs, format: ExportFormat) -> Result<()> {
+    let source = book.open()?;
+    match format {
+        ExportFormat::Json => println!("{}", commands::export_json(&source)?),
+        ExportFormat::Csv => print!("{}", commands::export_csv(&source)?),
+    }
+    Ok(())
+}
+
+fn install(book: &BookArgs, dry_run: bool) -> Result<()> {
+    let source = book.open()?;
+    let changes = commands::install(&source)?;
+    if changes.is_empty() {
+        println!("termlink is already installed");
+    }
+
+    for change in &changes {
+        if dry_run {
+            print!("{}", change.unified_diff());
+        } else {
+            change.write()?;
+            println!("Updated {}", change.path().display());
+        }
+    }
+
+    Ok(())
+}
+
+fn lsp_data(book: &BookArgs) -> Result<()> {
+    let source = book.open()?;
+    println!("{}", commands::lsp_data(&source)?);
+    Ok(())
+}
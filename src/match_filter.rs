@@ -0,0 +1,92 @@
+//! Custom rules deciding which occurrences of terms are linked.
+
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::glossary::Term;
+
+/// An occurrence of a term about to be linked.
+#[derive(Debug, Clone)]
+pub struct MatchContext<'a> {
+    /// The term found.
+    pub term: &'a Term,
+    /// The occurrence, as written.
+    pub matched_text: &'a str,
+    /// The text the occurrence was found in: a paragraph, list item, or table
+    /// cell, possibly split around inline markup.
+    pub text: &'a str,
+    /// Byte range of the occurrence in `text`.
+    pub range: Range<usize>,
+    /// Path of the chapter, relative to `src` (empty when linking text on its own).
+    pub chapter: &'a Path,
+}
+
+impl MatchContext<'_> {
+    /// Returns the text before the occurrence.
+    #[must_use]
+    pub fn before(&self) -> &str {
+        &self.text[..self.range.start]
+    }
+
+    /// Returns the text after the occurrence.
+    #[must_use]
+    pub fn after(&self) -> &str {
+        &self.text[self.range.end..]
+    }
+}
+
+/// Decides whether occurrences of terms are linked, beyond termlink's own rules.
+///
+/// The filter is consulted for every occurrence that would otherwise be
+/// linked; one it rejects stays plain text, and does not use up the term's
+/// first link on the page. Closures taking a [`MatchContext`] are filters:
+///
+/// ```
+/// use mdbook_termlink::{MatchContext, TermMatcher};
+/// # use mdbook_termlink::{Config, Term};
+/// # let terms = [Term::new("API")];
+/// # let config = Config::default();
+///
+/// // Leave "API" alone in "REST API"
+/// let matcher = TermMatcher::new(&terms, &config)?.with_filter(|m: &MatchContext| {
+///     !(m.term.name() == "API" && m.before().ends_with("REST "))
+/// });
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub trait MatchFilter: Send + Sync {
+    /// Returns whether the occurrence is linked.
+    fn allows(&self, occurrence: &MatchContext<'_>) -> bool;
+}
+
+impl<F> MatchFilter for F
+where
+    F: Fn(&MatchContext<'_>) -> bool + Send + Sync,
+{
+    fn allows(&self, occurrence: &MatchContext<'_>) -> bool {
+        self(occurrence)
+    }
+}
+
+/// A match filter shared by the matchers of a book.
+#[derive(Clone)]
+pub struct SharedFilter(Arc<dyn MatchFilter>);
+
+impl SharedFilter {
+    /// Wraps `filter` for sharing.
+    pub fn new(filter: impl MatchFilter + 'static) -> Self {
+        Self(Arc::new(filter))
+    }
+
+    /// Returns whether the filter links the occurrence.
+    pub fn allows(&self, occurrence: &MatchContext<'_>) -> bool {
+        self.0.allows(occurrence)
+    }
+}
+
+impl fmt::Debug for SharedFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedFilter(..)")
+    }
+}
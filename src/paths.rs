@@ -0,0 +1,151 @@
+//! Path helpers shared by glossary resolution and link generation.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Lexically resolves `.` and `..` components of a relative path.
+///
+/// Chapter paths come from `SUMMARY.md` as written, so `./guide/intro.md` or
+/// `guide/../reference/glossary.md` are possible. Nothing is read from disk;
+/// the path a chapter is rendered at is what matters for links, not where a
+/// symlink points. `..` at the top is dropped, as the book root is the
+/// outermost directory a chapter can be rendered in.
+#[must_use]
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Formats a relative path for use in a URL, always with `/` separators.
+///
+/// `Path::display` would use `\` on Windows, which browsers do not treat as
+/// a path separator in links.
+#[must_use]
+pub fn to_url_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            Component::ParentDir => Some("..".into()),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the path mdBook gives a chapter: a `README.md` becomes the `index.md` of its directory.
+///
+/// mdBook's built-in index preprocessor renames these chapters, usually
+/// before termlink sees them, and renders them as `index.html`. The file
+/// name is matched case-insensitively, as mdBook does.
+#[must_use]
+pub fn index_page(path: &Path) -> PathBuf {
+    if path
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("readme"))
+    {
+        path.with_file_name("index.md")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Parses a path written in `book.toml`, accepting `\` as a separator on every platform.
+///
+/// A book configured on Windows then builds the same elsewhere. Unlike
+/// [`normalize`], `..` at the top is kept: a configured path may leave the
+/// `src` directory, such as a glossary shared between several books.
+#[must_use]
+pub fn from_config(path: &str) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(&path.replace('\\', "/")).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(Path::new("./guide/intro.md")),
+            PathBuf::from("guide/intro.md")
+        );
+        assert_eq!(
+            normalize(Path::new("guide/../reference/glossary.md")),
+            PathBuf::from("reference/glossary.md")
+        );
+        assert_eq!(
+            normalize(Path::new("../README.md")),
+            PathBuf::from("README.md")
+        );
+    }
+
+    #[test]
+    fn test_to_url_path() {
+        let path: PathBuf = ["reference", "terms", "glossary.html"].iter().collect();
+        assert_eq!(to_url_path(&path), "reference/terms/glossary.html");
+        assert_eq!(to_url_path(Path::new("./glossary.html")), "glossary.html");
+    }
+
+    #[test]
+    fn test_index_page() {
+        assert_eq!(
+            index_page(Path::new("reference/README.md")),
+            PathBuf::from("reference/index.md")
+        );
+        assert_eq!(
+            index_page(Path::new("Readme.md")),
+            PathBuf::from("index.md")
+        );
+        assert_eq!(
+            index_page(Path::new("reference/readme-first.md")),
+            PathBuf::from("reference/readme-first.md")
+        );
+    }
+
+    #[test]
+    fn test_from_config_accepts_backslashes() {
+        assert_eq!(
+            from_config(r"reference\glossary.md"),
+            PathBuf::from("reference/glossary.md")
+        );
+        assert_eq!(
+            from_config("./reference/glossary.md"),
+            PathBuf::from("reference/glossary.md")
+        );
+    }
+
+    #[test]
+    fn test_from_config_keeps_leading_parent_dirs() {
+        assert_eq!(
+            from_config("../../shared/./glossary.md"),
+            PathBuf::from("../../shared/glossary.md")
+        );
+        assert_eq!(
+            from_config("reference/../../shared/glossary.md"),
+            PathBuf::from("../shared/glossary.md")
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Progress reporting while walking every chapter of a book.
+
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Reports chapters processed, and the links inserted into them.
+///
+/// Draws a progress bar when stderr is a terminal, and falls back to plain
+/// log lines otherwise (CI logs, redirected output).
+#[derive(Debug)]
+pub struct Progress {
+    /// The bar, when drawing to a terminal.
+    bar: Option<ProgressBar>,
+    /// Links inserted so far.
+    links: usize,
+}
+
+impl Progress {
+    /// Starts reporting progress over `chapters` chapters.
+    #[must_use]
+    pub fn new(chapters: usize) -> Self {
+        let bar = io::stderr().is_terminal().then(|| {
+            let bar = ProgressBar::new(chapters as u64);
+            if let Ok(style) =
+                ProgressStyle::with_template("{bar:30} {pos}/{len} chapters {prefix}  {wide_msg}")
+            {
+                bar.set_style(style);
+            }
+            bar
+        });
+        Self { bar, links: 0 }
+    }
+
+    /// Records that `path` has been processed, with `links` links inserted into it.
+    pub fn chapter_done(&mut self, path: &Path, links: usize) {
+        self.links += links;
+        match &self.bar {
+            Some(bar) => {
+                if self.links > 0 {
+                    bar.set_prefix(format!("({} links)", self.links));
+                }
+                bar.set_message(path.display().to_string());
+                bar.inc(1);
+            }
+            None if links > 0 => log::debug!("Processed {} ({links} links)", path.display()),
+            None => log::debug!("Processed {}", path.display()),
+        }
+    }
+
+    /// Returns the number of links inserted so far.
+    #[must_use]
+    pub const fn links(&self) -> usize {
+        self.links
+    }
+
+    /// Clears the bar and logs `summary`.
+    pub fn finish(self, summary: &str) {
+        if let Some(bar) = self.bar {
+            bar.finish_and_clear();
+        }
+        log::info!("{summary}");
+    }
+}
@@ -0,0 +1,207 @@
+//! Scanning of raw HTML written in chapters.
+
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Elements whose content is never linked when written as raw HTML.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "a", "code", "kbd", "math", "pre", "samp", "script", "style", "svg", "textarea",
+];
+
+/// Classes of the elements other preprocessors expand their fenced blocks
+/// into, whose content is never linked: mdbook-admonish's
+/// `<div class="admonition ...">` and mdbook-mermaid's `<pre class="mermaid">`.
+const SKIPPED_CLASSES: &[&str] = &["admonition", "mermaid"];
+
+/// Matches the `class` attribute of a tag, capturing its value.
+static CLASS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\sclass\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#)
+        .expect("class regex is valid")
+});
+
+/// Follows raw HTML across the events of a chapter.
+///
+/// An element or comment may open in one event and close in a later one,
+/// as in an HTML block spanning several lines, or inline HTML such as
+/// `<code>API</code>` with markdown text between the tags.
+#[derive(Debug, Default)]
+pub struct HtmlScanner {
+    /// Whether a comment is open.
+    in_comment: bool,
+    /// Open elements whose content is never linked, innermost last, each
+    /// with the number of elements of the same name open inside it.
+    skipped: Vec<(String, usize)>,
+}
+
+impl HtmlScanner {
+    /// Returns true if the text at this point lies inside an element that is never linked.
+    pub const fn in_skipped_element(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+
+    /// Scans the next piece of raw HTML.
+    ///
+    /// Returns the ranges of the text in `html` that may be linked: outside
+    /// tags, comments, and skipped elements.
+    pub fn scan(&mut self, html: &str) -> Vec<Range<usize>> {
+        let mut texts: Vec<Range<usize>> = Vec::new();
+        let mut pos = 0;
+        while pos < html.len() {
+            let rest = &html[pos..];
+            if self.in_comment {
+                let Some(end) = rest.find("-->") else {
+                    break;
+                };
+                self.in_comment = false;
+                pos += end + 3;
+            } else if rest.starts_with("<!--") {
+                self.in_comment = true;
+                pos += 4;
+            } else if let Some(len) = tag_len(rest) {
+                self.on_tag(&rest[..len]);
+                pos += len;
+            } else {
+                // Text up to the next `<`, which may be a lone `<` itself
+                let first = rest.chars().next().map_or(1, char::len_utf8);
+                let end = rest[first..]
+                    .find('<')
+                    .map_or(html.len(), |i| pos + first + i);
+                if !self.in_skipped_element() {
+                    match texts.last_mut() {
+                        Some(last) if last.end == pos => last.end = end,
+                        _ => texts.push(pos..end),
+                    }
+                }
+                pos = end;
+            }
+        }
+        texts
+    }
+
+    /// Opens or closes a skipped element for `tag`.
+    fn on_tag(&mut self, tag: &str) {
+        let (closing, name) = tag
+            .strip_prefix("</")
+            .map_or((false, &tag[1..]), |name| (true, name));
+        let name: String = name
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        if closing {
+            match self.skipped.last_mut() {
+                Some((open, nested)) if *open == name && *nested > 0 => *nested -= 1,
+                _ => {
+                    if let Some(index) = self.skipped.iter().rposition(|(open, _)| *open == name) {
+                        self.skipped.truncate(index);
+                    }
+                }
+            }
+        } else if !tag.ends_with("/>") {
+            match self.skipped.last_mut() {
+                // A `<div>` inside a skipped `<div>` must not end it at its `</div>`
+                Some((open, nested)) if *open == name => *nested += 1,
+                _ if SKIPPED_ELEMENTS.contains(&name.as_str()) || has_skipped_class(tag) => {
+                    self.skipped.push((name, 0));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Returns true if `tag` has one of the `SKIPPED_CLASSES`.
+fn has_skipped_class(tag: &str) -> bool {
+    CLASS_REGEX.captures(tag).is_some_and(|captures| {
+        captures
+            .iter()
+            .skip(1)
+            .flatten()
+            .flat_map(|value| value.as_str().split_whitespace())
+            .any(|class| SKIPPED_CLASSES.contains(&class))
+    })
+}
+
+/// Returns the length of the tag `html` starts with, if it starts with one.
+///
+/// A `>` inside a quoted attribute value does not end the tag.
+fn tag_len(html: &str) -> Option<usize> {
+    let name = html.strip_prefix("</").or_else(|| html.strip_prefix('<'))?;
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut quote = None;
+    for (index, c) in html.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '>') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(scanner: &mut HtmlScanner, html: &'a str) -> Vec<&'a str> {
+        scanner
+            .scan(html)
+            .into_iter()
+            .map(|range| &html[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_scan() {
+        let mut scanner = HtmlScanner::default();
+        assert_eq!(
+            texts(
+                &mut scanner,
+                r#"<div title="API > REST">The API<br/> and <code>API</code> if a < b</div>"#
+            ),
+            ["The API", " and ", " if a < b"]
+        );
+        assert!(!scanner.in_skipped_element());
+    }
+
+    #[test]
+    fn test_scan_across_pieces() {
+        let mut scanner = HtmlScanner::default();
+        assert_eq!(texts(&mut scanner, "<script>"), Vec::<&str>::new());
+        assert!(scanner.in_skipped_element());
+        assert_eq!(texts(&mut scanner, "let api = 1;"), Vec::<&str>::new());
+        assert_eq!(texts(&mut scanner, "</SCRIPT>API"), ["API"]);
+
+        assert_eq!(texts(&mut scanner, "<!-- an API"), Vec::<&str>::new());
+        assert_eq!(texts(&mut scanner, "<b>REST</b> -->REST"), ["REST"]);
+        assert!(!scanner.in_skipped_element());
+    }
+
+    #[test]
+    fn test_scan_skipped_classes() {
+        let mut scanner = HtmlScanner::default();
+        let admonition =
+            r#"<div id="admonition-note" class="admonition admonish-note" role="note">"#;
+        assert_eq!(texts(&mut scanner, admonition), Vec::<&str>::new());
+        assert_eq!(
+            texts(&mut scanner, "<div class=admonition-title>Note</div>"),
+            Vec::<&str>::new()
+        );
+        assert!(scanner.in_skipped_element());
+        assert_eq!(texts(&mut scanner, "</div>API"), ["API"]);
+
+        assert_eq!(
+            texts(
+                &mut scanner,
+                "<pre class='mermaid'>graph TD; API</pre><div>API</div>"
+            ),
+            ["API"]
+        );
+        assert!(!scanner.in_skipped_element());
+    }
+}
@@ -0,0 +1,209 @@
+//! Usage statistics of glossary terms across a book, and the log of the links added.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::backlinks::ChapterRef;
+use crate::glossary::Term;
+use crate::linker::TermLink;
+use crate::paths;
+
+/// Version of the JSON shape, bumped on incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// How often each glossary term was linked, written to the `report` file.
+#[derive(Debug, Serialize)]
+pub struct UsageReport<'a> {
+    /// Version of this JSON shape.
+    version: u32,
+    /// Every glossary term, in glossary order.
+    terms: Vec<TermUsage<'a>>,
+    /// Names of the terms linked nowhere in the book.
+    unused: Vec<&'a str>,
+}
+
+/// The links to one term.
+#[derive(Debug, Serialize)]
+struct TermUsage<'a> {
+    /// Full term name as written in the glossary.
+    name: &'a str,
+    /// URL fragment of the term on its glossary page.
+    anchor: &'a str,
+    /// Links to the term in the whole book.
+    links: usize,
+    /// Chapters linking to the term, in book order.
+    chapters: Vec<ChapterUsage>,
+}
+
+/// The links to a term from one chapter.
+#[derive(Debug, Serialize)]
+struct ChapterUsage {
+    /// Chapter path relative to `src`.
+    path: String,
+    /// Links to the term in the chapter.
+    links: usize,
+}
+
+impl<'a> UsageReport<'a> {
+    /// Collects the usage of `terms`.
+    ///
+    /// `uses` maps term names to the chapters linking to them, in book order.
+    #[must_use]
+    pub fn new(terms: &'a [Term], uses: &HashMap<String, Vec<ChapterRef>>) -> Self {
+        let terms: Vec<TermUsage> = terms
+            .iter()
+            .map(|term| {
+                let chapters: Vec<ChapterUsage> = uses
+                    .get(term.name())
+                    .into_iter()
+                    .flatten()
+                    .map(|chapter| ChapterUsage {
+                        path: paths::to_url_path(&chapter.path),
+                        links: chapter.links,
+                    })
+                    .collect();
+                TermUsage {
+                    name: term.name(),
+                    anchor: term.anchor(),
+                    links: chapters.iter().map(|chapter| chapter.links).sum(),
+                    chapters,
+                }
+            })
+            .collect();
+        let unused = terms
+            .iter()
+            .filter(|term| term.links == 0)
+            .map(|term| term.name)
+            .collect();
+        Self {
+            version: FORMAT_VERSION,
+            terms,
+            unused,
+        }
+    }
+
+    /// Returns the names of the terms linked nowhere in the book.
+    #[must_use]
+    pub fn unused(&self) -> &[&'a str] {
+        &self.unused
+    }
+
+    /// Logs a one-line summary, and the links per term at debug level.
+    pub fn log_summary(&self) {
+        let linked = self.terms.len() - self.unused.len();
+        let links: usize = self.terms.iter().map(|term| term.links).sum();
+        log::info!(
+            "Linked {linked} of {} glossary terms ({links} links); {} never linked",
+            self.terms.len(),
+            self.unused.len()
+        );
+        for term in &self.terms {
+            log::debug!(
+                "Term '{}': {} links in {} chapters",
+                term.name,
+                term.links,
+                term.chapters.len()
+            );
+        }
+    }
+}
+
+/// One line of the `replacement-log` file: a link added to a chapter.
+#[derive(Debug, Serialize)]
+struct LoggedLink<'a> {
+    /// Chapter path relative to `src`.
+    chapter: String,
+    #[serde(flatten)]
+    link: &'a TermLink,
+}
+
+/// Appends `links`, added to the chapter at `chapter`, to `out` as JSON lines.
+///
+/// # Errors
+///
+/// Returns an error if a link cannot be serialized.
+pub fn log_links(out: &mut String, chapter: &Path, links: &[TermLink]) -> Result<()> {
+    let chapter = paths::to_url_path(chapter);
+    for link in links {
+        let line = LoggedLink {
+            chapter: chapter.clone(),
+            link,
+        };
+        out.push_str(&serde_json::to_string(&line)?);
+        out.push('\n');
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_usage_report() {
+        let terms = [Term::new("API"), Term::new("REST")];
+        let uses = HashMap::from([(
+            "API".to_string(),
+            vec![
+                ChapterRef {
+                    path: PathBuf::from("intro.md"),
+                    name: "Intro".to_string(),
+                    links: 2,
+                },
+                ChapterRef {
+                    path: PathBuf::from("guide/setup.md"),
+                    name: "Setup".to_string(),
+                    links: 1,
+                },
+            ],
+        )]);
+
+        let json = serde_json::to_value(UsageReport::new(&terms, &uses)).unwrap();
+        assert_eq!(json["unused"], serde_json::json!(["REST"]));
+        assert_eq!(json["version"], 1);
+        assert_eq!(json["terms"][0]["links"], 3);
+        assert_eq!(json["terms"][0]["chapters"][1]["path"], "guide/setup.md");
+        assert_eq!(json["terms"][1]["chapters"].as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_log_links() {
+        let links = [
+            TermLink {
+                term: "API (Application Programming Interface)".to_string(),
+                matched_text: "APIs".to_string(),
+                offset: 12,
+                href: Some("../glossary.html#api-application-programming-interface".to_string()),
+            },
+            TermLink {
+                term: "SLA".to_string(),
+                matched_text: "SLA".to_string(),
+                offset: 40,
+                href: None,
+            },
+        ];
+
+        let mut log = String::new();
+        log_links(&mut log, Path::new("guide/setup.md"), &links).unwrap();
+        let logged: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            logged[0],
+            serde_json::json!({
+                "chapter": "guide/setup.md",
+                "term": "API (Application Programming Interface)",
+                "matched_text": "APIs",
+                "offset": 12,
+                "href": "../glossary.html#api-application-programming-interface",
+            })
+        );
+        assert_eq!(logged[1]["href"], serde_json::Value::Null);
+    }
+}
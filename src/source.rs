@@ -0,0 +1,248 @@
+//! Loading a book directly from disk for the standalone subcommands.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use mdbook_preprocessor::book::{Book, Chapter};
+
+use crate::Config;
+use crate::progress::Progress;
+
+/// A book on disk, opened from the directory containing its `book.toml`.
+///
+/// mdBook hands the preprocessor a fully loaded [`Book`]; the standalone
+/// subcommands have to find the chapters themselves.
+#[derive(Debug)]
+pub struct BookSource {
+    /// Root directory of the book (containing `book.toml`).
+    root: PathBuf,
+    /// Directory holding the chapter sources (`book.src`, default `src`).
+    src_dir: PathBuf,
+    /// Directory mdBook renders the book to (`build.build-dir`, default `book`).
+    build_dir: PathBuf,
+    /// File the termlink configuration was read from.
+    config_file: PathBuf,
+    /// Whether `config_file` is a standalone termlink file rather than `book.toml`.
+    standalone_config: bool,
+    /// Termlink configuration.
+    config: Config,
+}
+
+impl BookSource {
+    /// Opens the book rooted at `root`.
+    ///
+    /// The termlink configuration is read from `[preprocessor.termlink]` in
+    /// `book.toml`, or from `config_file` when given. A standalone config file
+    /// holds the same keys at the top level, so books without a `book.toml`
+    /// (or with the configuration kept elsewhere) can still be processed; in
+    /// that case chapters are read from `<root>/src` if it exists, or `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `book.toml` or the config file cannot be read or parsed.
+    pub fn open(root: &Path, config_file: Option<&Path>) -> Result<Self> {
+        let book_toml_path = root.join("book.toml");
+        let book_toml = match config_file {
+            Some(_) if !book_toml_path.exists() => None,
+            _ => Some(read_toml(&book_toml_path)?),
+        };
+
+        let src_dir = match book_toml
+            .as_ref()
+            .and_then(|table| table.get("book"))
+            .and_then(|book| book.get("src"))
+            .and_then(toml::Value::as_str)
+        {
+            Some(src) => root.join(src),
+            None if book_toml.is_some() || root.join("src").is_dir() => root.join("src"),
+            None => root.to_path_buf(),
+        };
+
+        let build_dir = root.join(
+            book_toml
+                .as_ref()
+                .and_then(|table| table.get("build"))
+                .and_then(|build| build.get("build-dir"))
+                .and_then(toml::Value::as_str)
+                .unwrap_or("book"),
+        );
+
+        let (config, config_file, standalone_config) = if let Some(path) = config_file {
+            let config = Config::from_termlink_toml(&read_toml(path)?)?;
+            (config, path.to_path_buf(), true)
+        } else {
+            let config = book_toml
+                .as_ref()
+                .map(Config::from_book_toml)
+                .transpose()?
+                .unwrap_or_default();
+            (config, book_toml_path, false)
+        };
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            src_dir,
+            build_dir,
+            config_file,
+            standalone_config,
+            config,
+        })
+    }
+
+    /// Returns the root directory of the book.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the directory holding the chapter sources.
+    #[must_use]
+    pub fn src_dir(&self) -> &Path {
+        &self.src_dir
+    }
+
+    /// Returns the directory the book is rendered to.
+    #[must_use]
+    pub fn build_dir(&self) -> &Path {
+        &self.build_dir
+    }
+
+    /// Returns the termlink configuration of the book.
+    #[must_use]
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the path of the file holding the termlink configuration.
+    #[must_use]
+    pub fn config_file(&self) -> &Path {
+        &self.config_file
+    }
+
+    /// Returns the keys leading to the termlink table within [`Self::config_file`].
+    ///
+    /// Empty for a standalone config file, `["preprocessor", "termlink"]` for `book.toml`.
+    #[must_use]
+    pub const fn config_table_keys(&self) -> &'static [&'static str] {
+        if self.standalone_config {
+            &[]
+        } else {
+            &["preprocessor", "termlink"]
+        }
+    }
+
+    /// Returns the on-disk paths of the glossary pages.
+    ///
+    /// This is the configured glossary file, or every markdown file below it
+    /// when `glossary-path` names a directory (a glossary split across pages).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glossary directory cannot be read.
+    pub fn glossary_files(&self) -> Result<Vec<PathBuf>> {
+        let glossary_path = self.src_dir.join(self.config.glossary_path());
+        if !glossary_path.is_dir() {
+            return Ok(vec![glossary_path]);
+        }
+
+        let mut paths = Vec::new();
+        collect_markdown(&glossary_path, Path::new(""), &mut Vec::new(), &mut paths)?;
+        paths.sort();
+        Ok(paths.iter().map(|p| glossary_path.join(p)).collect())
+    }
+
+    /// Returns every markdown chapter below the `src` directory, relative to it.
+    ///
+    /// `SUMMARY.md` is skipped. Paths are sorted so output is stable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `src` directory cannot be read.
+    pub fn chapter_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        collect_markdown(&self.src_dir, Path::new(""), &mut Vec::new(), &mut paths)?;
+        paths.retain(|p| p != Path::new("SUMMARY.md"));
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Reads a chapter given its path relative to the `src` directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn read_chapter(&self, path: &Path) -> Result<String> {
+        let full_path = self.src_dir.join(path);
+        fs::read_to_string(&full_path)
+            .with_context(|| format!("Failed to read {}", full_path.display()))
+    }
+
+    /// Loads every chapter into a flat [`Book`] so the preprocessor pipeline can run on it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a chapter cannot be read.
+    pub fn load_book(&self) -> Result<Book> {
+        let paths = self.chapter_paths()?;
+        let chapters = paths.len();
+        let mut progress = Progress::new(chapters);
+        let mut book = Book::new();
+        for path in paths {
+            let content = self.read_chapter(&path)?;
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            progress.chapter_done(&path, 0);
+            book.push_item(Chapter::new(&name, content, path, Vec::new()));
+        }
+        progress.finish(&format!("Read {chapters} chapter(s)"));
+        Ok(book)
+    }
+}
+
+/// Reads and parses a TOML file.
+fn read_toml(path: &Path) -> Result<toml::Table> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    text.parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Recursively collects `.md` files below `dir`, recording paths relative to the `src` root.
+///
+/// Symlinked directories are followed and recorded under the link's path, as
+/// that is where mdBook renders them. `visited` holds the canonical paths of
+/// the directories being walked, so symlink cycles are skipped.
+fn collect_markdown(
+    dir: &Path,
+    relative: &Path,
+    visited: &mut Vec<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let canonical = fs::canonicalize(dir)
+        .with_context(|| format!("Failed to resolve directory {}", dir.display()))?;
+    if visited.contains(&canonical) {
+        log::warn!("Skipping symlink cycle at {}", dir.display());
+        return Ok(());
+    }
+    visited.push(canonical);
+
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("Failed to read directory {}", dir.display()))?;
+
+    for entry in entries {
+        let entry = entry?;
+        let relative_path = relative.join(entry.file_name());
+        // `Path::is_dir` follows symlinks, unlike `DirEntry::file_type`
+        if entry.path().is_dir() {
+            collect_markdown(&entry.path(), &relative_path, visited, paths)?;
+        } else if relative_path.extension().is_some_and(|ext| ext == "md") {
+            paths.push(relative_path);
+        }
+    }
+
+    visited.pop();
+    Ok(())
+}
@@ -0,0 +1,342 @@
+//! Loading glossary terms from a structured data file (`glossary-source = "file"`).
+//!
+//! This keeps the canonical term database separate from the rendered
+//! glossary chapter. The format is chosen by the file extension (TOML,
+//! YAML, a JSON array shaped like the YAML one, or CSV with a header row):
+//!
+//! ```toml
+//! # glossary.toml
+//! [[term]]
+//! name = "API (Application Programming Interface)"
+//! definition = "A set of protocols and tools for building software applications."
+//! aliases = ["apis"]
+//! ```
+//!
+//! ```yaml
+//! # glossary.yaml
+//! - name: API (Application Programming Interface)
+//!   definition: A set of protocols and tools for building software applications.
+//!   aliases: [apis]
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::config::CsvColumns;
+use crate::glossary::{Term, plain_definition, render_definition};
+
+/// One term as written in a term file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TermEntry {
+    /// Term name, with an optional short form: "API (Application Programming Interface)".
+    name: String,
+    /// Definition in markdown, shown as plain text in tooltips.
+    definition: Option<String>,
+    /// Alternative names matched in chapters.
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// Anchor of the entry on the glossary page, when it differs from the generated one.
+    anchor: Option<String>,
+    /// Where the definition comes from.
+    citation: Option<String>,
+    /// Category of the term.
+    category: Option<String>,
+    /// URL to link to instead of the glossary entry.
+    url: Option<String>,
+    /// Priority of the term's occurrences over overlapping ones of other terms.
+    #[serde(default)]
+    priority: i32,
+    /// Language of the entry, when it differs from the glossary's.
+    lang: Option<String>,
+}
+
+impl TermEntry {
+    /// Converts the entry into a term.
+    fn into_term(self) -> Result<Term> {
+        if self.name.trim().is_empty() {
+            bail!("Glossary file contains a term without a name");
+        }
+        let definition_html = self.definition.as_deref().map(render_definition);
+        let definition = self.definition.as_deref().and_then(plain_definition);
+        let mut term = Term::with_definition(self.name.trim(), definition)
+            .with_definition_html(definition_html)
+            .with_definition_markdown(self.definition.filter(|d| !d.trim().is_empty()))
+            .with_aliases(self.aliases)
+            .with_citation(self.citation)
+            .with_category(self.category)
+            .with_url(self.url)
+            .with_priority(self.priority)
+            .with_lang(self.lang);
+        if let Some(anchor) = self.anchor {
+            term = term.with_anchor(anchor);
+        }
+        Ok(term)
+    }
+}
+
+/// A TOML term file: an array of `[[term]]` tables.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlTermFile {
+    #[serde(default)]
+    term: Vec<TermEntry>,
+}
+
+/// Loads the terms of a TOML (`.toml`), YAML (`.yaml`, `.yml`), JSON (`.json`),
+/// or CSV (`.csv`) term file.
+///
+/// CSV columns are found by the header names in `columns`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, has an unsupported
+/// extension, or contains a term without a name.
+pub fn load_terms(path: &Path, columns: &CsvColumns) -> Result<Vec<Term>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read glossary file {}", path.display()))?;
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    parse_terms(&text, &extension, columns)
+        .with_context(|| format!("Failed to load glossary file {}", path.display()))
+}
+
+/// Parses term file contents in the format named by `extension`.
+fn parse_terms(text: &str, extension: &str, columns: &CsvColumns) -> Result<Vec<Term>> {
+    let entries: Vec<TermEntry> = match extension {
+        "toml" => toml::from_str::<TomlTermFile>(text).map(|file| file.term)?,
+        "yaml" | "yml" => serde_yaml_ng::from_str(text)?,
+        "json" => serde_json::from_str(text)?,
+        "csv" => parse_csv(text, columns)?,
+        _ => {
+            bail!("Unsupported format '.{extension}' (expected .toml, .yaml, .yml, .json, or .csv)")
+        }
+    };
+
+    entries.into_iter().map(TermEntry::into_term).collect()
+}
+
+/// Parses the rows of a CSV term file, locating fields by their header.
+///
+/// Only the name column is required; headers match case-insensitively and
+/// empty cells count as absent.
+fn parse_csv(text: &str, columns: &CsvColumns) -> Result<Vec<TermEntry>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = reader.headers()?.clone();
+    let find = |column: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(column))
+    };
+
+    let Some(name) = find(&columns.name) else {
+        bail!("CSV term file has no '{}' column", columns.name);
+    };
+    let definition = find(&columns.definition);
+    let aliases = find(&columns.aliases);
+    let anchor = find(&columns.anchor);
+    let citation = find(&columns.citation);
+    let category = find(&columns.category);
+    let url = find(&columns.url);
+    let priority = find(&columns.priority);
+    let lang = find(&columns.lang);
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|i| record.get(i))
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        // Skip blank rows spreadsheets tend to leave at the end
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        let priority = cell(priority)
+            .map(|value| {
+                value
+                    .parse()
+                    .with_context(|| format!("Invalid priority '{value}' in CSV term file"))
+            })
+            .transpose()?
+            .unwrap_or(0);
+        entries.push(TermEntry {
+            name: cell(Some(name)).unwrap_or_default(),
+            definition: cell(definition),
+            aliases: cell(aliases)
+                .map(|value| {
+                    value
+                        .split(columns.alias_separator.as_str())
+                        .map(str::trim)
+                        .filter(|alias| !alias.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            anchor: cell(anchor),
+            citation: cell(citation),
+            category: cell(category),
+            url: cell(url),
+            priority,
+            lang: cell(lang),
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_terms_toml() {
+        let terms = parse_terms(
+            r#"
+[[term]]
+name = "API (Application Programming Interface)"
+definition = "A set of protocols."
+aliases = ["apis"]
+
+[[term]]
+name = "REST"
+anchor = "rest-architecture"
+"#,
+            "toml",
+            &CsvColumns::default(),
+        )
+        .unwrap();
+
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].short_name(), Some("API"));
+        assert_eq!(terms[0].definition(), Some("A set of protocols."));
+        assert_eq!(terms[0].aliases(), ["apis"]);
+        assert_eq!(terms[0].anchor(), "api-application-programming-interface");
+        assert_eq!(terms[1].anchor(), "rest-architecture");
+    }
+
+    #[test]
+    fn test_parse_terms_yaml() {
+        let terms = parse_terms(
+            "- name: API\n  definition: A set of protocols.\n  category: Web\n- name: REST\n  priority: 5\n  lang: en\n",
+            "yaml",
+            &CsvColumns::default(),
+        )
+        .unwrap();
+
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].category(), Some("Web"));
+        assert_eq!(terms[0].priority(), 0);
+        assert_eq!(terms[1].definition(), None);
+        assert_eq!(terms[1].priority(), 5);
+        assert_eq!(terms[0].lang(), None);
+        assert_eq!(terms[1].lang(), Some("en"));
+    }
+
+    #[test]
+    fn test_parse_terms_markdown_definition() {
+        let terms = parse_terms(
+            "[[term]]\nname = \"XPT\"\ndefinition = \"The *SAS* `xport` format.\"\n",
+            "toml",
+            &CsvColumns::default(),
+        )
+        .unwrap();
+
+        assert_eq!(terms[0].definition(), Some("The SAS xport format."));
+        assert_eq!(
+            terms[0].definition_html(),
+            Some("<p>The <em>SAS</em> <code>xport</code> format.</p>")
+        );
+    }
+
+    #[test]
+    fn test_parse_terms_json() {
+        let terms = parse_terms(
+            r#"[
+                {"name": "API", "definition": "A set of protocols.", "aliases": ["apis"]},
+                {"name": "RFC 7231", "url": "https://www.rfc-editor.org/rfc/rfc7231"}
+            ]"#,
+            "json",
+            &CsvColumns::default(),
+        )
+        .unwrap();
+
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].aliases(), ["apis"]);
+        assert_eq!(terms[0].url(), None);
+        assert_eq!(
+            terms[1].url(),
+            Some("https://www.rfc-editor.org/rfc/rfc7231")
+        );
+    }
+
+    #[test]
+    fn test_parse_terms_csv_with_column_mapping() {
+        let columns: CsvColumns = toml::from_str(
+            "name = \"Term\"\ndefinition = \"Description\"\naliases = \"Synonyms\"\nalias-separator = \"|\"\n",
+        )
+        .unwrap();
+        let csv = "Term,Description,Synonyms,Owner\n\
+                   API,\"A set of protocols, and tools.\",apis | api endpoints,Docs\n\
+                   REST,,,\n\
+                   ,,,\n";
+        let terms = parse_terms(csv, "csv", &columns).unwrap();
+
+        assert_eq!(terms.len(), 2);
+        assert_eq!(
+            terms[0].definition(),
+            Some("A set of protocols, and tools.")
+        );
+        assert_eq!(terms[0].aliases(), ["apis", "api endpoints"]);
+        assert_eq!(terms[1].name(), "REST");
+        assert_eq!(terms[1].definition(), None);
+    }
+
+    #[test]
+    fn test_parse_terms_csv_priority() {
+        let terms = parse_terms(
+            "name,priority,lang\nREST,10,en\nAPI,,\n",
+            "csv",
+            &CsvColumns::default(),
+        )
+        .unwrap();
+        assert_eq!(terms[0].priority(), 10);
+        assert_eq!(terms[1].priority(), 0);
+        assert_eq!(terms[0].lang(), Some("en"));
+        assert_eq!(terms[1].lang(), None);
+
+        let result = parse_terms("name,priority\nREST,high\n", "csv", &CsvColumns::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_terms_csv_requires_name_column() {
+        let result = parse_terms("Term,Definition\nAPI,x\n", "csv", &CsvColumns::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_terms_rejects_unknown_fields_and_formats() {
+        assert!(
+            parse_terms(
+                "[[term]]\nname = \"API\"\ndefinitoin = \"x\"\n",
+                "toml",
+                &CsvColumns::default()
+            )
+            .is_err()
+        );
+        assert!(parse_terms("- name: \"\"\n", "yaml", &CsvColumns::default()).is_err());
+        assert!(parse_terms("API", "txt", &CsvColumns::default()).is_err());
+    }
+}
@@ -282,6 +282,10 @@ fn test_e2e_exclude_pages() {
         !html.contains("glossary-term"),
         "Excluded page should not have any glossary links"
     );
+    assert!(
+        !html.contains("exclude-terms"),
+        "Excluded page should not show its front matter"
+    );
 }
 
 // =============================================================================
@@ -305,3 +309,37 @@ fn test_e2e_alias_linking() {
         "Alias 'RESTful' should link to REST term anchor"
     );
 }
+
+// =============================================================================
+// Test 12: CLI Support Check (non-HTML renderers)
+// =============================================================================
+
+#[test]
+fn test_e2e_cli_supports_non_html_renderers() {
+    let binary = preprocessor_binary();
+
+    for renderer in ["epub", "markdown", "pandoc"] {
+        let output = Command::new(&binary)
+            .args(["supports", renderer])
+            .output()
+            .expect("Failed to run preprocessor binary");
+
+        assert!(
+            output.status.success(),
+            "Preprocessor should support {renderer} renderer (exit 0)"
+        );
+    }
+}
+
+// =============================================================================
+// Test 13: Front Matter on Pages Left Unlinked
+// =============================================================================
+
+#[test]
+fn test_e2e_glossary_page_front_matter() {
+    let html = read_html("reference/glossary.html");
+    assert!(
+        !html.contains("exclude-terms"),
+        "Glossary page should not show its front matter"
+    );
+}